@@ -18,6 +18,14 @@ pub enum Error {
     MissingBoostMint,
     #[error("missing pool url")]
     MissingPoolUrl,
+    #[error("missing max members")]
+    MissingMaxMembers,
+    #[error("missing claim cooldown")]
+    MissingClaimCooldown,
+    #[error("missing new authority")]
+    MissingNewAuthority,
+    #[error("missing pool")]
+    MissingPool,
     #[error("invalid command")]
     InvalidCommand,
 }
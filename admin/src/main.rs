@@ -2,15 +2,23 @@ use std::str::FromStr;
 
 use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::{
-    commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Keypair, signer::EncodableKey,
+    commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::{EncodableKey, Signer},
 };
 
+mod decommission;
 mod error;
 mod init;
 mod member_account;
+mod migrate;
 mod open_stake;
 mod pool_account;
 mod proof_account;
+mod set_claim_cooldown;
+mod set_max_members;
+mod transfer_authority;
 
 #[tokio::main]
 async fn main() -> Result<(), error::Error> {
@@ -20,13 +28,54 @@ async fn main() -> Result<(), error::Error> {
     let rpc_client = rpc_client()?;
     let boost_mint = boost_mint();
     let pool_url = pool_url();
+    let max_members = max_members();
+    let claim_cooldown = claim_cooldown();
+    // the pool is derived from the authority it was launched with,
+    // which is no longer the keypair once the pool has been transferred
+    let pool = pubkey_var("POOL");
+    let launched_pool = pool.unwrap_or_else(|| ore_pool_api::state::pool_pda(keypair.pubkey()).0);
     // run
     match command.as_str() {
         "init" => init::init(&rpc_client, &keypair, pool_url).await,
-        "open-stake" => open_stake::open_stake(&rpc_client, &keypair, boost_mint).await,
-        "pool-account" => pool_account::pool_account(&rpc_client, &keypair).await,
-        "proof-account" => proof_account::proof_account(&rpc_client, &keypair).await,
-        "member-account" => member_account::member_account(&rpc_client, &keypair).await,
+        "decommission" => decommission::decommission(&rpc_client, &keypair, launched_pool).await,
+        "migrate" => migrate::migrate(&rpc_client, &keypair, launched_pool).await,
+        "open-stake" => {
+            open_stake::open_stake(&rpc_client, &keypair, launched_pool, boost_mint).await
+        }
+        "pool-account" => pool_account::pool_account(&rpc_client, launched_pool).await,
+        "proof-account" => proof_account::proof_account(&rpc_client, launched_pool).await,
+        "set-max-members" => {
+            let max_members = max_members.ok_or(error::Error::MissingMaxMembers)?;
+            set_max_members::set_max_members(&rpc_client, &keypair, launched_pool, max_members)
+                .await
+        }
+        "set-claim-cooldown" => {
+            let claim_cooldown = claim_cooldown.ok_or(error::Error::MissingClaimCooldown)?;
+            set_claim_cooldown::set_claim_cooldown(
+                &rpc_client,
+                &keypair,
+                launched_pool,
+                claim_cooldown,
+            )
+            .await
+        }
+        "transfer-authority" => {
+            let new_authority = pubkey_var("NEW_AUTHORITY");
+            transfer_authority::transfer_authority(
+                &rpc_client,
+                &keypair,
+                launched_pool,
+                new_authority,
+            )
+            .await
+        }
+        "accept-authority" => {
+            // the accepting keypair never launched the pool, so it must be passed
+            transfer_authority::accept_authority(&rpc_client, &keypair, pool).await
+        }
+        "member-account" => {
+            member_account::member_account(&rpc_client, &keypair, launched_pool).await
+        }
         _ => Err(error::Error::InvalidCommand),
     }
 }
@@ -36,8 +85,12 @@ fn command() -> Result<String, error::Error> {
 }
 
 fn boost_mint() -> Option<Pubkey> {
-    std::env::var("MINT").ok().and_then(|mint| {
-        Pubkey::from_str(mint.as_str())
+    pubkey_var("MINT")
+}
+
+fn pubkey_var(name: &str) -> Option<Pubkey> {
+    std::env::var(name).ok().and_then(|pubkey| {
+        Pubkey::from_str(pubkey.as_str())
             .map_err(|err| {
                 println!("{:?}", err);
                 err
@@ -62,3 +115,26 @@ fn keypair() -> Result<Keypair, error::Error> {
 fn pool_url() -> Option<String> {
     std::env::var("POOL_URL").ok()
 }
+
+fn max_members() -> Option<u64> {
+    std::env::var("MAX_MEMBERS").ok().and_then(|max| {
+        max.parse()
+            .map_err(|err| {
+                println!("{:?}", err);
+                err
+            })
+            .ok()
+    })
+}
+
+fn claim_cooldown() -> Option<i64> {
+    std::env::var("CLAIM_COOLDOWN").ok().and_then(|cooldown| {
+        cooldown
+            .parse()
+            .map_err(|err| {
+                println!("{:?}", err);
+                err
+            })
+            .ok()
+    })
+}
@@ -1,6 +1,6 @@
 use ore_pool_api::state::Member;
 use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::{signature::Keypair, signer::Signer};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
 use steel::AccountDeserialize;
 
 use crate::error::Error;
@@ -9,8 +9,11 @@ use crate::error::Error;
 /// because this is where the operator commissions will be attributed.
 /// this command will fetch and print the address and decoded data of the member account.
 /// to manage this account (claim, stake, etc), use the ore-cli.
-pub async fn member_account(rpc_client: &RpcClient, keypair: &Keypair) -> Result<(), Error> {
-    let (pool_pda, _) = ore_pool_api::state::pool_pda(keypair.pubkey());
+pub async fn member_account(
+    rpc_client: &RpcClient,
+    keypair: &Keypair,
+    pool_pda: Pubkey,
+) -> Result<(), Error> {
     let (member_pda, _) = ore_pool_api::state::member_pda(keypair.pubkey(), pool_pda);
     println!("membda address: {:?}", member_pda);
     let data = rpc_client.get_account_data(&member_pda).await?;
@@ -0,0 +1,49 @@
+use ore_pool_api::state::Member;
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::RpcProgramAccountsConfig,
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::Transaction};
+use steel::Discriminator;
+
+use crate::error::Error;
+
+/// the number of member accounts migrated per transaction.
+const MEMBERS_PER_TX: usize = 20;
+
+/// grows the pool account and every member account of the pool
+/// that was written with an older, shorter layout.
+/// the pool is migrated with the first transaction, and is left as is by the rest.
+pub async fn migrate(rpc_client: &RpcClient, keypair: &Keypair, pool: Pubkey) -> Result<(), Error> {
+    let pubkey = keypair.pubkey();
+    // member accounts are prefixed by the discriminator and id, followed by the pool
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![
+            RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, vec![Member::discriminator()])),
+            RpcFilterType::Memcmp(Memcmp::new_raw_bytes(16, pool.to_bytes().to_vec())),
+        ]),
+        ..Default::default()
+    };
+    let members: Vec<Pubkey> = rpc_client
+        .get_program_accounts_with_config(&ore_pool_api::ID, config)
+        .await?
+        .into_iter()
+        .filter(|(_, account)| account.data.len() < 8 + std::mem::size_of::<Member>())
+        .map(|(address, _)| address)
+        .collect();
+    println!("migrating {} member accounts", members.len());
+    let mut batches: Vec<&[Pubkey]> = members.chunks(MEMBERS_PER_TX).collect();
+    if batches.is_empty() {
+        batches.push(&[]);
+    }
+    for batch in batches {
+        let ix = ore_pool_api::sdk::migrate(pubkey, pool, batch);
+        let mut tx = Transaction::new_with_payer(&[ix], Some(&pubkey));
+        let hash = rpc_client.get_latest_blockhash().await?;
+        tx.sign(&[keypair], hash);
+        let sig = rpc_client.send_and_confirm_transaction(&tx).await?;
+        println!("{:?}", sig);
+    }
+    Ok(())
+}
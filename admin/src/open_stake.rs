@@ -6,11 +6,12 @@ use crate::error::Error;
 pub async fn open_stake(
     rpc_client: &RpcClient,
     keypair: &Keypair,
+    pool: Pubkey,
     mint: Option<Pubkey>,
 ) -> Result<(), Error> {
     let mint = mint.ok_or(Error::MissingBoostMint)?;
     let pubkey = keypair.pubkey();
-    let ix = ore_pool_api::sdk::open_stake(pubkey, mint);
+    let ix = ore_pool_api::sdk::open_stake(pubkey, pool, mint);
     let mut tx = Transaction::new_with_payer(&[ix], Some(&pubkey));
     let hash = rpc_client.get_latest_blockhash().await?;
     tx.sign(&[keypair], hash);
@@ -0,0 +1,20 @@
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::Transaction};
+
+use crate::error::Error;
+
+pub async fn set_claim_cooldown(
+    rpc_client: &RpcClient,
+    keypair: &Keypair,
+    pool: Pubkey,
+    claim_cooldown: i64,
+) -> Result<(), Error> {
+    let pubkey = keypair.pubkey();
+    let ix = ore_pool_api::sdk::set_claim_cooldown(pubkey, pool, claim_cooldown);
+    let mut tx = Transaction::new_with_payer(&[ix], Some(&pubkey));
+    let hash = rpc_client.get_latest_blockhash().await?;
+    tx.sign(&[keypair], hash);
+    let sig = rpc_client.send_transaction(&tx).await?;
+    println!("{:?}", sig);
+    Ok(())
+}
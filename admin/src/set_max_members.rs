@@ -0,0 +1,20 @@
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::Transaction};
+
+use crate::error::Error;
+
+pub async fn set_max_members(
+    rpc_client: &RpcClient,
+    keypair: &Keypair,
+    pool: Pubkey,
+    max_members: u64,
+) -> Result<(), Error> {
+    let pubkey = keypair.pubkey();
+    let ix = ore_pool_api::sdk::set_max_members(pubkey, pool, max_members);
+    let mut tx = Transaction::new_with_payer(&[ix], Some(&pubkey));
+    let hash = rpc_client.get_latest_blockhash().await?;
+    tx.sign(&[keypair], hash);
+    let sig = rpc_client.send_transaction(&tx).await?;
+    println!("{:?}", sig);
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::Transaction};
+
+use crate::error::Error;
+
+pub async fn transfer_authority(
+    rpc_client: &RpcClient,
+    keypair: &Keypair,
+    pool: Pubkey,
+    new_authority: Option<Pubkey>,
+) -> Result<(), Error> {
+    let new_authority = new_authority.ok_or(Error::MissingNewAuthority)?;
+    let pubkey = keypair.pubkey();
+    let ix = ore_pool_api::sdk::transfer_authority(pubkey, pool, new_authority);
+    submit(rpc_client, keypair, ix).await
+}
+
+pub async fn accept_authority(
+    rpc_client: &RpcClient,
+    keypair: &Keypair,
+    pool: Option<Pubkey>,
+) -> Result<(), Error> {
+    let pool = pool.ok_or(Error::MissingPool)?;
+    let ix = ore_pool_api::sdk::accept_authority(keypair.pubkey(), pool);
+    submit(rpc_client, keypair, ix).await
+}
+
+async fn submit(
+    rpc_client: &RpcClient,
+    keypair: &Keypair,
+    ix: solana_sdk::instruction::Instruction,
+) -> Result<(), Error> {
+    let pubkey = keypair.pubkey();
+    let mut tx = Transaction::new_with_payer(&[ix], Some(&pubkey));
+    let hash = rpc_client.get_latest_blockhash().await?;
+    tx.sign(&[keypair], hash);
+    let sig = rpc_client.send_transaction(&tx).await?;
+    println!("{:?}", sig);
+    Ok(())
+}
@@ -1,3 +1,6 @@
+/// The claim amount that claims the member's entire balance.
+pub const CLAIM_ALL: u64 = u64::MAX;
+
 /// The seed of the member account PDA.
 pub const MEMBER: &[u8] = b"member";
 
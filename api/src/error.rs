@@ -7,6 +7,14 @@ pub enum PoolError {
     MissingMiningReward = 0,
     #[error("Could not parse mining reward")]
     CouldNotParseMiningReward = 1,
+    #[error("Pool is closed")]
+    PoolClosed = 2,
+    #[error("Claim amount exceeds member balance")]
+    InsufficientBalance = 3,
+    #[error("Pool is full")]
+    PoolFull = 4,
+    #[error("Claim cooldown has not elapsed")]
+    ClaimCooldown = 5,
 }
 
 #[derive(Debug, Error)]
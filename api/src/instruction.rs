@@ -9,6 +9,7 @@ pub enum PoolInstruction {
     OpenShare = 2,
     Stake = 3,
     Unstake = 4,
+    Deregister = 5,
 
     // Operator
     Attribute = 100,
@@ -16,8 +17,19 @@ pub enum PoolInstruction {
     Launch = 102,
     OpenStake = 103,
     Submit = 104,
+    Decommission = 105,
+    Compound = 106,
+    SetMaxMembers = 107,
+    TransferAuthority = 108,
+    AcceptAuthority = 109,
+    SetClaimCooldown = 110,
+    Migrate = 111,
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct AcceptAuthority {}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct Attribute {
@@ -35,6 +47,20 @@ pub struct Claim {
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct Commit {}
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct Compound {
+    pub amount: [u8; 8],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct Decommission {}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct Deregister {}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct Launch {
@@ -43,6 +69,10 @@ pub struct Launch {
     pub url: [u8; 128],
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct Migrate {}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct OpenShare {
@@ -59,6 +89,18 @@ pub struct Join {
     pub member_bump: u8,
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct SetClaimCooldown {
+    pub claim_cooldown: [u8; 8],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct SetMaxMembers {
+    pub max_members: [u8; 8],
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct Stake {
@@ -73,19 +115,33 @@ pub struct Submit {
     pub nonce: [u8; 8],
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct TransferAuthority {
+    pub new_authority: [u8; 32],
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct Unstake {
     pub amount: [u8; 8],
 }
 
+instruction!(PoolInstruction, AcceptAuthority);
 instruction!(PoolInstruction, Attribute);
 instruction!(PoolInstruction, Claim);
 instruction!(PoolInstruction, Commit);
+instruction!(PoolInstruction, Compound);
+instruction!(PoolInstruction, Decommission);
+instruction!(PoolInstruction, Deregister);
 instruction!(PoolInstruction, Launch);
+instruction!(PoolInstruction, Migrate);
 instruction!(PoolInstruction, OpenShare);
 instruction!(PoolInstruction, OpenStake);
 instruction!(PoolInstruction, Join);
+instruction!(PoolInstruction, SetClaimCooldown);
+instruction!(PoolInstruction, SetMaxMembers);
 instruction!(PoolInstruction, Stake);
 instruction!(PoolInstruction, Submit);
+instruction!(PoolInstruction, TransferAuthority);
 instruction!(PoolInstruction, Unstake);
@@ -1,8 +1,9 @@
 use drillx::Solution;
-use ore_api::consts::{CONFIG_ADDRESS, TREASURY_ADDRESS, TREASURY_TOKENS_ADDRESS};
+use ore_api::consts::{CONFIG_ADDRESS, MINT_ADDRESS, TREASURY_ADDRESS, TREASURY_TOKENS_ADDRESS};
 use steel::*;
 
 use crate::{
+    consts::CLAIM_ALL,
     error::ApiError,
     instruction::*,
     state::{member_pda, pool_pda, pool_proof_pda, share_pda},
@@ -52,14 +53,35 @@ pub fn join(member_authority: Pubkey, pool: Pubkey, payer: Pubkey) -> Instructio
     }
 }
 
+/// Builds a deregister instruction.
+pub fn deregister(signer: Pubkey, pool: Pubkey) -> Instruction {
+    let (member_pda, _) = member_pda(signer, pool);
+    Instruction {
+        program_id: crate::ID,
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new(member_pda, false),
+            AccountMeta::new(pool, false),
+        ],
+        data: Deregister {}.to_bytes(),
+    }
+}
+
 /// Builds a claim instruction.
+/// The beneficiary may be any ORE token account, e.g. a custody or exchange deposit account.
+/// If none is provided, claims to the signer's associated token account.
+/// If no amount is provided, claims the entire balance.
 pub fn claim(
     signer: Pubkey,
-    beneficiary: Pubkey,
+    beneficiary: Option<Pubkey>,
     pool_pda: Pubkey,
     pool_bump: u8,
-    amount: u64,
+    amount: Option<u64>,
 ) -> Instruction {
+    let amount = amount.unwrap_or(CLAIM_ALL);
+    let beneficiary = beneficiary.unwrap_or_else(|| {
+        spl_associated_token_account::get_associated_token_address(&signer, &MINT_ADDRESS)
+    });
     let (member_pda, _) = member_pda(signer, pool_pda);
     let (pool_proof_pda, _) = pool_proof_pda(pool_pda);
     Instruction {
@@ -84,8 +106,12 @@ pub fn claim(
 }
 
 /// Builds an attribute instruction.
-pub fn attribute(signer: Pubkey, member_authority: Pubkey, total_balance: u64) -> Instruction {
-    let (pool_pda, _) = pool_pda(signer);
+pub fn attribute(
+    signer: Pubkey,
+    pool_pda: Pubkey,
+    member_authority: Pubkey,
+    total_balance: u64,
+) -> Instruction {
     let (member_pda, _) = member_pda(member_authority, pool_pda);
     Instruction {
         program_id: crate::ID,
@@ -102,11 +128,10 @@ pub fn attribute(signer: Pubkey, member_authority: Pubkey, total_balance: u64) -
 }
 
 /// Builds a commit instruction.
-pub fn commit(signer: Pubkey, mint: Pubkey) -> Instruction {
+pub fn commit(signer: Pubkey, pool_pda: Pubkey, mint: Pubkey) -> Instruction {
     let (boost_pda, _) = ore_boost_api::state::boost_pda(mint);
     let boost_tokens =
         spl_associated_token_account::get_associated_token_address(&boost_pda, &mint);
-    let (pool_pda, _) = pool_pda(signer);
     let pool_tokens = spl_associated_token_account::get_associated_token_address(&pool_pda, &mint);
     let (stake_pda, _) = ore_boost_api::state::stake_pda(pool_pda, boost_pda);
     Instruction {
@@ -126,15 +151,148 @@ pub fn commit(signer: Pubkey, mint: Pubkey) -> Instruction {
     }
 }
 
+/// Builds a compound instruction,
+/// restaking the member's balance into their ORE share on their behalf.
+/// If no amount is provided, compounds the entire balance.
+pub fn compound(
+    signer: Pubkey,
+    pool_pda: Pubkey,
+    member_authority: Pubkey,
+    amount: Option<u64>,
+) -> Instruction {
+    let amount = amount.unwrap_or(CLAIM_ALL);
+    let (member_pda, _) = member_pda(member_authority, pool_pda);
+    let (pool_proof_pda, _) = pool_proof_pda(pool_pda);
+    let (share_pda, _) = share_pda(member_authority, pool_pda, MINT_ADDRESS);
+    let pool_tokens =
+        spl_associated_token_account::get_associated_token_address(&pool_pda, &MINT_ADDRESS);
+    Instruction {
+        program_id: crate::ID,
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new(member_pda, false),
+            AccountMeta::new(pool_pda, false),
+            AccountMeta::new(pool_tokens, false),
+            AccountMeta::new(pool_proof_pda, false),
+            AccountMeta::new(share_pda, false),
+            AccountMeta::new_readonly(TREASURY_ADDRESS, false),
+            AccountMeta::new(TREASURY_TOKENS_ADDRESS, false),
+            AccountMeta::new_readonly(ore_api::ID, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+        ],
+        data: Compound {
+            amount: amount.to_le_bytes(),
+        }
+        .to_bytes(),
+    }
+}
+
+/// Builds a decommission instruction.
+pub fn decommission(signer: Pubkey, pool_pda: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new(pool_pda, false),
+        ],
+        data: Decommission {}.to_bytes(),
+    }
+}
+
+/// Builds a migrate instruction, growing the pool and the given member accounts
+/// of the pool to the current layout.
+pub fn migrate(signer: Pubkey, pool: Pubkey, members: &[Pubkey]) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(signer, true),
+        AccountMeta::new(pool, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+    accounts.extend(
+        members
+            .iter()
+            .map(|member| AccountMeta::new(*member, false)),
+    );
+    Instruction {
+        program_id: crate::ID,
+        accounts,
+        data: Migrate {}.to_bytes(),
+    }
+}
+
+/// Builds a transfer authority instruction, proposing a new authority for the pool.
+/// The pool is passed explicitly, since it is derived from the authority it was launched with,
+/// which may no longer be the signer. The default pubkey cancels a pending transfer.
+pub fn transfer_authority(signer: Pubkey, pool: Pubkey, new_authority: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new(pool, false),
+        ],
+        data: TransferAuthority {
+            new_authority: new_authority.to_bytes(),
+        }
+        .to_bytes(),
+    }
+}
+
+/// Builds an accept authority instruction, signed by the proposed authority.
+/// The pool proof's miner is handed to the new authority too.
+pub fn accept_authority(signer: Pubkey, pool: Pubkey) -> Instruction {
+    let (proof_pda, _) = pool_proof_pda(pool);
+    Instruction {
+        program_id: crate::ID,
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new(pool, false),
+            AccountMeta::new(proof_pda, false),
+            AccountMeta::new_readonly(ore_api::ID, false),
+        ],
+        data: AcceptAuthority {}.to_bytes(),
+    }
+}
+
+/// Builds a set claim cooldown instruction.
+/// Zero lets members claim as often as they like.
+pub fn set_claim_cooldown(signer: Pubkey, pool_pda: Pubkey, claim_cooldown: i64) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new(pool_pda, false),
+        ],
+        data: SetClaimCooldown {
+            claim_cooldown: claim_cooldown.to_le_bytes(),
+        }
+        .to_bytes(),
+    }
+}
+
+/// Builds a set max members instruction.
+/// Zero removes the cap.
+pub fn set_max_members(signer: Pubkey, pool_pda: Pubkey, max_members: u64) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new(pool_pda, false),
+        ],
+        data: SetMaxMembers {
+            max_members: max_members.to_le_bytes(),
+        }
+        .to_bytes(),
+    }
+}
+
 /// Builds an submit instruction.
 pub fn submit(
     signer: Pubkey,
+    pool_pda: Pubkey,
     solution: Solution,
     attestation: [u8; 32],
     bus: Pubkey,
     boost_accounts: Vec<Pubkey>,
 ) -> Instruction {
-    let (pool_pda, _) = pool_pda(signer);
     let (proof_pda, _) = pool_proof_pda(pool_pda);
     let accounts = vec![
         AccountMeta::new(signer, true),
@@ -253,9 +411,8 @@ pub fn open_share(signer: Pubkey, mint: Pubkey, pool: Pubkey) -> Instruction {
 }
 
 /// Builds an open stake instruction.
-pub fn open_stake(signer: Pubkey, mint: Pubkey) -> Instruction {
+pub fn open_stake(signer: Pubkey, pool_pda: Pubkey, mint: Pubkey) -> Instruction {
     let (boost_pda, _) = ore_boost_api::state::boost_pda(mint);
-    let (pool_pda, _) = pool_pda(signer);
     let pool_tokens = spl_associated_token_account::get_associated_token_address(&pool_pda, &mint);
     let (stake_pda, _) = ore_boost_api::state::stake_pda(pool_pda, boost_pda);
     Instruction {
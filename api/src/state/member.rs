@@ -20,6 +20,27 @@ pub struct Member {
 
     /// The total balance this member has earned in the lifetime of their participation in the pool.
     pub total_balance: u64,
+
+    /// Whether or not this member has deregistered from the pool (1 if deregistered).
+    /// A deregistered member may still claim their remaining balance.
+    pub deregistered: u64,
+
+    /// The unix timestamp of this member's last claim (0 if never claimed).
+    pub last_claim_at: i64,
+}
+
+impl Member {
+    /// The seconds left until this member may claim again (0 if it may claim now).
+    /// A member that has never claimed may always claim.
+    pub fn claim_cooldown_remaining(&self, claim_cooldown: i64, now: i64) -> i64 {
+        if self.last_claim_at == 0 {
+            return 0;
+        }
+        self.last_claim_at
+            .saturating_add(claim_cooldown)
+            .saturating_sub(now)
+            .max(0)
+    }
 }
 
 account!(AccountDiscriminator, Member);
@@ -32,6 +32,39 @@ pub struct Pool {
 
     // The total number of members in this pool at the last submission.
     pub last_total_members: u64,
+
+    /// Whether or not this pool has been decommissioned (1 if closed).
+    /// A closed pool accepts no new members or stake, but members may still claim.
+    pub closed: u64,
+
+    /// The number of members that have not deregistered.
+    /// Member ids are never reused, so this may be less than total members.
+    pub active_members: u64,
+
+    /// The max number of active members this pool accepts (0 if uncapped).
+    pub max_members: u64,
+
+    /// The authority proposed by the current authority, until it accepts (0s if none).
+    pub pending_authority: Pubkey,
+
+    /// The authority this pool's address was derived from,
+    /// set when the authority is first transferred (0s if never transferred).
+    pub seed_authority: Pubkey,
+
+    /// The min number of seconds between claims of a member (0 if unlimited).
+    pub claim_cooldown: i64,
+}
+
+impl Pool {
+    /// The authority to derive this pool's address and sign its CPIs with,
+    /// which outlives transfers of the authority.
+    pub fn seed_authority(&self) -> Pubkey {
+        if self.seed_authority == Pubkey::default() {
+            self.authority
+        } else {
+            self.seed_authority
+        }
+    }
 }
 
 account!(AccountDiscriminator, Pool);
@@ -11,12 +11,31 @@ BEGIN
           is_approved BOOLEAN NOT NULL,
           is_kyc BOOLEAN NOT NULL,
           is_synced BOOLEAN NOT NULL,
+          is_active BOOLEAN NOT NULL DEFAULT TRUE, -- false once the member has deregistered
           CONSTRAINT unique_member_id UNIQUE (id)
         );
     END IF;
 END
 $$;
 
+-- add is-active column to existing members tables
+ALTER TABLE members ADD COLUMN IF NOT EXISTS is_active BOOLEAN NOT NULL DEFAULT TRUE;
+
+-- add auto-compound column to existing members tables
+ALTER TABLE members ADD COLUMN IF NOT EXISTS auto_compound BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- add carry-over column to existing members tables,
+-- the attributions held back below the min attribution
+ALTER TABLE members ADD COLUMN IF NOT EXISTS carry_over BIGINT NOT NULL DEFAULT 0;
+
+-- add first-seen column to existing members tables,
+-- the last-hash-at of the round the member first contributed to
+ALTER TABLE members ADD COLUMN IF NOT EXISTS first_seen_at BIGINT;
+
+-- add registered-at column to existing members tables,
+-- the unix timestamp the member registered with the server (null if registered before the column existed)
+ALTER TABLE members ADD COLUMN IF NOT EXISTS registered_at BIGINT;
+
 -- create stakers table
 DO $$
 BEGIN
@@ -31,3 +50,51 @@ BEGIN
     END IF;
 END
 $$;
+
+
+-- create balance events table
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM information_schema.tables WHERE table_name = 'balance_events') THEN
+        CREATE TABLE balance_events (
+          address VARCHAR NOT NULL, -- address of member account
+          last_hash_at BIGINT NOT NULL, -- foreign key to the ore proof account, identifies the round
+          amount BIGINT NOT NULL, -- total balance earned in the round
+          PRIMARY KEY (address, last_hash_at)
+        );
+    END IF;
+END
+$$;
+
+-- drop the members foreign key from existing balance_events tables,
+-- an event for a member without a members row would otherwise fail the whole attribution
+ALTER TABLE balance_events DROP CONSTRAINT IF EXISTS balance_events_address_fkey;
+
+-- create attestations table
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM information_schema.tables WHERE table_name = 'attestations') THEN
+        CREATE TABLE attestations (
+          last_hash_at BIGINT PRIMARY KEY, -- foreign key to the ore proof account
+          url VARCHAR NOT NULL -- url of the attestation block published to s3
+        );
+    END IF;
+END
+$$;
+
+-- add compressed column to existing attestations tables,
+-- whether the published block is gzip-compressed (blocks published before the column existed are not)
+ALTER TABLE attestations ADD COLUMN IF NOT EXISTS compressed BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- create member access table,
+-- the authorities allowed or denied registration and contributions
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM information_schema.tables WHERE table_name = 'member_access') THEN
+        CREATE TABLE member_access (
+          authority VARCHAR PRIMARY KEY, -- member authority
+          list VARCHAR NOT NULL -- allow or deny
+        );
+    END IF;
+END
+$$;
\ No newline at end of file
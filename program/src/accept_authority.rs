@@ -0,0 +1,40 @@
+use ore_pool_api::prelude::*;
+use steel::*;
+
+/// AcceptAuthority completes a transfer of the pool authority, signed by the proposed authority.
+/// The pool keeps the address derived from its original authority, see [`Pool::seed_authority`].
+/// The pool proof's miner is handed to the new authority too, since it signs the submits.
+pub fn process_accept_authority(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResult {
+    // Load accounts.
+    let [signer_info, pool_info, proof_info, ore_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    signer_info.is_signer()?;
+    // No one can sign for the default pubkey, so there must be a pending transfer.
+    let pool = pool_info
+        .is_writable()?
+        .to_account_mut::<Pool>(&ore_pool_api::ID)?
+        .check_mut(|p| p.pending_authority == *signer_info.key)?;
+    let seed_authority = pool.seed_authority();
+    pool_info.has_seeds(
+        &[POOL, seed_authority.as_ref()],
+        pool.bump as u8,
+        &ore_pool_api::ID,
+    )?;
+    proof_info.is_writable()?;
+    ore_program.is_program(&ore_api::ID)?;
+
+    // Pin the seed authority before the authority it falls back to is replaced.
+    pool.seed_authority = seed_authority;
+    pool.authority = *signer_info.key;
+    pool.pending_authority = Pubkey::default();
+
+    // Hand the proof's miner to the new authority.
+    solana_program::program::invoke_signed(
+        &ore_api::sdk::update(*pool_info.key, *signer_info.key),
+        &[pool_info.clone(), signer_info.clone(), proof_info.clone()],
+        &[&[POOL, seed_authority.as_ref(), &[pool.bump as u8]]],
+    )?;
+
+    Ok(())
+}
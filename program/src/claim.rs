@@ -2,7 +2,8 @@ use ore_api::prelude::*;
 use ore_pool_api::prelude::*;
 use steel::*;
 
-/// Claim allows a member to claim their ORE rewards from the pool.
+/// Claim allows a member to claim their ORE rewards from the pool
+/// to any ORE token account, not only their associated token account.
 pub fn process_claim(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult {
     // Parse args.
     let args = ore_pool_api::instruction::Claim::try_from_bytes(data)?;
@@ -17,6 +18,7 @@ pub fn process_claim(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult
     signer_info.is_signer()?;
     beneficiary_info
         .is_writable()?
+        .has_owner(&spl_token::ID)?
         .to_token_account()?
         .check(|t| t.mint == MINT_ADDRESS)?;
     let member = member_info
@@ -27,11 +29,17 @@ pub fn process_claim(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult
     ore_program.is_program(&ore_api::ID)?;
     token_program.is_program(&spl_token::ID)?;
 
+    // Reject claims until the pool's cooldown has elapsed.
+    let clock = Clock::get()?;
+    check_claim_cooldown(member, pool.claim_cooldown, clock.unix_timestamp)?;
+
     // Update member balance
-    member.balance = member.balance.checked_sub(amount).unwrap();
+    let amount = claim_amount(member.balance, amount)?;
+    member.balance -= amount;
+    member.last_claim_at = clock.unix_timestamp;
 
     // Claim tokens to the beneficiary
-    let pool_authority = pool.authority;
+    let pool_authority = pool.seed_authority();
     solana_program::program::invoke_signed(
         &ore_api::sdk::claim(*pool_info.key, *beneficiary_info.key, amount),
         &[
@@ -47,3 +55,82 @@ pub fn process_claim(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult
 
     Ok(())
 }
+
+/// The amount to claim, the entire balance if [`CLAIM_ALL`] is requested.
+/// Errors if the requested amount exceeds the balance.
+pub(crate) fn claim_amount(balance: u64, requested: u64) -> Result<u64, PoolError> {
+    if requested == CLAIM_ALL {
+        return Ok(balance);
+    }
+    if requested > balance {
+        return Err(PoolError::InsufficientBalance);
+    }
+    Ok(requested)
+}
+
+/// Checks the cooldown since the member's last claim has elapsed.
+pub(crate) fn check_claim_cooldown(
+    member: &Member,
+    claim_cooldown: i64,
+    now: i64,
+) -> Result<(), PoolError> {
+    if member.claim_cooldown_remaining(claim_cooldown, now) > 0 {
+        return Err(PoolError::ClaimCooldown);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn claim_exact_balance() {
+        assert_eq!(claim_amount(100, 100), Ok(100));
+    }
+
+    #[test]
+    fn claim_partial_balance() {
+        assert_eq!(claim_amount(100, 40), Ok(40));
+    }
+
+    #[test]
+    fn claim_over_balance() {
+        assert_eq!(claim_amount(100, 101), Err(PoolError::InsufficientBalance));
+    }
+
+    #[test]
+    fn claim_all() {
+        assert_eq!(claim_amount(100, CLAIM_ALL), Ok(100));
+    }
+
+    fn member(last_claim_at: i64) -> Member {
+        let mut member = Member::zeroed();
+        member.last_claim_at = last_claim_at;
+        member
+    }
+
+    #[test]
+    fn first_claim_is_allowed() {
+        assert_eq!(check_claim_cooldown(&member(0), 3_600, 1_000), Ok(()));
+    }
+
+    #[test]
+    fn immediate_reclaim_is_rejected() {
+        assert_eq!(
+            check_claim_cooldown(&member(1_000), 3_600, 1_000),
+            Err(PoolError::ClaimCooldown)
+        );
+        assert_eq!(
+            check_claim_cooldown(&member(1_000), 3_600, 4_599),
+            Err(PoolError::ClaimCooldown)
+        );
+    }
+
+    #[test]
+    fn claim_after_cooldown_is_allowed() {
+        assert_eq!(check_claim_cooldown(&member(1_000), 3_600, 4_600), Ok(()));
+        // no cooldown
+        assert_eq!(check_claim_cooldown(&member(1_000), 0, 1_000), Ok(()));
+    }
+}
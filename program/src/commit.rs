@@ -40,7 +40,7 @@ pub fn process_commit(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResu
             stake_info.clone(),
             spl_token_program.clone(),
         ],
-        &[&[POOL, signer_info.key.as_ref(), &[pool.bump as u8]]],
+        &[&[POOL, pool.seed_authority().as_ref(), &[pool.bump as u8]]],
     )?;
 
     Ok(())
@@ -0,0 +1,130 @@
+use ore_api::prelude::*;
+use ore_pool_api::prelude::*;
+use steel::*;
+
+use crate::{claim::claim_amount, stake::merge_stake};
+
+/// Compound restakes a member's ORE rewards into their ORE share on their behalf.
+/// Signed by the pool authority for members that opted in with the operator.
+/// The balance can only move into the member's own share, so the operator can't redirect it,
+/// and the member may unstake it as usual.
+pub fn process_compound(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult {
+    // Parse args.
+    let args = ore_pool_api::instruction::Compound::try_from_bytes(data)?;
+    let amount = u64::from_le_bytes(args.amount);
+
+    // Load accounts.
+    let [signer_info, member_info, pool_info, pool_tokens_info, proof_info, share_info, treasury_info, treasury_tokens_info, ore_program, token_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    signer_info.is_signer()?;
+    let member = member_info
+        .to_account_mut::<Member>(&ore_pool_api::ID)?
+        .check_mut(|m| m.pool == *pool_info.key)?;
+    let pool = pool_info
+        .to_account::<Pool>(&ore_pool_api::ID)?
+        .check(|p| p.authority == *signer_info.key)?;
+    pool_tokens_info
+        .is_writable()?
+        .to_associated_token_account(pool_info.key, &MINT_ADDRESS)?;
+    let share = share_info
+        .to_account_mut::<Share>(&ore_pool_api::ID)?
+        .check_mut(|s| s.authority == member.authority)?
+        .check_mut(|s| s.pool == *pool_info.key)?
+        .check_mut(|s| s.mint == MINT_ADDRESS)?;
+    ore_program.is_program(&ore_api::ID)?;
+    token_program.is_program(&spl_token::ID)?;
+
+    // Reject new stake if the pool is closed.
+    if pool.closed == 1 {
+        return Err(PoolError::PoolClosed.into());
+    }
+
+    // Move the member balance into their share.
+    let amount = compound(member, share, amount)?;
+
+    // Claim tokens into the pool's pending stake account,
+    // to be deposited into the boost on the next commit.
+    let pool_authority = pool.seed_authority();
+    solana_program::program::invoke_signed(
+        &ore_api::sdk::claim(*pool_info.key, *pool_tokens_info.key, amount),
+        &[
+            pool_info.clone(),
+            pool_tokens_info.clone(),
+            proof_info.clone(),
+            treasury_info.clone(),
+            treasury_tokens_info.clone(),
+            token_program.clone(),
+        ],
+        &[&[POOL, pool_authority.as_ref(), &[pool.bump as u8]]],
+    )?;
+
+    Ok(())
+}
+
+/// Debits the member balance and credits the share, returning the amount moved.
+/// The entire balance if [`CLAIM_ALL`] is requested.
+fn compound(member: &mut Member, share: &mut Share, requested: u64) -> Result<u64, ProgramError> {
+    let amount = claim_amount(member.balance, requested)?;
+    merge_stake(share, amount)?;
+    member.balance -= amount;
+    Ok(amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accounts(balance: u64) -> (Member, Share) {
+        let authority = Pubkey::new_unique();
+        let pool = Pubkey::new_unique();
+        let member = Member {
+            id: 0,
+            pool,
+            authority,
+            balance,
+            total_balance: balance,
+            deregistered: 0,
+            last_claim_at: 0,
+        };
+        let share = Share {
+            authority,
+            balance: 50,
+            mint: MINT_ADDRESS,
+            pool,
+        };
+        (member, share)
+    }
+
+    #[test]
+    fn compound_moves_balance_into_share() {
+        let (mut member, mut share) = accounts(100);
+        assert_eq!(compound(&mut member, &mut share, CLAIM_ALL), Ok(100));
+        assert_eq!(member.balance, 0);
+        assert_eq!(share.balance, 150);
+    }
+
+    #[test]
+    fn compound_exceeding_balance_is_rejected() {
+        let (mut member, mut share) = accounts(100);
+        assert_eq!(
+            compound(&mut member, &mut share, 101),
+            Err(PoolError::InsufficientBalance.into())
+        );
+        assert_eq!(member.balance, 100);
+        assert_eq!(share.balance, 50);
+    }
+
+    #[test]
+    fn overflowing_compound_leaves_balance() {
+        let (mut member, mut share) = accounts(100);
+        share.balance = u64::MAX;
+        assert_eq!(
+            compound(&mut member, &mut share, 100),
+            Err(ProgramError::ArithmeticOverflow)
+        );
+        assert_eq!(member.balance, 100);
+    }
+}
@@ -0,0 +1,26 @@
+use ore_pool_api::prelude::*;
+use steel::*;
+
+/// Decommission closes a pool to new members and stake.
+/// Members may still claim their remaining balances.
+pub fn process_decommission(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResult {
+    // Load accounts.
+    let [signer_info, pool_info] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    signer_info.is_signer()?;
+    let pool = pool_info
+        .is_writable()?
+        .to_account_mut::<Pool>(&ore_pool_api::ID)?
+        .check_mut(|p| p.authority == *signer_info.key)?;
+    pool_info.has_seeds(
+        &[POOL, pool.seed_authority().as_ref()],
+        pool.bump as u8,
+        &ore_pool_api::ID,
+    )?;
+
+    // Mark pool as closed.
+    pool.closed = 1;
+
+    Ok(())
+}
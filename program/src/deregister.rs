@@ -0,0 +1,33 @@
+use ore_pool_api::prelude::*;
+use steel::*;
+
+/// Deregister removes a member from the pool's active member count.
+/// The member account is kept so that any remaining balance may still be claimed.
+pub fn process_deregister(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResult {
+    // Load accounts.
+    let [signer_info, member_info, pool_info] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    signer_info.is_signer()?;
+    let member = member_info
+        .is_writable()?
+        .to_account_mut::<Member>(&ore_pool_api::ID)?
+        .check_mut(|m| m.authority == *signer_info.key)?
+        .check_mut(|m| m.pool == *pool_info.key)?;
+    let pool = pool_info
+        .is_writable()?
+        .to_account_mut::<Pool>(&ore_pool_api::ID)?;
+
+    // Deregistering twice is a no-op.
+    if member.deregistered == 1 {
+        return Ok(());
+    }
+
+    // Mark member as deregistered.
+    member.deregistered = 1;
+
+    // Update active pool member count.
+    pool.active_members = pool.active_members.saturating_sub(1);
+
+    Ok(())
+}
@@ -9,9 +9,29 @@ pub fn process_join(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult
     // Load accounts.
     let [signer_info, member_authority_info, member_info, pool_info, system_program] = accounts
     else {
-        return Err(ProgramError::NotEnoughAccountKeys);
+        return Err(account_count_error(accounts.len(), 5));
     };
-    signer_info.is_signer()?;
+    if !signer_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if *system_program.key != system_program::ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    // The member is derived from the pool key,
+    // so the pool must be a real pool of this program at its derived address,
+    // otherwise a member could be opened against a substituted account.
+    if *pool_info.owner != ore_pool_api::ID {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    let pool = pool_info.to_account_mut::<Pool>(&ore_pool_api::ID)?;
+    let pool_address = Pubkey::create_program_address(
+        &[POOL, pool.seed_authority().as_ref(), &[pool.bump as u8]],
+        &ore_pool_api::ID,
+    )
+    .map_err(|_| ProgramError::InvalidSeeds)?;
+    if pool_address != *pool_info.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
     member_info.is_empty()?.is_writable()?.has_seeds(
         &[
             MEMBER,
@@ -21,8 +41,14 @@ pub fn process_join(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult
         args.member_bump,
         &ore_pool_api::ID,
     )?;
-    let pool = pool_info.to_account_mut::<Pool>(&ore_pool_api::ID)?;
-    system_program.is_program(&system_program::ID)?;
+
+    // Reject new members if the pool is closed.
+    if pool.closed == 1 {
+        return Err(PoolError::PoolClosed.into());
+    }
+
+    // Reject new members if the pool is full.
+    check_capacity(pool.active_members, pool.max_members)?;
 
     // Initialize member account
     create_account::<Member>(
@@ -43,11 +69,177 @@ pub fn process_join(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult
     member.authority = *member_authority_info.key;
     member.balance = 0;
     member.total_balance = 0;
+    member.deregistered = 0;
     member.pool = *pool_info.key;
     member.id = pool.total_members; // zero index
 
     // Update total pool member count.
     pool.total_members = pool.total_members.checked_add(1).unwrap();
+    pool.active_members = pool.active_members.checked_add(1).unwrap();
+
+    Ok(())
+}
 
+/// Checks the pool has room for one more member.
+/// Counts active members rather than the member count at the last submission,
+/// which lags behind joins and deregistrations until the next submit.
+fn check_capacity(active_members: u64, max_members: u64) -> Result<(), PoolError> {
+    if max_members > 0 && active_members >= max_members {
+        return Err(PoolError::PoolFull);
+    }
     Ok(())
 }
+
+/// Too few accounts can't be loaded, and too many means the caller built the
+/// instruction against a different layout, so both are rejected.
+fn account_count_error(count: usize, expected: usize) -> ProgramError {
+    if count < expected {
+        ProgramError::NotEnoughAccountKeys
+    } else {
+        ProgramError::InvalidArgument
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The owned state backing the accounts of a join instruction.
+    struct Accounts {
+        keys: [Pubkey; 5],
+        owners: [Pubkey; 5],
+        lamports: [u64; 5],
+        data: [Vec<u8>; 5],
+        signer: bool,
+    }
+
+    impl Accounts {
+        /// A join against a pool at its derived address.
+        fn new() -> Self {
+            let authority = Pubkey::new_unique();
+            let (pool_address, pool_bump) = pool_pda(authority);
+            let mut pool = Pool::zeroed();
+            pool.authority = authority;
+            pool.bump = pool_bump as u64;
+            let mut pool_data = vec![0u8; 8];
+            pool_data[0] = Pool::discriminator();
+            pool_data.extend_from_slice(bytemuck::bytes_of(&pool));
+            let member_authority = Pubkey::new_unique();
+            let (member_address, _) = member_pda(member_authority, pool_address);
+            Accounts {
+                keys: [
+                    Pubkey::new_unique(),
+                    member_authority,
+                    member_address,
+                    pool_address,
+                    system_program::ID,
+                ],
+                owners: [
+                    system_program::ID,
+                    system_program::ID,
+                    system_program::ID,
+                    ore_pool_api::ID,
+                    solana_program::bpf_loader::ID,
+                ],
+                lamports: [1_000_000_000, 0, 0, 1_000_000, 1],
+                data: [vec![], vec![], vec![], pool_data, vec![]],
+                signer: true,
+            }
+        }
+
+        fn data(&self) -> Vec<u8> {
+            let (_, member_bump) = member_pda(self.keys[1], self.keys[3]);
+            Join { member_bump }.to_bytes()[1..].to_vec()
+        }
+
+        fn join(&mut self, count: usize) -> ProgramResult {
+            let data = self.data();
+            let mut lamports = self.lamports;
+            let mut account_data = self.data.clone();
+            let infos: Vec<AccountInfo> = self
+                .keys
+                .iter()
+                .zip(self.owners.iter())
+                .zip(lamports.iter_mut())
+                .zip(account_data.iter_mut())
+                .enumerate()
+                .map(|(i, (((key, owner), lamports), data))| {
+                    AccountInfo::new(
+                        key,
+                        i == 0 && self.signer,
+                        i != 1 && i != 4,
+                        lamports,
+                        data.as_mut_slice(),
+                        owner,
+                        i == 4,
+                        0,
+                    )
+                })
+                .collect();
+            let mut infos = infos;
+            while infos.len() < count {
+                let extra = infos[0].clone();
+                infos.push(extra);
+            }
+            infos.truncate(count);
+            process_join(infos.as_slice(), data.as_slice())
+        }
+    }
+
+    #[test]
+    fn capacity_is_enforced() {
+        assert_eq!(check_capacity(1_000, 0), Ok(()));
+        assert_eq!(check_capacity(2, 3), Ok(()));
+        assert_eq!(check_capacity(3, 3), Err(PoolError::PoolFull));
+        assert_eq!(check_capacity(4, 3), Err(PoolError::PoolFull));
+    }
+
+    #[test]
+    fn full_pool_is_rejected() {
+        let mut accounts = Accounts::new();
+        let pool = Pool::try_from_bytes_mut(accounts.data[3].as_mut_slice()).unwrap();
+        pool.active_members = 3;
+        pool.max_members = 3;
+        assert_eq!(accounts.join(5), Err(PoolError::PoolFull.into()));
+    }
+
+    #[test]
+    fn wrong_account_count_is_rejected() {
+        assert_eq!(
+            Accounts::new().join(4),
+            Err(ProgramError::NotEnoughAccountKeys)
+        );
+        assert_eq!(Accounts::new().join(6), Err(ProgramError::InvalidArgument));
+    }
+
+    #[test]
+    fn unsigned_payer_is_rejected() {
+        let mut accounts = Accounts::new();
+        accounts.signer = false;
+        assert_eq!(
+            accounts.join(5),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+    }
+
+    #[test]
+    fn substituted_system_program_is_rejected() {
+        let mut accounts = Accounts::new();
+        accounts.keys[4] = Pubkey::new_unique();
+        assert_eq!(accounts.join(5), Err(ProgramError::IncorrectProgramId));
+    }
+
+    #[test]
+    fn pool_of_wrong_owner_is_rejected() {
+        let mut accounts = Accounts::new();
+        accounts.owners[3] = Pubkey::new_unique();
+        assert_eq!(accounts.join(5), Err(ProgramError::InvalidAccountOwner));
+    }
+
+    #[test]
+    fn pool_not_at_derived_address_is_rejected() {
+        let mut accounts = Accounts::new();
+        accounts.keys[3] = Pubkey::new_unique();
+        assert_eq!(accounts.join(5), Err(ProgramError::InvalidSeeds));
+    }
+}
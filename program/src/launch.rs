@@ -62,6 +62,8 @@ pub fn process_launch(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResul
     pool.attestation = [0; 32];
     pool.last_total_members = 0;
     pool.last_hash_at = proof.last_hash_at;
+    pool.closed = 0;
+    pool.active_members = 0;
 
     Ok(())
 }
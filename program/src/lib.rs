@@ -1,23 +1,39 @@
+mod accept_authority;
 mod attribute;
 mod claim;
 mod commit;
+mod compound;
+mod decommission;
+mod deregister;
 mod join;
 mod launch;
+mod migrate;
 mod open_share;
 mod open_stake;
+mod set_claim_cooldown;
+mod set_max_members;
 mod stake;
 mod submit;
+mod transfer_authority;
 mod unstake;
 
+use accept_authority::*;
 use attribute::*;
 use claim::*;
 use commit::*;
+use compound::*;
+use decommission::*;
+use deregister::*;
 use join::*;
 use launch::*;
+use migrate::*;
 use open_share::*;
 use open_stake::*;
+use set_claim_cooldown::*;
+use set_max_members::*;
 use stake::*;
 use submit::*;
+use transfer_authority::*;
 use unstake::*;
 
 use ore_pool_api::prelude::*;
@@ -34,16 +50,24 @@ pub fn process_instruction(
         // User
         PoolInstruction::Join => process_join(accounts, data)?,
         PoolInstruction::Claim => process_claim(accounts, data)?,
+        PoolInstruction::Deregister => process_deregister(accounts, data)?,
         PoolInstruction::OpenShare => process_open_share(accounts, data)?,
         PoolInstruction::Stake => process_stake(accounts, data)?,
         PoolInstruction::Unstake => process_unstake(accounts, data)?,
 
         // Admin
+        PoolInstruction::AcceptAuthority => process_accept_authority(accounts, data)?,
         PoolInstruction::Attribute => process_attribute(accounts, data)?,
         PoolInstruction::Commit => process_commit(accounts, data)?,
+        PoolInstruction::Compound => process_compound(accounts, data)?,
+        PoolInstruction::Decommission => process_decommission(accounts, data)?,
         PoolInstruction::Launch => process_launch(accounts, data)?,
+        PoolInstruction::Migrate => process_migrate(accounts, data)?,
         PoolInstruction::OpenStake => process_open_stake(accounts, data)?,
+        PoolInstruction::SetClaimCooldown => process_set_claim_cooldown(accounts, data)?,
+        PoolInstruction::SetMaxMembers => process_set_max_members(accounts, data)?,
         PoolInstruction::Submit => process_submit(accounts, data)?,
+        PoolInstruction::TransferAuthority => process_transfer_authority(accounts, data)?,
     }
 
     Ok(())
@@ -0,0 +1,76 @@
+use ore_pool_api::prelude::*;
+use solana_program::{rent::Rent, sysvar::Sysvar};
+use steel::*;
+
+/// Migrate grows a pool and its member accounts written with an older, shorter layout
+/// to the current layout. The new fields start zeroed, and accounts already at the
+/// current layout are left as they are.
+pub fn process_migrate(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResult {
+    // Load accounts.
+    let [signer_info, pool_info, system_program, member_infos @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    signer_info.is_signer()?;
+    system_program.is_program(&system_program::ID)?;
+
+    // Grow the pool.
+    let grown = grow::<Pool>(pool_info.is_writable()?, signer_info, system_program)?;
+    let pool = pool_info
+        .to_account_mut::<Pool>(&ore_pool_api::ID)?
+        .check_mut(|p| p.authority == *signer_info.key)?;
+    pool_info.has_seeds(
+        &[POOL, pool.seed_authority().as_ref()],
+        pool.bump as u8,
+        &ore_pool_api::ID,
+    )?;
+
+    // Pools written before members could deregister have no deregistered members,
+    // so every member is active.
+    if grown {
+        pool.active_members = pool.total_members;
+    }
+
+    // Grow the members.
+    for member_info in member_infos {
+        grow::<Member>(member_info.is_writable()?, signer_info, system_program)?;
+        let member = member_info.to_account::<Member>(&ore_pool_api::ID)?;
+        if member.pool != *pool_info.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+    }
+
+    Ok(())
+}
+
+/// Grows an account of this program to the current size of `T`, zeroing the new bytes
+/// and topping up its rent from the payer. Returns whether the account was grown.
+fn grow<'a, T: Discriminator + Pod>(
+    info: &AccountInfo<'a>,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> Result<bool, ProgramError> {
+    if *info.owner != ore_pool_api::ID {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    let len = 8 + std::mem::size_of::<T>();
+    if info.data_len() >= len {
+        return Ok(false);
+    }
+    if info.try_borrow_data()?.first() != Some(&T::discriminator()) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Top up rent for the new size.
+    let rent = Rent::get()?
+        .minimum_balance(len)
+        .saturating_sub(info.lamports());
+    if rent > 0 {
+        solana_program::program::invoke(
+            &solana_program::system_instruction::transfer(payer.key, info.key, rent),
+            &[payer.clone(), info.clone(), system_program.clone()],
+        )?;
+    }
+    info.realloc(len, true)?;
+
+    Ok(true)
+}
@@ -18,7 +18,7 @@ pub fn process_open_share(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramR
         .to_account::<Boost>(&ore_boost_api::ID)?
         .check(|b| b.mint == *mint_info.key)?;
     mint_info.to_mint()?;
-    pool_info.to_account::<Pool>(&ore_pool_api::ID)?;
+    let pool = pool_info.to_account::<Pool>(&ore_pool_api::ID)?;
     share_info.is_empty()?.is_writable()?.has_seeds(
         &[
             SHARE,
@@ -35,6 +35,11 @@ pub fn process_open_share(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramR
         .check(|s| s.boost == *boost_info.key)?;
     system_program.is_program(&system_program::ID)?;
 
+    // Reject new stake if the pool is closed.
+    if pool.closed == 1 {
+        return Err(PoolError::PoolClosed.into());
+    }
+
     // Create the share pda.
     create_account::<Share>(
         share_info,
@@ -27,6 +27,7 @@ pub fn process_open_stake(accounts: &[AccountInfo<'_>], _data: &[u8]) -> Program
 
     // Open the stake account.
     let pool_bump = pool.bump as u8;
+    let pool_authority = pool.seed_authority();
     solana_program::program::invoke_signed(
         &ore_boost_api::sdk::open(*pool_info.key, *signer_info.key, *mint_info.key),
         &[
@@ -37,7 +38,7 @@ pub fn process_open_stake(accounts: &[AccountInfo<'_>], _data: &[u8]) -> Program
             stake_info.clone(),
             system_program.clone(),
         ],
-        &[&[POOL, signer_info.key.as_ref(), &[pool_bump]]],
+        &[&[POOL, pool_authority.as_ref(), &[pool_bump]]],
     )?;
 
     // Create token account for pending pool stake, if necessary
@@ -0,0 +1,33 @@
+use ore_pool_api::prelude::*;
+use steel::*;
+
+/// SetClaimCooldown sets the min number of seconds between claims of a member,
+/// so that balances are claimed in batches rather than after every attribution.
+pub fn process_set_claim_cooldown(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult {
+    // Parse args.
+    let args = SetClaimCooldown::try_from_bytes(data)?;
+    let claim_cooldown = i64::from_le_bytes(args.claim_cooldown);
+    if claim_cooldown < 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Load accounts.
+    let [signer_info, pool_info] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    signer_info.is_signer()?;
+    let pool = pool_info
+        .is_writable()?
+        .to_account_mut::<Pool>(&ore_pool_api::ID)?
+        .check_mut(|p| p.authority == *signer_info.key)?;
+    pool_info.has_seeds(
+        &[POOL, pool.seed_authority().as_ref()],
+        pool.bump as u8,
+        &ore_pool_api::ID,
+    )?;
+
+    // Set the cooldown.
+    pool.claim_cooldown = claim_cooldown;
+
+    Ok(())
+}
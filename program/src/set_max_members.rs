@@ -0,0 +1,31 @@
+use ore_pool_api::prelude::*;
+use steel::*;
+
+/// SetMaxMembers caps the number of active members a pool accepts.
+/// Lowering the cap below the active member count removes no one,
+/// it only rejects new members until enough deregister.
+pub fn process_set_max_members(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult {
+    // Parse args.
+    let args = SetMaxMembers::try_from_bytes(data)?;
+    let max_members = u64::from_le_bytes(args.max_members);
+
+    // Load accounts.
+    let [signer_info, pool_info] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    signer_info.is_signer()?;
+    let pool = pool_info
+        .is_writable()?
+        .to_account_mut::<Pool>(&ore_pool_api::ID)?
+        .check_mut(|p| p.authority == *signer_info.key)?;
+    pool_info.has_seeds(
+        &[POOL, pool.seed_authority().as_ref()],
+        pool.bump as u8,
+        &ore_pool_api::ID,
+    )?;
+
+    // Set the cap.
+    pool.max_members = max_members;
+
+    Ok(())
+}
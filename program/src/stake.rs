@@ -19,7 +19,7 @@ pub fn process_stake(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult
         .to_account::<Member>(&ore_pool_api::ID)?
         .check(|m| m.authority == *signer_info.key)?
         .check(|m| m.pool == *pool_info.key)?;
-    pool_info.to_account::<Pool>(&ore_pool_api::ID)?;
+    let pool = pool_info.to_account::<Pool>(&ore_pool_api::ID)?;
     pool_tokens_info
         .is_writable()?
         .to_associated_token_account(pool_info.key, mint_info.key)?;
@@ -31,11 +31,17 @@ pub fn process_stake(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult
     let share = share_info
         .to_account_mut::<Share>(&ore_pool_api::ID)?
         .check_mut(|s| s.authority == *signer_info.key)?
+        .check_mut(|s| s.pool == *pool_info.key)?
         .check_mut(|s| s.mint == *mint_info.key)?;
     token_program.is_program(&spl_token::ID)?;
 
+    // Reject new stake if the pool is closed.
+    if pool.closed == 1 {
+        return Err(PoolError::PoolClosed.into());
+    }
+
     // Update the share balance.
-    share.balance = share.balance.checked_add(amount).unwrap();
+    merge_stake(share, amount)?;
 
     // Transfer tokens into pool's pending stake account.
     transfer(
@@ -48,3 +54,46 @@ pub fn process_stake(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult
 
     Ok(())
 }
+
+/// Merges the deposit into the share's existing balance,
+/// so staking again tops up the same position rather than opening another.
+pub(crate) fn merge_stake(share: &mut Share, amount: u64) -> Result<(), ProgramError> {
+    share.balance = share
+        .balance
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn share() -> Share {
+        Share {
+            authority: Pubkey::new_unique(),
+            balance: 0,
+            mint: Pubkey::new_unique(),
+            pool: Pubkey::new_unique(),
+        }
+    }
+
+    #[test]
+    fn staking_twice_merges_balances() {
+        let mut share = share();
+        merge_stake(&mut share, 100).unwrap();
+        merge_stake(&mut share, 250).unwrap();
+        assert_eq!(share.balance, 350);
+    }
+
+    #[test]
+    fn overflowing_stake_is_rejected() {
+        let mut share = share();
+        merge_stake(&mut share, u64::MAX).unwrap();
+        assert_eq!(
+            merge_stake(&mut share, 1),
+            Err(ProgramError::ArithmeticOverflow)
+        );
+        assert_eq!(share.balance, u64::MAX);
+    }
+}
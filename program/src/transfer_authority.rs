@@ -0,0 +1,202 @@
+use ore_pool_api::prelude::*;
+use steel::*;
+
+/// TransferAuthority proposes a new authority for the pool.
+/// The transfer only takes effect once the new authority accepts it,
+/// so that a mistyped key can't lock the pool, see [`crate::process_accept_authority`].
+/// Proposing the default pubkey cancels a pending transfer.
+pub fn process_transfer_authority(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult {
+    // Parse args.
+    let args = TransferAuthority::try_from_bytes(data)?;
+
+    // Load accounts.
+    let [signer_info, pool_info] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    signer_info.is_signer()?;
+    let pool = pool_info
+        .is_writable()?
+        .to_account_mut::<Pool>(&ore_pool_api::ID)?
+        .check_mut(|p| p.authority == *signer_info.key)?;
+    pool_info.has_seeds(
+        &[POOL, pool.seed_authority().as_ref()],
+        pool.bump as u8,
+        &ore_pool_api::ID,
+    )?;
+
+    // Propose the new authority.
+    pool.pending_authority = Pubkey::new_from_array(args.new_authority);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_accept_authority;
+
+    /// The owned state backing a pool and the signer of an authority instruction.
+    /// The proof is only passed through to ORE, which is stubbed off-chain.
+    struct Accounts {
+        pool: Pubkey,
+        pool_data: Vec<u8>,
+        proof: Pubkey,
+        signer: bool,
+    }
+
+    impl Accounts {
+        /// A pool launched by the authority.
+        fn new(authority: Pubkey) -> Self {
+            let (pool_address, pool_bump) = pool_pda(authority);
+            let mut pool = Pool::zeroed();
+            pool.authority = authority;
+            pool.bump = pool_bump as u64;
+            let mut pool_data = vec![0u8; 8];
+            pool_data[0] = Pool::discriminator();
+            pool_data.extend_from_slice(bytemuck::bytes_of(&pool));
+            Accounts {
+                pool: pool_address,
+                pool_data,
+                proof: pool_proof_pda(pool_address).0,
+                signer: true,
+            }
+        }
+
+        fn pool(&self) -> Pool {
+            *Pool::try_from_bytes(self.pool_data.as_slice()).unwrap()
+        }
+
+        fn run(
+            &mut self,
+            signer: Pubkey,
+            process: fn(&[AccountInfo], &[u8]) -> ProgramResult,
+            data: &[u8],
+            num_accounts: usize,
+        ) -> ProgramResult {
+            let mut signer_lamports = 1_000_000_000;
+            let mut pool_lamports = 1_000_000;
+            let mut proof_lamports = 1_000_000;
+            let mut ore_lamports = 1;
+            let mut signer_data = vec![];
+            let mut proof_data = vec![];
+            let mut ore_data = vec![];
+            let infos = [
+                AccountInfo::new(
+                    &signer,
+                    self.signer,
+                    true,
+                    &mut signer_lamports,
+                    signer_data.as_mut_slice(),
+                    &system_program::ID,
+                    false,
+                    0,
+                ),
+                AccountInfo::new(
+                    &self.pool,
+                    false,
+                    true,
+                    &mut pool_lamports,
+                    self.pool_data.as_mut_slice(),
+                    &ore_pool_api::ID,
+                    false,
+                    0,
+                ),
+                AccountInfo::new(
+                    &self.proof,
+                    false,
+                    true,
+                    &mut proof_lamports,
+                    proof_data.as_mut_slice(),
+                    &ore_api::ID,
+                    false,
+                    0,
+                ),
+                AccountInfo::new(
+                    &ore_api::ID,
+                    false,
+                    false,
+                    &mut ore_lamports,
+                    ore_data.as_mut_slice(),
+                    &solana_program::bpf_loader::ID,
+                    true,
+                    0,
+                ),
+            ];
+            process(&infos[..num_accounts], data)
+        }
+
+        fn propose(&mut self, signer: Pubkey, new_authority: Pubkey) -> ProgramResult {
+            let data = TransferAuthority {
+                new_authority: new_authority.to_bytes(),
+            }
+            .to_bytes();
+            self.run(signer, process_transfer_authority, &data[1..], 2)
+        }
+
+        fn accept(&mut self, signer: Pubkey) -> ProgramResult {
+            let data = AcceptAuthority {}.to_bytes();
+            self.run(signer, process_accept_authority, &data[1..], 4)
+        }
+    }
+
+    #[test]
+    fn propose_sets_pending_authority() {
+        let authority = Pubkey::new_unique();
+        let new_authority = Pubkey::new_unique();
+        let mut accounts = Accounts::new(authority);
+        accounts.propose(authority, new_authority).unwrap();
+        assert_eq!(accounts.pool().pending_authority, new_authority);
+        assert_eq!(accounts.pool().authority, authority);
+        // the default pubkey cancels
+        accounts.propose(authority, Pubkey::default()).unwrap();
+        assert_eq!(accounts.pool().pending_authority, Pubkey::default());
+    }
+
+    #[test]
+    fn unauthorized_propose_is_rejected() {
+        let authority = Pubkey::new_unique();
+        let mut accounts = Accounts::new(authority);
+        let intruder = Pubkey::new_unique();
+        assert!(accounts.propose(intruder, intruder).is_err());
+        accounts.signer = false;
+        assert!(accounts.propose(authority, intruder).is_err());
+        assert_eq!(accounts.pool().pending_authority, Pubkey::default());
+    }
+
+    #[test]
+    fn accept_transfers_authority() {
+        let authority = Pubkey::new_unique();
+        let new_authority = Pubkey::new_unique();
+        let mut accounts = Accounts::new(authority);
+        accounts.propose(authority, new_authority).unwrap();
+        accounts.accept(new_authority).unwrap();
+        let pool = accounts.pool();
+        assert_eq!(pool.authority, new_authority);
+        assert_eq!(pool.pending_authority, Pubkey::default());
+        // the pool address is still derived from the original authority
+        assert_eq!(pool.seed_authority(), authority);
+        // only the new authority can transfer it on
+        let next = Pubkey::new_unique();
+        assert!(accounts.propose(authority, next).is_err());
+        accounts.propose(new_authority, next).unwrap();
+        accounts.accept(next).unwrap();
+        assert_eq!(accounts.pool().authority, next);
+        assert_eq!(accounts.pool().seed_authority(), authority);
+    }
+
+    #[test]
+    fn unauthorized_accept_is_rejected() {
+        let authority = Pubkey::new_unique();
+        let new_authority = Pubkey::new_unique();
+        let mut accounts = Accounts::new(authority);
+        // nothing is pending
+        assert!(accounts.accept(new_authority).is_err());
+        accounts.propose(authority, new_authority).unwrap();
+        assert!(accounts.accept(Pubkey::new_unique()).is_err());
+        assert!(accounts.accept(authority).is_err());
+        accounts.signer = false;
+        assert!(accounts.accept(new_authority).is_err());
+        assert_eq!(accounts.pool().authority, authority);
+        assert_eq!(accounts.pool().pending_authority, new_authority);
+    }
+}
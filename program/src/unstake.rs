@@ -69,7 +69,7 @@ pub fn process_unstake(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResu
                 stake_info.clone(),
                 token_program.clone(),
             ],
-            &[&[POOL, pool.authority.as_ref(), &[pool.bump as u8]]],
+            &[&[POOL, pool.seed_authority().as_ref(), &[pool.bump as u8]]],
         )?;
     }
 
@@ -80,7 +80,7 @@ pub fn process_unstake(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResu
         recipient_tokens_info,
         token_program,
         amount,
-        &[&[POOL, pool.authority.as_ref(), &[pool.bump as u8]]],
+        &[&[POOL, pool.seed_authority().as_ref(), &[pool.bump as u8]]],
     )?;
 
     // Log the balance for parsing.
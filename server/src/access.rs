@@ -0,0 +1,241 @@
+use std::{collections::HashMap, time::Duration};
+
+use ore_pool_types::{AccessEntry, AccessList};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{database, error::Error};
+
+/// How often (in seconds) the access lists are re-read from the db by default.
+pub const DEFAULT_ACCESS_REFRESH_SECS: u64 = 60;
+
+/// Restricts who may register and contribute, shared across http workers.
+/// Denylisted authorities are always rejected. If the pool is allowlist-only,
+/// authorities not on the allowlist are rejected too.
+/// The lists live in the db, and are cached here and refreshed on an interval,
+/// so that edits made straight to the db (or by another server) are picked up.
+pub struct Access {
+    /// whether or not only allowlisted authorities may register and contribute
+    allowlist_only: bool,
+    lists: std::sync::RwLock<Lists>,
+    interval: Duration,
+}
+
+#[derive(Default)]
+struct Lists {
+    /// the list each authority is on, an authority is on at most one
+    lists: HashMap<Pubkey, AccessList>,
+    /// the edits made since the last refresh started reading the db,
+    /// which its snapshot may predate and are re-applied over it
+    edits: Vec<(Pubkey, Option<AccessList>)>,
+}
+
+impl Lists {
+    fn apply(&mut self, authority: Pubkey, list: Option<AccessList>) {
+        match list {
+            Some(list) => self.lists.insert(authority, list),
+            None => self.lists.remove(&authority),
+        };
+    }
+}
+
+impl Access {
+    pub fn new(allowlist_only: bool, interval: Duration) -> Self {
+        log::info!(
+            "allowlist only: {} access refresh: {:?}",
+            allowlist_only,
+            interval
+        );
+        Self {
+            allowlist_only,
+            lists: std::sync::RwLock::new(Lists::default()),
+            interval,
+        }
+    }
+
+    /// Errors if the authority may not register or contribute.
+    pub fn check(&self, authority: &Pubkey) -> Result<(), Error> {
+        let list = self.read().lists.get(authority).copied();
+        match list {
+            Some(AccessList::Deny) => Err(Error::AccessDenied),
+            Some(AccessList::Allow) => Ok(()),
+            None if self.allowlist_only => Err(Error::AccessDenied),
+            None => Ok(()),
+        }
+    }
+
+    /// The cached lists, sorted by authority.
+    pub fn entries(&self) -> Vec<AccessEntry> {
+        let mut entries: Vec<AccessEntry> = self
+            .read()
+            .lists
+            .iter()
+            .map(|(authority, list)| AccessEntry {
+                authority: *authority,
+                list: *list,
+            })
+            .collect();
+        entries.sort_by_key(|entry| entry.authority);
+        entries
+    }
+
+    /// Caches an edit, after it's been written to the db.
+    pub fn insert(&self, entry: &AccessEntry) {
+        self.edit(entry.authority, Some(entry.list));
+    }
+
+    /// Caches a removal, after it's been written to the db.
+    pub fn remove(&self, authority: &Pubkey) {
+        self.edit(*authority, None);
+    }
+
+    fn edit(&self, authority: Pubkey, list: Option<AccessList>) {
+        let mut lists = self.write();
+        lists.apply(authority, list);
+        lists.edits.push((authority, list));
+    }
+
+    /// Marks the start of a refresh, before the db is read.
+    fn begin_refresh(&self) {
+        self.write().edits.clear();
+    }
+
+    /// Swaps in the entries read from the db,
+    /// re-applying the edits made since the refresh began.
+    fn replace(&self, entries: Vec<AccessEntry>) {
+        let fresh: HashMap<Pubkey, AccessList> = entries
+            .into_iter()
+            .map(|entry| (entry.authority, entry.list))
+            .collect();
+        let mut lists = self.write();
+        lists.lists = fresh;
+        for (authority, list) in std::mem::take(&mut lists.edits) {
+            lists.apply(authority, list);
+        }
+    }
+
+    /// Re-reads the lists from the db.
+    pub async fn refresh(&self, db_client: &deadpool_postgres::Pool) -> Result<(), Error> {
+        self.begin_refresh();
+        let conn = db_client.get().await?;
+        let entries = database::read_access(&conn).await?;
+        self.replace(entries);
+        Ok(())
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Lists> {
+        match self.lists.read() {
+            Ok(lists) => lists,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, Lists> {
+        match self.lists.write() {
+            Ok(lists) => lists,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// Refreshes the lists on the interval, keeping the cached lists if a refresh fails.
+    pub async fn run(&self, db_client: &deadpool_postgres::Pool) {
+        loop {
+            tokio::time::sleep(self.interval).await;
+            if let Err(err) = self.refresh(db_client).await {
+                log::error!("failed to refresh access lists: {:?}", err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn access(allowlist_only: bool, entries: &[(Pubkey, AccessList)]) -> Access {
+        let access = Access::new(allowlist_only, Duration::from_secs(60));
+        for (authority, list) in entries {
+            access.insert(&AccessEntry {
+                authority: *authority,
+                list: *list,
+            });
+        }
+        access
+    }
+
+    #[test]
+    fn allowlisted_authority_is_accepted() {
+        let allowed = Pubkey::new_unique();
+        let access = access(true, &[(allowed, AccessList::Allow)]);
+        assert!(access.check(&allowed).is_ok());
+    }
+
+    #[test]
+    fn authority_not_on_the_allowlist_is_rejected() {
+        let access = access(true, &[(Pubkey::new_unique(), AccessList::Allow)]);
+        assert!(matches!(
+            access.check(&Pubkey::new_unique()),
+            Err(Error::AccessDenied)
+        ));
+        // but accepted if the pool isn't allowlist-only
+        let access = Access::new(false, Duration::from_secs(60));
+        assert!(access.check(&Pubkey::new_unique()).is_ok());
+    }
+
+    #[test]
+    fn denylisted_authority_is_rejected() {
+        let denied = Pubkey::new_unique();
+        for allowlist_only in [true, false] {
+            let access = access(allowlist_only, &[(denied, AccessList::Deny)]);
+            assert!(matches!(access.check(&denied), Err(Error::AccessDenied)));
+        }
+        // moving the authority onto the allowlist replaces the denial
+        let access = access(true, &[(denied, AccessList::Deny)]);
+        access.insert(&AccessEntry {
+            authority: denied,
+            list: AccessList::Allow,
+        });
+        assert!(access.check(&denied).is_ok());
+    }
+
+    #[test]
+    fn refresh_replaces_the_cached_lists() {
+        let dropped = Pubkey::new_unique();
+        let kept = Pubkey::new_unique();
+        let access = access(
+            true,
+            &[(dropped, AccessList::Allow), (kept, AccessList::Allow)],
+        );
+        access.begin_refresh();
+        access.replace(vec![AccessEntry {
+            authority: kept,
+            list: AccessList::Deny,
+        }]);
+        assert!(access.check(&dropped).is_err());
+        assert!(access.check(&kept).is_err());
+        assert_eq!(access.entries().len(), 1);
+    }
+
+    #[test]
+    fn edits_made_during_a_refresh_survive_it() {
+        let added = Pubkey::new_unique();
+        let removed = Pubkey::new_unique();
+        let access = access(true, &[(removed, AccessList::Allow)]);
+        access.begin_refresh();
+        // edited after the db was read
+        access.insert(&AccessEntry {
+            authority: added,
+            list: AccessList::Allow,
+        });
+        access.remove(&removed);
+        access.replace(vec![AccessEntry {
+            authority: removed,
+            list: AccessList::Allow,
+        }]);
+        assert!(access.check(&added).is_ok());
+        assert!(access.check(&removed).is_err());
+        // and are only re-applied once
+        access.begin_refresh();
+        access.replace(vec![]);
+        assert!(access.entries().is_empty());
+    }
+}
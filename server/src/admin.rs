@@ -0,0 +1,335 @@
+use std::str::FromStr;
+
+use actix_web::{http::StatusCode, web, HttpRequest, HttpResponse, Responder};
+
+use ore_pool_types::{
+    AccessEntry, ForensicsEntry, GetMemberPayload, JailedMember, MemberPage, MembersQuery,
+    MinDifficultyPayload,
+};
+use solana_sdk::pubkey::Pubkey;
+use subtle::ConstantTimeEq;
+
+use crate::{
+    access::Access,
+    aggregator::Aggregator,
+    database,
+    error::{Error, ErrorCode},
+    jail::Jail,
+    operator::Operator,
+};
+
+/// The number of members returned per page by default.
+const MEMBERS_DEFAULT_LIMIT: usize = 100;
+/// The max number of members returned per page.
+const MEMBERS_MAX_LIMIT: usize = 1_000;
+
+/// Gates the admin endpoints behind a bearer token.
+pub struct Auth {
+    /// the token expected in the authorization header,
+    /// admin endpoints are disabled if not configured.
+    admin_token: Option<String>,
+}
+
+impl Auth {
+    pub fn new(admin_token: Option<String>) -> Self {
+        if admin_token.is_none() {
+            log::info!("admin token not configured, admin endpoints are disabled");
+        }
+        Self { admin_token }
+    }
+
+    /// parse and validate the bearer token
+    pub fn check(&self, req: &HttpRequest) -> Result<(), Error> {
+        let admin_token = self.admin_token.as_ref().ok_or(Error::Unauthorized)?;
+        let header = req
+            .headers()
+            .get("Authorization")
+            .ok_or(Error::Unauthorized)?;
+        let header = header.to_str()?;
+        let token = header.strip_prefix("Bearer ").ok_or(Error::Unauthorized)?;
+        // compared in constant time so the token can't be guessed byte by byte from timings
+        if !bool::from(token.as_bytes().ct_eq(admin_token.as_bytes())) {
+            return Err(Error::Unauthorized);
+        }
+        Ok(())
+    }
+}
+
+/// Closes the current round out of band,
+/// a recovery lever for when the aggregator is wedged on a stale challenge.
+pub async fn force_submit(
+    auth: web::Data<Auth>,
+    operator: web::Data<Operator>,
+    aggregator: web::Data<tokio::sync::RwLock<Aggregator>>,
+    req: HttpRequest,
+) -> impl Responder {
+    if let Err(err) = auth.check(&req) {
+        log::error!("{:?}", err);
+        let http_response: HttpResponse = err.into();
+        return http_response;
+    }
+    let triggered_by = req
+        .connection_info()
+        .realip_remote_addr()
+        .unwrap_or("unknown")
+        .to_string();
+    log::info!("force submit triggered by: {}", triggered_by);
+    let mut aggregator = aggregator.write().await;
+    match aggregator.force_submit(operator.as_ref()).await {
+        Ok(()) => HttpResponse::Ok().finish(),
+        Err(err) => {
+            log::error!("{:?}", err);
+            let http_response: HttpResponse = err.into();
+            http_response
+        }
+    }
+}
+
+/// Sets or clears the min difficulty override,
+/// raising the min difficulty of the current and following challenges above the protocol min.
+/// Responds with the current challenge.
+pub async fn min_difficulty(
+    auth: web::Data<Auth>,
+    aggregator: web::Data<tokio::sync::RwLock<Aggregator>>,
+    payload: web::Json<MinDifficultyPayload>,
+    req: HttpRequest,
+) -> impl Responder {
+    if let Err(err) = auth.check(&req) {
+        log::error!("{:?}", err);
+        let http_response: HttpResponse = err.into();
+        return http_response;
+    }
+    let challenge = {
+        let mut aggregator = aggregator.write().await;
+        aggregator.set_min_difficulty_override(payload.min_difficulty);
+        aggregator.challenge
+    };
+    HttpResponse::Ok().json(challenge)
+}
+
+/// Resumes contributions paused after repeated submit failures,
+/// without waiting for a submit to land.
+pub async fn resume(
+    auth: web::Data<Auth>,
+    aggregator: web::Data<tokio::sync::RwLock<Aggregator>>,
+    req: HttpRequest,
+) -> impl Responder {
+    if let Err(err) = auth.check(&req) {
+        log::error!("{:?}", err);
+        let http_response: HttpResponse = err.into();
+        return http_response;
+    }
+    aggregator.write().await.reset_breaker();
+    HttpResponse::Ok().finish()
+}
+
+/// Lists the authorities on the access lists, as cached.
+pub async fn access(
+    auth: web::Data<Auth>,
+    access: web::Data<Access>,
+    req: HttpRequest,
+) -> impl Responder {
+    if let Err(err) = auth.check(&req) {
+        log::error!("{:?}", err);
+        let http_response: HttpResponse = err.into();
+        return http_response;
+    }
+    HttpResponse::Ok().json(&access.entries())
+}
+
+/// Puts the authority on the allowlist or denylist,
+/// moving it off the other list if it was on it. Applies right away.
+pub async fn set_access(
+    auth: web::Data<Auth>,
+    operator: web::Data<Operator>,
+    access: web::Data<Access>,
+    payload: web::Json<AccessEntry>,
+    req: HttpRequest,
+) -> impl Responder {
+    if let Err(err) = auth.check(&req) {
+        log::error!("{:?}", err);
+        let http_response: HttpResponse = err.into();
+        return http_response;
+    }
+    let entry = payload.into_inner();
+    let res = async {
+        let conn = operator.db_client.get().await?;
+        database::write_access(&conn, &entry).await
+    }
+    .await;
+    match res {
+        Ok(()) => {
+            log::info!("access: {} {:?}", entry.authority, entry.list);
+            access.insert(&entry);
+            HttpResponse::Ok().json(&entry)
+        }
+        Err(err) => {
+            log::error!("{:?}", err);
+            let http_response: HttpResponse = err.into();
+            http_response
+        }
+    }
+}
+
+/// Takes the authority off the access lists. Applies right away.
+pub async fn remove_access(
+    auth: web::Data<Auth>,
+    operator: web::Data<Operator>,
+    access: web::Data<Access>,
+    path: web::Path<GetMemberPayload>,
+    req: HttpRequest,
+) -> impl Responder {
+    if let Err(err) = auth.check(&req) {
+        log::error!("{:?}", err);
+        let http_response: HttpResponse = err.into();
+        return http_response;
+    }
+    let res = async {
+        let authority = Pubkey::from_str(path.authority.as_str())?;
+        let conn = operator.db_client.get().await?;
+        let deleted = database::delete_access(&conn, &authority).await?;
+        Ok::<_, Error>((authority, deleted))
+    }
+    .await;
+    match res {
+        Ok((authority, true)) => {
+            log::info!("access: {} removed", authority);
+            access.remove(&authority);
+            HttpResponse::Ok().finish()
+        }
+        Ok((authority, false)) => {
+            // drop any stale cached entry all the same
+            access.remove(&authority);
+            ErrorCode::NotFound.response(StatusCode::NOT_FOUND, "authority not on an access list")
+        }
+        Err(err) => {
+            log::error!("{:?}", err);
+            let http_response: HttpResponse = err.into();
+            http_response
+        }
+    }
+}
+
+/// Lists the currently jailed members.
+pub async fn jailed(
+    auth: web::Data<Auth>,
+    jail: web::Data<Jail>,
+    req: HttpRequest,
+) -> impl Responder {
+    if let Err(err) = auth.check(&req) {
+        log::error!("{:?}", err);
+        let http_response: HttpResponse = err.into();
+        return http_response;
+    }
+    let jailed: Vec<JailedMember> = jail
+        .jailed()
+        .into_iter()
+        .map(|(authority, remaining_secs)| JailedMember {
+            authority,
+            remaining_secs,
+        })
+        .collect();
+    HttpResponse::Ok().json(&jailed)
+}
+
+/// Lists the most recently accepted contributions, oldest first,
+/// for detecting correlated nonces across authorities.
+pub async fn forensics(
+    auth: web::Data<Auth>,
+    aggregator: web::Data<tokio::sync::RwLock<Aggregator>>,
+    req: HttpRequest,
+) -> impl Responder {
+    if let Err(err) = auth.check(&req) {
+        log::error!("{:?}", err);
+        let http_response: HttpResponse = err.into();
+        return http_response;
+    }
+    let forensics: Vec<ForensicsEntry> = {
+        let aggregator = aggregator.read().await;
+        aggregator.forensics.iter().copied().collect()
+    };
+    HttpResponse::Ok().json(&forensics)
+}
+
+/// Lists when each contribution to a round arrived, oldest first,
+/// for seeing the round's submission curve.
+/// Responds with 404 if the round's contributions are no longer (or never were) retained.
+pub async fn timeline(
+    auth: web::Data<Auth>,
+    aggregator: web::Data<tokio::sync::RwLock<Aggregator>>,
+    path: web::Path<i64>,
+    req: HttpRequest,
+) -> impl Responder {
+    if let Err(err) = auth.check(&req) {
+        log::error!("{:?}", err);
+        let http_response: HttpResponse = err.into();
+        return http_response;
+    }
+    let last_hash_at = path.into_inner();
+    let timeline = {
+        let aggregator = aggregator.read().await;
+        aggregator.get_timeline(last_hash_at)
+    };
+    match timeline {
+        Some(timeline) => HttpResponse::Ok().json(&timeline),
+        None => ErrorCode::NotFound.response(StatusCode::NOT_FOUND, "round not retained"),
+    }
+}
+
+/// Lists the members, optionally filtered by active status and min balance,
+/// with the total number of matches in the x-total-count header.
+pub async fn members(
+    auth: web::Data<Auth>,
+    operator: web::Data<Operator>,
+    query: web::Query<MembersQuery>,
+    req: HttpRequest,
+) -> impl Responder {
+    if let Err(err) = auth.check(&req) {
+        log::error!("{:?}", err);
+        let http_response: HttpResponse = err.into();
+        return http_response;
+    }
+    match list_members(operator.as_ref(), query.into_inner()).await {
+        Ok((page, total)) => HttpResponse::Ok()
+            .insert_header(("x-total-count", total.to_string()))
+            .json(&page),
+        Err(err) => {
+            log::error!("{:?}", err);
+            let http_response: HttpResponse = err.into();
+            http_response
+        }
+    }
+}
+
+async fn list_members(
+    operator: &Operator,
+    query: MembersQuery,
+) -> Result<(MemberPage, i64), Error> {
+    let limit = query
+        .limit
+        .unwrap_or(MEMBERS_DEFAULT_LIMIT)
+        .clamp(1, MEMBERS_MAX_LIMIT);
+    let min_balance = query.min_balance.unwrap_or(0).min(i64::MAX as u64) as i64;
+    let db_client = operator.db_read_client.get().await?;
+    let members = database::list_members(
+        &db_client,
+        query.active,
+        min_balance,
+        query.cursor,
+        limit as i64,
+    )
+    .await?;
+    let total = database::count_members(&db_client, query.active, min_balance).await?;
+    // a short page is the last page
+    let next_cursor = match members.last() {
+        Some(last) if members.len() == limit => Some(last.id),
+        _ => None,
+    };
+    Ok((
+        MemberPage {
+            members,
+            next_cursor,
+        },
+        total,
+    ))
+}
@@ -1,32 +1,72 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Write,
     hash::Hash,
 };
 
 use drillx::Solution;
 use ore_api::{
-    consts::{BUS_ADDRESSES, BUS_COUNT},
+    consts::{BUS_ADDRESSES, BUS_COUNT, ONE_ORE},
     state::Bus,
 };
-use ore_pool_types::Challenge;
-use rand::Rng;
-use sha3::{Digest, Sha3_256};
-use solana_sdk::{pubkey::Pubkey, signer::Signer};
+use ore_pool_types::{
+    Attestation, AttestationEntry, BoostStake, Challenge, ForensicsEntry, RewardEstimate,
+    RoundSummary, TimelineEntry,
+};
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    Rng,
+};
+use solana_sdk::pubkey::Pubkey;
 use steel::AccountDeserialize;
+use tracing::Instrument;
 
 use crate::{
+    arrival::ArrivalCurve,
+    attestation,
+    breaker::Breaker,
+    config::{Config, ReloadableConfig},
+    contributions_hash::ContributionsHash,
     database,
+    difficulty::DifficultyHistogram,
     error::Error,
-    operator::{Operator, BUFFER_OPERATOR},
-    tx,
+    metrics,
+    operator::{DustRecipient, Operator, UnstakedRecipient, WinnerStrategy, BUFFER_OPERATOR},
+    request_id::RequestId,
+    scoring::Curve,
+    stake, tx,
     webhook::Rewards,
 };
 
 /// The client submits slightly earlier
 /// than the operator's cutoff time to create a "submission window".
 pub const BUFFER_CLIENT: u64 = 2 + BUFFER_OPERATOR;
-const MAX_DIFFICULTY: u32 = 22;
+pub const MAX_DIFFICULTY: u32 = 22;
+/// The submit is time sensitive, so retry quickly.
+const SUBMIT_RETRY: tx::submit::Retry = tx::submit::Retry {
+    max_retries: 3,
+    base_delay_ms: 500,
+    max_jitter_ms: 250,
+};
+/// The number of new challenges buffered for slow websocket subscribers.
+const CHALLENGE_CHANNEL_CAPACITY: usize = 16;
+/// The number of closed rounds retained in the challenge history by default.
+pub const DEFAULT_CHALLENGE_HISTORY_SIZE: usize = 64;
+/// The number of recently distributed rounds averaged over for reward estimates.
+const REWARD_SAMPLES_SIZE: usize = 32;
+/// The number of accepted contributions retained in the forensics log by default, disabled.
+pub const DEFAULT_FORENSICS_LOG_SIZE: usize = 0;
+/// How long (in seconds) a submitted round waits on its rewards by default.
+pub const DEFAULT_REWARDS_TIMEOUT_SECS: u64 = 300;
 const MAX_SCORE: u64 = 2u64.pow(MAX_DIFFICULTY);
+/// How long (in seconds) startup waits before checking again for an uninitialized pool,
+/// doubled per check up to the max.
+const INIT_POLL_BASE_SECS: u64 = 1;
+const INIT_POLL_MAX_SECS: u64 = 30;
+/// How long (in seconds) a round that starts with a zero cutoff inside its cutoff window
+/// waits before fetching the challenge again, and how many times it fetches.
+const ZERO_CUTOFF_POLL_SECS: u64 = 1;
+const ZERO_CUTOFF_MAX_CHECKS: u32 = 5;
 
 /// Aggregates contributions from the pool members.
 pub struct Aggregator {
@@ -39,14 +79,131 @@ pub struct Aggregator {
     /// The total difficulty score of all the contributions aggregated so far.
     pub total_score: u64,
 
-    /// The best solution submitted.
+    /// The solution to be submitted, per the winner strategy.
     pub winner: Option<Winner>,
 
+    /// How the solution to be submitted is selected from the contributions.
+    pub winner_strategy: WinnerStrategy,
+
+    /// The curve mapping solution difficulty to contribution score.
+    pub score_curve: Curve,
+
+    /// The max share of the running total score in % percentage
+    /// that a contribution may add on insert, if capped.
+    pub max_member_score_pct: Option<u64>,
+
     /// The number of workers that have been approved for the current challenge.
     pub num_members: u64,
 
     /// The map of stake contributors for attribution.
+    /// A cached snapshot, see [`stake::Refresh`].
     pub stake: Stakers,
+
+    /// The client for publishing attestation blocks, if configured.
+    pub attestation_client: Option<attestation::Client>,
+
+    /// The most recently closed rounds, oldest first.
+    pub history: VecDeque<RoundSummary>,
+
+    /// The max number of rounds retained in the history.
+    pub history_size: usize,
+
+    /// The rewards of the most recently distributed rounds, oldest first,
+    /// for estimating rewards.
+    pub reward_samples: VecDeque<RewardSample>,
+
+    /// The most recently accepted contributions, oldest first,
+    /// for sybil analysis rather than rewards.
+    pub forensics: VecDeque<ForensicsEntry>,
+
+    /// The max number of contributions retained in the forensics log.
+    pub forensics_size: usize,
+
+    /// Publishes each new challenge to websocket subscribers.
+    pub challenge_tx: tokio::sync::broadcast::Sender<Challenge>,
+
+    /// The submitted rounds still waiting on their rewards webhook.
+    pub pending_rewards: HashMap<LastHashAt, PendingRewards>,
+
+    /// How long a submitted round waits on its rewards webhook before it is expired.
+    pub rewards_timeout: std::time::Duration,
+
+    /// When contributions arrive within recent rounds, for closing a round early.
+    pub arrivals: ArrivalCurve,
+
+    /// The solutions (digest and nonce) contributed so far in the current challenge,
+    /// so that the same solution is only scored for the first member to submit it.
+    pub solutions: HashSet<[u8; 24]>,
+
+    /// The attributions held back from members below the min attribution,
+    /// keyed by member address. Mirrors the db, see [`database::read_carry_over`].
+    pub carry_over: HashMap<String, u64>,
+
+    /// The last-hash-at of the round each member first contributed to, keyed by authority.
+    /// Mirrors the db, see [`database::read_first_seen`].
+    pub first_seen: HashMap<Pubkey, i64>,
+
+    /// The contributions to the current round by difficulty.
+    pub difficulties: DifficultyHistogram,
+
+    /// The contributions hash of the current round, kept up to date as contributions arrive
+    /// so that closing the round doesn't rehash every contribution.
+    pub contributions_hash: ContributionsHash,
+
+    /// When the current round started, on a monotonic clock,
+    /// for timing contributions within the round.
+    pub round_started: std::time::Instant,
+
+    /// Whether or not each contribution's arrival time is included in the attestation block.
+    /// Changes the contributions hash, so it's opt-in.
+    pub attest_arrivals: bool,
+
+    /// The min difficulty of the current challenge as set by the protocol,
+    /// before the override.
+    pub protocol_min_difficulty: u64,
+
+    /// The admin-set min difficulty, raising the challenge min difficulty above the protocol min.
+    /// Kept in memory only, see [`Aggregator::set_min_difficulty_override`].
+    pub min_difficulty_override: Option<u64>,
+
+    /// Pauses contributions after repeated submit failures.
+    pub breaker: Breaker,
+
+    /// When the contribution loop stops accepting contributions to the current round,
+    /// after the cutoff margin, late grace and adaptive cutoff. None before the first round.
+    pub deadline: Option<tokio::time::Instant>,
+}
+
+/// The rewards of a distributed round.
+#[derive(Clone, Copy, Debug)]
+pub struct RewardSample {
+    /// The rewards from mining, net of boost rewards.
+    pub mine: u64,
+
+    /// The rewards from all boosts.
+    pub boost: u64,
+
+    /// The total score of the round.
+    pub total_score: u64,
+}
+
+/// A submitted round waiting on its rewards webhook.
+#[derive(Clone, Debug)]
+pub struct PendingRewards {
+    /// When the submit landed.
+    pub submitted_at: std::time::Instant,
+
+    /// The config as of the submit, which the round's rewards are distributed with
+    /// even if the config is reloaded in the meantime.
+    pub config: std::sync::Arc<ReloadableConfig>,
+
+    /// The pool reward read back from chain right after the submit landed, if the read succeeded.
+    /// Includes any boost rewards, which can't be split out without the webhook.
+    pub reward: Option<u64>,
+
+    /// The webhook rewards, if they arrived before the submit settled,
+    /// held until a sweep finds it settled.
+    pub held: Option<Rewards>,
 }
 
 /// Miners
@@ -59,6 +216,53 @@ pub type BoostMint = Pubkey;
 pub type StakerBalances = HashMap<Pubkey, u64>;
 pub type Stakers = HashMap<BoostMint, StakerBalances>;
 
+/// Member addresses by authority, derived once per round
+/// and shared by the miner, staker, and operator distributions,
+/// since a member that both mines and stakes would otherwise be derived for each.
+type MemberAddresses = HashMap<Pubkey, String>;
+
+/// The address of the authority's member account, derived on first use.
+fn member_address(addresses: &mut MemberAddresses, authority: Pubkey, pool: Pubkey) -> String {
+    addresses
+        .entry(authority)
+        .or_insert_with(|| {
+            let (member_pda, _) = ore_pool_api::state::member_pda(authority, pool);
+            member_pda.to_string()
+        })
+        .clone()
+}
+
+/// Snapshot of the live challenge for the /stats endpoint.
+#[derive(Debug, serde::Serialize)]
+pub struct PoolStats {
+    /// Foreign key to the ORE proof account.
+    pub last_hash_at: i64,
+
+    /// The total difficulty score of all the contributions aggregated so far.
+    pub total_score: u64,
+
+    /// The number of members at the last submission.
+    pub num_members: u64,
+
+    /// The number of distinct contributions for the current challenge.
+    pub num_contributions: usize,
+
+    /// The difficulty of the best solution submitted, if any.
+    pub winner_difficulty: Option<u32>,
+
+    /// The cutoff time (in seconds) as of when the challenge was fetched.
+    pub cutoff_time: u64,
+
+    /// The seconds remaining until the operator stops accepting contributions.
+    pub remaining_time: u64,
+
+    /// Whether or not contributions are paused after repeated submit failures.
+    pub paused: bool,
+
+    /// The submit failures since the last submit landed.
+    pub consecutive_submit_failures: u32,
+}
+
 // Best hash to be submitted for the current challenge.
 #[derive(Clone, Copy, Debug)]
 pub struct Winner {
@@ -67,6 +271,45 @@ pub struct Winner {
 
     // The current largest difficulty.
     pub difficulty: u32,
+
+    // The request the winning solution was contributed in.
+    pub request_id: RequestId,
+}
+
+impl Winner {
+    /// Whether or not this solution should replace the current winner.
+    /// The higher difficulty wins, and ties go to the lower nonce,
+    /// so that the winner is a pure function of the contribution set
+    /// regardless of the order contributions arrive in.
+    fn beats(&self, other: &Winner) -> bool {
+        let nonce = u64::from_le_bytes(self.solution.n);
+        let other_nonce = u64::from_le_bytes(other.solution.n);
+        self.difficulty
+            .cmp(&other.difficulty)
+            .then(other_nonce.cmp(&nonce))
+            .is_gt()
+    }
+}
+
+/// A validated contribution on its way to the aggregator,
+/// with where to reply what became of it.
+#[derive(Debug)]
+pub struct Forwarded {
+    pub contribution: Contribution,
+    pub reply: tokio::sync::oneshot::Sender<Inserted>,
+}
+
+/// What became of a contribution on insert.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Inserted {
+    /// Added to the round, or replaced the member's previous contribution.
+    Accepted,
+
+    /// The member already contributed a solution at least as difficult.
+    Superseded,
+
+    /// Another member already contributed the same solution.
+    DuplicateSolution,
 }
 
 /// A recorded contribution from a particular member of the pool.
@@ -80,6 +323,13 @@ pub struct Contribution {
 
     /// The drillx solution submitted representing the member's best hash.
     pub solution: Solution,
+
+    /// The request the solution was contributed in, for correlating logs.
+    pub request_id: RequestId,
+
+    /// The milliseconds since the round started at which the contribution arrived,
+    /// set on insert.
+    pub received_at: u64,
 }
 
 impl PartialEq for Contribution {
@@ -96,53 +346,85 @@ impl Hash for Contribution {
     }
 }
 
+/// Correlates the logs of a round's distribution, keyed by the round.
+fn distribution_span(rewards: &Rewards) -> tracing::Span {
+    tracing::info_span!("distribution", last_hash_at = rewards.last_hash_at)
+}
+
+/// Correlates the aggregator's logs of a contribution with the request it arrived in.
+fn contribution_span(contribution: &Contribution) -> tracing::Span {
+    tracing::info_span!("contribution", request_id = %contribution.request_id)
+}
+
 pub async fn process_contributions(
     aggregator: &tokio::sync::RwLock<Aggregator>,
     operator: &Operator,
-    rx: &mut tokio::sync::mpsc::UnboundedReceiver<Contribution>,
+    rx: &mut tokio::sync::mpsc::Receiver<Forwarded>,
+    shutdown: &tokio::sync::Notify,
 ) -> Result<(), Error> {
     // outer loop for new challenges
     loop {
-        let timer = tokio::time::Instant::now();
-        let cutoff_time = {
-            let proof = match operator.get_proof().await {
-                Ok(proof) => proof,
-                Err(err) => {
-                    log::error!("{:?}", err);
-                    continue;
-                }
+        // the round is collected against this challenge,
+        // if it moves on out of band, e.g. on a forced submit, the loop starts over
+        let (epoch, mut challenges) = {
+            let read = aggregator.read().await;
+            (read.challenge.epoch, read.challenge_tx.subscribe())
+        };
+        let fetch_cutoff = || async {
+            // in a dry run the on-chain proof never advances,
+            // so rounds are timed off the locally advanced challenge
+            let last_hash_at = if operator.dry_run {
+                aggregator.read().await.challenge.lash_hash_at
+            } else {
+                operator.get_proof().await?.last_hash_at
             };
-            match operator.get_cutoff(&proof).await {
-                Ok(cutoff_time) => cutoff_time,
-                Err(err) => {
-                    log::error!("{:?}", err);
-                    continue;
-                }
+            operator.get_cutoff_window_at(last_hash_at).await
+        };
+        // a zero cutoff inside the cutoff window before any score arrived usually means
+        // the proof was read right at the boundary of the reset, so fetch the challenge
+        // again rather than closing the round on its first contribution.
+        // past the on-chain deadline the pool has been idle, and the zero cutoff is final.
+        // score left over from a failed submit is submitted right away
+        let max_checks = match aggregator.read().await.total_score {
+            0 => ZERO_CUTOFF_MAX_CHECKS,
+            _ => 1,
+        };
+        let delay = tokio::time::Duration::from_secs(ZERO_CUTOFF_POLL_SECS);
+        let (timer, cutoff_time) = match wait_for_cutoff(fetch_cutoff, max_checks, delay).await {
+            Ok(cutoff) => cutoff,
+            Err(err) => {
+                log::error!("{:?}", err);
+                continue;
             }
         };
-        let mut remaining_time = cutoff_time.saturating_sub(timer.elapsed().as_secs());
-        // inner loop to process contributions until cutoff time
-        while remaining_time > 0 {
-            // race the next contribution against remaining time
-            match tokio::time::timeout(tokio::time::Duration::from_secs(remaining_time), rx.recv())
-                .await
-            {
-                Ok(Some(mut contribution)) => {
-                    {
-                        let mut aggregator = aggregator.write().await;
-                        let _ = aggregator.insert(&mut contribution);
-                    }
-                    // recalculate the remaining time after processing the contribution
-                    remaining_time = cutoff_time.saturating_sub(timer.elapsed().as_secs());
-                }
-                Ok(None) => {
-                    // if the receiver is closed, exit server
-                    return Err(Error::Internal("contribution channel closed".to_string()));
-                }
-                Err(_) => {
-                    // timeout expired, meaning cutoff time has been reached
-                    break;
-                }
+        // accept late contributions into the cutoff margin,
+        // as far as the observed submit latency allows
+        let grace = operator.late_grace();
+        if grace > 0 {
+            log::info!("late grace: {}", grace);
+        }
+        let cutoff_time = cutoff_time.saturating_add(grace);
+        // close early once the score still expected to arrive is negligible,
+        // but never before the first contribution
+        let early_stop = aggregator.read().await.arrivals.early_stop();
+        metrics::set_adaptive_cutoff(early_stop);
+        if let Some(early_stop) = early_stop {
+            log::info!("adaptive cutoff: {}", early_stop);
+        }
+        let clock = RoundClock {
+            timer,
+            cutoff_time,
+            early_stop,
+        };
+        match collect_round(aggregator, rx, shutdown, &mut challenges, epoch, &clock).await? {
+            RoundEnd::Cutoff => {}
+            RoundEnd::Reset => {
+                log::info!("round reset out of band, starting over against the new challenge");
+                continue;
+            }
+            RoundEnd::Shutdown => {
+                log::info!("shutting down contributions");
+                return Ok(());
             }
         }
         // at this point, the cutoff time has been reached
@@ -153,14 +435,42 @@ pub async fn process_contributions(
         if total_score > 0 {
             // submit if contributions exist
             let mut aggregator = aggregator.write().await;
+            if aggregator.challenge.epoch != epoch {
+                log::info!("round reset out of band, starting over against the new challenge");
+                continue;
+            }
             if let Err(err) = aggregator.submit_and_reset(operator).await {
                 log::error!("{:?}", err);
             }
         } else {
             // no contributions yet, wait for the first one to submit
-            if let Some(mut contribution) = rx.recv().await {
+            let next = tokio::select! {
+                _ = shutdown.notified() => {
+                    log::info!("shutting down contributions");
+                    return Ok(());
+                }
+                _ = round_reset(&mut challenges, epoch) => {
+                    log::info!("round reset out of band, starting over against the new challenge");
+                    continue;
+                }
+                next = rx.recv() => next,
+            };
+            if let Some(Forwarded {
+                mut contribution,
+                reply,
+            }) = next
+            {
                 let mut aggregator = aggregator.write().await;
-                let _ = aggregator.insert(&mut contribution);
+                {
+                    let span = contribution_span(&contribution);
+                    let _enter = span.enter();
+                    aggregator.insert_and_reply(&mut contribution, reply);
+                }
+                // the contribution went into the new round if it was reset in the meantime,
+                // which then waits for its own cutoff
+                if aggregator.challenge.epoch != epoch {
+                    continue;
+                }
                 if let Err(err) = aggregator.submit_and_reset(operator).await {
                     log::error!("{:?}", err);
                 }
@@ -169,12 +479,243 @@ pub async fn process_contributions(
     }
 }
 
+/// When a round closes, timed from when its cutoff was fetched.
+struct RoundClock {
+    timer: tokio::time::Instant,
+    cutoff_time: u64,
+    early_stop: Option<u64>,
+}
+
+impl RoundClock {
+    /// Seconds from the timer to the close of the round,
+    /// early once the round has a score if the adaptive cutoff allows.
+    fn deadline(&self, has_score: bool) -> u64 {
+        match self.early_stop {
+            Some(early_stop) if has_score => early_stop.min(self.cutoff_time),
+            _ => self.cutoff_time,
+        }
+    }
+
+    fn deadline_at(&self, has_score: bool) -> tokio::time::Instant {
+        self.timer + tokio::time::Duration::from_secs(self.deadline(has_score))
+    }
+
+    fn remaining_time(&self, has_score: bool) -> u64 {
+        self.deadline(has_score)
+            .saturating_sub(self.timer.elapsed().as_secs())
+    }
+}
+
+/// How collecting the contributions of a round ended.
+#[derive(Debug, PartialEq, Eq)]
+enum RoundEnd {
+    /// The round's deadline passed.
+    Cutoff,
+
+    /// The challenge moved on out of band, e.g. on a forced submit,
+    /// so the round's deadline no longer applies.
+    Reset,
+
+    Shutdown,
+}
+
+/// Inserts contributions into the round until its deadline passes,
+/// or until the round is reset out of band.
+async fn collect_round(
+    aggregator: &tokio::sync::RwLock<Aggregator>,
+    rx: &mut tokio::sync::mpsc::Receiver<Forwarded>,
+    shutdown: &tokio::sync::Notify,
+    challenges: &mut tokio::sync::broadcast::Receiver<Challenge>,
+    epoch: u64,
+    clock: &RoundClock,
+) -> Result<RoundEnd, Error> {
+    let timer = clock.timer;
+    let mut has_score = false;
+    aggregator.write().await.deadline = Some(clock.deadline_at(has_score));
+    let mut remaining_time = clock.remaining_time(has_score);
+    // process contributions until cutoff time
+    while remaining_time > 0 {
+        // race the next contribution against remaining time
+        let next = tokio::select! {
+            _ = shutdown.notified() => return Ok(RoundEnd::Shutdown),
+            _ = round_reset(challenges, epoch) => return Ok(RoundEnd::Reset),
+            next = tokio::time::timeout(
+                tokio::time::Duration::from_secs(remaining_time),
+                rx.recv(),
+            ) => next,
+        };
+        match next {
+            Ok(Some(Forwarded {
+                mut contribution,
+                reply,
+            })) => {
+                let mut aggregator = aggregator.write().await;
+                let span = contribution_span(&contribution);
+                let _enter = span.enter();
+                let total_score = aggregator.total_score;
+                aggregator.insert_and_reply(&mut contribution, reply);
+                let score = aggregator.total_score.saturating_sub(total_score);
+                if score > 0 {
+                    let offset = timer.elapsed().as_secs();
+                    aggregator.arrivals.record(offset, score);
+                    metrics::observe_arrival(offset);
+                }
+                has_score = aggregator.total_score > 0;
+                aggregator.deadline = Some(clock.deadline_at(has_score));
+            }
+            Ok(None) => {
+                // if the receiver is closed, exit server
+                return Err(Error::Internal("contribution channel closed".to_string()));
+            }
+            Err(_) => {
+                // timeout expired, meaning cutoff time has been reached
+                break;
+            }
+        }
+        // recalculate the remaining time after processing the contribution
+        remaining_time = clock.remaining_time(has_score);
+    }
+    if timer.elapsed().as_secs() < clock.cutoff_time {
+        // the rest of the round was never observed
+        aggregator.write().await.arrivals.censor();
+    }
+    Ok(RoundEnd::Cutoff)
+}
+
+/// Resolves once the challenge moves on past the given epoch.
+/// Challenges published within the epoch, e.g. on a min difficulty override, are skipped.
+async fn round_reset(challenges: &mut tokio::sync::broadcast::Receiver<Challenge>, epoch: u64) {
+    loop {
+        match challenges.recv().await {
+            Ok(challenge) if challenge.epoch != epoch => return,
+            Ok(_) | Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {}
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                std::future::pending::<()>().await
+            }
+        }
+    }
+}
+
+/// The on-chain state the aggregator starts up against.
+#[derive(Debug, PartialEq, Eq)]
+enum Recovery {
+    /// The pool has never submitted.
+    Fresh,
+
+    /// The last landed submit opened the current challenge,
+    /// so the restart happened before it was submitted.
+    Resumed,
+
+    /// The proof advanced past the pool's last submit,
+    /// which only a submit outside of this server can do.
+    Diverged,
+}
+
+fn recovery(pool_last_hash_at: i64, total_submissions: u64, proof_last_hash_at: i64) -> Recovery {
+    if total_submissions == 0 {
+        Recovery::Fresh
+    } else if pool_last_hash_at == proof_last_hash_at {
+        Recovery::Resumed
+    } else {
+        Recovery::Diverged
+    }
+}
+
+/// Polls until the pool's accounts exist, e.g. on a fresh deployment
+/// where the server is started before the admin `init` command has run.
+/// Errors fetching the accounts are returned rather than retried.
+async fn wait_for_initialization<T, F, Fut>(
+    fetch: F,
+    delay: fn(u32) -> std::time::Duration,
+) -> Result<T, Error>
+where
+    F: Fn() -> Fut,
+    Fut: std::future::Future<Output = Result<Option<T>, Error>>,
+{
+    let mut checks = 0;
+    loop {
+        if let Some(accounts) = fetch().await? {
+            return Ok(accounts);
+        }
+        let delay = delay(checks);
+        log::warn!(
+            "pool or proof account not found, waiting for the pool to be initialized (admin init), checking again in {:?}",
+            delay
+        );
+        tokio::time::sleep(delay).await;
+        checks = checks.saturating_add(1);
+    }
+}
+
+/// Fetches the cutoff, fetching again after the delay while it's zero
+/// and the round's deadline is still ahead, at most `max_checks` times.
+/// Returns the cutoff with when it was fetched, which is zero if it never moved off zero.
+async fn wait_for_cutoff<F, Fut>(
+    fetch: F,
+    max_checks: u32,
+    delay: std::time::Duration,
+) -> Result<(tokio::time::Instant, u64), Error>
+where
+    F: Fn() -> Fut,
+    Fut: std::future::Future<Output = Result<(u64, bool), Error>>,
+{
+    let mut checks = 0;
+    loop {
+        let timer = tokio::time::Instant::now();
+        let (cutoff_time, in_window) = fetch().await?;
+        checks += 1;
+        if cutoff_time > 0 || !in_window || checks >= max_checks {
+            return Ok((timer, cutoff_time));
+        }
+        log::warn!(
+            "zero cutoff at round start, fetching the challenge again in {:?}",
+            delay
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
+fn initialization_delay(checks: u32) -> std::time::Duration {
+    let secs = INIT_POLL_BASE_SECS
+        .saturating_mul(2u64.saturating_pow(checks))
+        .min(INIT_POLL_MAX_SECS);
+    std::time::Duration::from_secs(secs)
+}
+
 impl Aggregator {
-    pub async fn new(operator: &Operator) -> Result<Self, Error> {
+    /// Builds the aggregator against the current on-chain challenge.
+    ///
+    /// Contributions are only held in memory, so a restart mid-round loses them,
+    /// but it can't cause a duplicate submit: every landed submit advances the proof,
+    /// so the challenge fetched here has never been submitted.
+    /// If the restart happened mid-round the round is resumed against the same challenge,
+    /// and members that contribute again are included.
+    /// If the round's cutoff already passed, the first contribution is submitted straight away.
+    /// If the restart happened between a submit landing and its rewards webhook,
+    /// that round's contributions are gone and its rewards can't be attributed.
+    /// If the pool hasn't been initialized yet, waits until it is.
+    pub async fn new(operator: &Operator, config: &Config) -> Result<Self, Error> {
         // fetch accounts
-        let pool = operator.get_pool().await?;
-        let proof = operator.get_proof().await?;
+        let (pool, proof) =
+            wait_for_initialization(|| operator.find_pool_and_proof(), initialization_delay)
+                .await?;
         log::info!("proof: {:?}", proof);
+        match recovery(
+            pool.last_hash_at,
+            pool.total_submissions,
+            proof.last_hash_at,
+        ) {
+            Recovery::Fresh => log::info!("pool has not submitted yet"),
+            Recovery::Resumed => log::info!(
+                "resuming round: {}, contributions received before restart are lost",
+                proof.last_hash_at
+            ),
+            Recovery::Diverged => log::warn!(
+                "proof advanced past the pool's last submit: {} -> {}",
+                pool.last_hash_at,
+                proof.last_hash_at
+            ),
+        }
         let cutoff_time = operator.get_cutoff(&proof).await?;
         let min_difficulty = operator.min_difficulty().await?;
         let challenge = Challenge {
@@ -182,14 +723,52 @@ impl Aggregator {
             lash_hash_at: proof.last_hash_at,
             min_difficulty,
             cutoff_time,
+            min_score: operator.config().min_score,
+            epoch: seed_epoch(proof.last_hash_at),
         };
-        // fetch staker balances
-        let mut stake: Stakers = HashMap::new();
-        let boost_acounts = operator.boost_accounts.iter();
-        for ba in boost_acounts {
-            let stakers = operator.get_stakers_onchain(&ba.mint).await?;
-            stake.insert(ba.mint, stakers);
-        }
+        // fetch staker balances,
+        // refreshed from here on by the stake refresh loop
+        let stake = stake::fetch(operator).await?;
+        // build attestation client
+        let attestation_client = match config.s3.as_ref() {
+            Some(s3) => Some(attestation::Client::new(s3).await),
+            None => {
+                log::info!("s3 bucket not configured, attestations will not be published");
+                None
+            }
+        };
+        // challenge history
+        let history_size = config.challenge_history_size;
+        log::info!("challenge history size: {}", history_size);
+        // forensics log
+        let forensics_size = config.forensics_log_size;
+        log::info!("forensics log size: {}", forensics_size);
+        // arrival times in the attestation
+        log::info!("attest arrivals: {}", config.attest_arrivals);
+        // rewards timeout
+        let rewards_timeout = config.rewards_timeout;
+        log::info!("rewards timeout: {:?}", rewards_timeout);
+        // adaptive cutoff
+        let arrivals = ArrivalCurve::new(config.adaptive_cutoff_window);
+        log::info!("adaptive cutoff window: {}", config.adaptive_cutoff_window);
+        // attributions held back from previous rounds
+        let carry_over = {
+            let db_client = operator.db_client.get().await?;
+            database::read_carry_over(&db_client).await?
+        };
+        log::info!("members with carry-over: {}", carry_over.len());
+        // when each member first contributed
+        let first_seen = {
+            let db_client = operator.db_client.get().await?;
+            database::read_first_seen(&db_client).await?
+        };
+        log::info!("members seen: {}", first_seen.len());
+        // pause contributions after repeated submit failures
+        log::info!(
+            "submit failure threshold: {}",
+            config.submit_failure_threshold
+        );
+        let breaker = Breaker::new(config.submit_failure_threshold);
         // build self
         let mut contributions = HashMap::new();
         contributions.insert(challenge.lash_hash_at as u64, HashSet::new());
@@ -198,192 +777,831 @@ impl Aggregator {
             contributions,
             total_score: 0,
             winner: None,
+            winner_strategy: operator.winner_strategy,
+            max_member_score_pct: operator.max_member_score_pct,
+            score_curve: config.score_curve,
             num_members: pool.last_total_members,
             stake,
+            attestation_client,
+            history: VecDeque::with_capacity(history_size),
+            history_size,
+            reward_samples: VecDeque::with_capacity(REWARD_SAMPLES_SIZE),
+            forensics: VecDeque::with_capacity(forensics_size),
+            forensics_size,
+            challenge_tx: tokio::sync::broadcast::channel(CHALLENGE_CHANNEL_CAPACITY).0,
+            pending_rewards: HashMap::new(),
+            rewards_timeout,
+            arrivals,
+            solutions: HashSet::new(),
+            carry_over,
+            first_seen,
+            difficulties: DifficultyHistogram::default(),
+            contributions_hash: ContributionsHash::default(),
+            round_started: std::time::Instant::now(),
+            attest_arrivals: config.attest_arrivals,
+            protocol_min_difficulty: min_difficulty,
+            min_difficulty_override: None,
+            breaker,
+            deadline: None,
         };
+        metrics::set_total_score(0);
+        metrics::set_num_members(aggregator.num_members);
         Ok(aggregator)
     }
 
-    fn insert(&mut self, contribution: &mut Contribution) -> Result<(), Error> {
+    /// Copies primitive fields out of the aggregator
+    /// so the read lock can be released quickly.
+    pub fn stats(&self) -> PoolStats {
+        let last_hash_at = self.challenge.lash_hash_at;
+        let num_contributions = self
+            .contributions
+            .get(&(last_hash_at as u64))
+            .map(|c| c.len())
+            .unwrap_or(0);
+        // the same deadline the contribution loop closes the round at
+        let remaining_time = self
+            .deadline
+            .map(|deadline| {
+                deadline
+                    .saturating_duration_since(tokio::time::Instant::now())
+                    .as_secs()
+            })
+            .unwrap_or(0);
+        PoolStats {
+            last_hash_at,
+            total_score: self.total_score,
+            num_members: self.num_members,
+            num_contributions,
+            winner_difficulty: self.winner.map(|w| w.difficulty),
+            cutoff_time: self.challenge.cutoff_time,
+            remaining_time,
+            paused: self.breaker.is_open(),
+            consecutive_submit_failures: self.breaker.consecutive_failures(),
+        }
+    }
+
+    /// Totals the cached stake of each boost, keyed by boost mint.
+    pub fn stake_totals(&self, config: &ReloadableConfig) -> HashMap<String, BoostStake> {
+        self.stake
+            .iter()
+            .map(|(mint, balances)| {
+                let stake = BoostStake {
+                    total_staked: balances.values().sum(),
+                    num_stakers: balances.values().filter(|balance| **balance > 0).count(),
+                    staker_commission: config.staker_commission_for(mint),
+                };
+                (mint.to_string(), stake)
+            })
+            .collect()
+    }
+
+    /// Looks up the member's contribution to the current challenge.
+    pub fn get_contribution(&self, member: &Pubkey) -> Option<Contribution> {
+        let last_hash_at = self.challenge.lash_hash_at as u64;
+        let contributions = self.contributions.get(&last_hash_at)?;
+        // contributions hash and compare on member only
+        let probe = Contribution {
+            member: *member,
+            score: 0,
+            solution: Solution::new([0; 16], [0; 8]),
+            request_id: RequestId::default(),
+            received_at: 0,
+        };
+        contributions.get(&probe).copied()
+    }
+
+    /// Whether or not the solution is the current best solution.
+    pub fn is_winner(&self, solution: &Solution) -> bool {
+        self.winner
+            .map(|w| w.solution.d == solution.d && w.solution.n == solution.n)
+            .unwrap_or(false)
+    }
+
+    /// Inserts a forwarded contribution, replying what became of it.
+    /// The reply is dropped if the insert fails, so the contribution goes unanswered.
+    fn insert_and_reply(
+        &mut self,
+        contribution: &mut Contribution,
+        reply: tokio::sync::oneshot::Sender<Inserted>,
+    ) {
+        match self.insert(contribution) {
+            // the request may have been dropped in the meantime
+            Ok(inserted) => {
+                let _ = reply.send(inserted);
+            }
+            Err(err) => log::error!("{:?}", err),
+        }
+    }
+
+    /// Inserts the contribution, deduped by member.
+    /// If the member already contributed, the solution with the higher difficulty wins.
+    fn insert(&mut self, contribution: &mut Contribution) -> Result<Inserted, Error> {
         // normalize contribution score
         let normalized_score = contribution.score.min(MAX_SCORE);
         contribution.score = normalized_score;
+        contribution.received_at = self.round_started.elapsed().as_millis() as u64;
+        let difficulty = contribution.solution.to_hash().difficulty();
         // get current contributions
         let contributions = self.get_current_contributions()?;
-        // insert
-        let insert = contributions.insert(*contribution);
-        match insert {
-            true => {
-                let difficulty = contribution.solution.to_hash().difficulty();
-                let contender = Winner {
-                    solution: contribution.solution,
-                    difficulty,
-                };
-                self.total_score += contribution.score;
-                match self.winner {
-                    Some(winner) => {
-                        if difficulty > winner.difficulty {
-                            self.winner = Some(contender);
-                        }
-                    }
-                    None => self.winner = Some(contender),
-                }
-                Ok(())
-            }
-            false => {
+        let previous = contributions.get(&*contribution).copied();
+        if let Some(previous) = previous {
+            let previous_difficulty = previous.solution.to_hash().difficulty();
+            if difficulty <= previous_difficulty {
                 log::error!("already received contribution: {:?}", contribution.member);
-                Ok(())
+                return Ok(Inserted::Superseded);
+            }
+        }
+        // reject a solution already contributed by another member, keeping the first,
+        // since the same nonce and digest means copied work or a relay bug
+        if !self.solutions.insert(solution_key(&contribution.solution)) {
+            log::warn!("duplicate solution: {:?}", contribution.member);
+            metrics::contribution_rejected(metrics::Rejection::DuplicateSolution);
+            return Ok(Inserted::DuplicateSolution);
+        }
+        // cap the score against the running total of the other contributions
+        let others_score = self
+            .total_score
+            .saturating_sub(previous.map_or(0, |previous| previous.score));
+        if let Some(max_pct) = self.max_member_score_pct {
+            contribution.score = capped_score(contribution.score, others_score, max_pct);
+        }
+        // insert or replace
+        let contributions = self.get_current_contributions()?;
+        let previous_score = match previous {
+            Some(previous) => {
+                contributions.replace(*contribution);
+                log::info!("replaced contribution: {:?}", contribution.member);
+                let previous_difficulty = previous.solution.to_hash().difficulty();
+                let (bucket, count) = self.difficulties.remove(previous_difficulty);
+                metrics::set_round_difficulty(bucket, count);
+                previous.score
+            }
+            None => {
+                contributions.insert(*contribution);
+                0
+            }
+        };
+        let line = attestation_line(contribution, self.attest_arrivals);
+        self.contributions_hash.insert(contribution.member, line);
+        let (bucket, count) = self.difficulties.record(difficulty);
+        metrics::set_round_difficulty(bucket, count);
+        self.total_score = self
+            .total_score
+            .saturating_sub(previous_score)
+            .saturating_add(contribution.score);
+        metrics::set_total_score(self.total_score);
+        // update winner
+        let replaced_winner = match (previous, self.winner) {
+            (Some(previous), Some(winner)) => {
+                solution_key(&previous.solution) == solution_key(&winner.solution)
             }
+            _ => false,
+        };
+        if replaced_winner {
+            // the winning solution is no longer in the round,
+            // so pick again as if the contributions arrived in their current order
+            let strategy = self.winner_strategy;
+            let contributions = self.get_current_contributions()?;
+            self.winner = winner_by_arrival(strategy, contributions);
+        } else {
+            let contender = Winner {
+                solution: contribution.solution,
+                difficulty,
+                request_id: contribution.request_id,
+            };
+            self.winner = Some(contend(self.winner_strategy, self.winner, contender));
+        }
+        self.first_seen
+            .entry(contribution.member)
+            .or_insert(self.challenge.lash_hash_at);
+        self.record_forensics(contribution, difficulty);
+        Ok(Inserted::Accepted)
+    }
+
+    /// Appends an accepted contribution to the forensics log, evicting the oldest.
+    fn record_forensics(&mut self, contribution: &Contribution, difficulty: u32) {
+        if self.forensics_size == 0 {
+            return;
+        }
+        let received_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        let started_at = (self.challenge.lash_hash_at.max(0) as u64).saturating_mul(1_000);
+        let entry = ForensicsEntry {
+            member: contribution.member,
+            last_hash_at: self.challenge.lash_hash_at,
+            nonce: u64::from_le_bytes(contribution.solution.n),
+            difficulty,
+            received_at,
+            offset_ms: received_at.saturating_sub(started_at),
+        };
+        while self.forensics.len() >= self.forensics_size {
+            self.forensics.pop_front();
+        }
+        self.forensics.push_back(entry);
+    }
+
+    /// Closes the current round out of band, for unsticking a wedged aggregator.
+    /// Resets if the on-chain challenge has already moved on,
+    /// otherwise submits the best solution so far.
+    /// Either way the contribution loop sees the new challenge and starts over,
+    /// so the next round is timed from its own cutoff.
+    pub async fn force_submit(&mut self, operator: &Operator) -> Result<(), Error> {
+        if self.check_for_reset(operator).await? {
+            log::info!("force submit: on-chain challenge has changed, resetting");
+            let attestation = self.winner.map(|_| self.attestation().1);
+            return self.reset(operator, attestation).await;
         }
+        if self.winner.is_none() {
+            return Err(Error::Internal(
+                "force submit: no contributions to submit".to_string(),
+            ));
+        }
+        log::info!("force submit: submitting best solution");
+        self.submit_and_reset(operator).await
     }
 
-    // TODO Publish block to S3
+    /// Submits the winner and resets for the next challenge,
+    /// counting failures towards pausing contributions.
     async fn submit_and_reset(&mut self, operator: &Operator) -> Result<(), Error> {
+        let res = self.try_submit_and_reset(operator).await;
+        match res {
+            Ok(()) => self.breaker.record_success(),
+            Err(_) => self.breaker.record_failure(),
+        }
+        metrics::set_paused(self.breaker.is_open());
+        res
+    }
+
+    async fn try_submit_and_reset(&mut self, operator: &Operator) -> Result<(), Error> {
         // check if reset is needed
         // this may happen if a solution is landed on chain
         // but a subsequent application error is thrown before resetting
-        if self.check_for_reset(operator).await? {
-            self.reset(operator).await?;
+        if !operator.dry_run && self.check_for_reset(operator).await? {
+            let attestation = self.winner.map(|_| self.attestation().1);
+            self.reset(operator, attestation).await?;
             // there was a reset
             // so restart contribution loop against new challenge
             return Ok(());
         };
+        // snapshot the config the round is settled with,
+        // a reload only applies from the next round
+        let config = operator.config();
+        // the weighted strategy only picks once the round is closed,
+        // so that every contribution has a chance
+        if let WinnerStrategy::Weighted { min_difficulty } = self.winner_strategy {
+            let contributions = self.get_current_contributions()?;
+            let picked = weighted_winner(contributions, min_difficulty, &mut rand::thread_rng());
+            if let Some(picked) = picked {
+                self.winner = Some(picked);
+            }
+        }
+        // only a round with a winner is submitted
+        if self.close_without_winner() {
+            return Ok(());
+        }
         // prepare best solution and attestation of hash-power
         let winner = self.winner()?;
-        log::info!("winner: {:?}", winner);
+        tracing::info!(
+            last_hash_at = self.challenge.lash_hash_at,
+            difficulty = winner.difficulty,
+            request_id = %winner.request_id,
+            "winner"
+        );
         let best_solution = winner.solution;
-        let attestation = self.attestation()?;
+        let num_contributions = self.get_current_contributions()?.len();
+        log::info!("num contributions: {}", num_contributions);
+        let (block, attestation) = self.attestation();
+        match self.difficulties.summary() {
+            Some(summary) => tracing::info!(
+                min = summary.min,
+                median = summary.median,
+                p90 = summary.p90,
+                max = summary.max,
+                "difficulty"
+            ),
+            None => log::info!("no contributions"),
+        }
+        if operator.dry_run {
+            self.log_dry_run(operator, &winner, &attestation)?;
+            let last_hash_at = self.challenge.lash_hash_at as u64;
+            self.reset(operator, Some(attestation)).await?;
+            // no rewards will arrive for the round
+            self.contributions.remove(&last_hash_at);
+            return Ok(());
+        }
+        self.publish_attestation(operator, block);
         // derive accounts for instructions
-        let authority = &operator.keypair.pubkey();
-        let (pool_pda, _) = ore_pool_api::state::pool_pda(*authority);
+        let pool_pda = operator.pool;
         let (pool_proof_pda, _) = ore_pool_api::state::pool_proof_pda(pool_pda);
         let bus = self.find_bus(operator).await?;
         // build instructions
         let auth_ix = ore_api::sdk::auth(pool_proof_pda);
         let submit_ix = ore_pool_api::sdk::submit(
-            operator.keypair.pubkey(),
+            operator.signer.pubkey(),
+            pool_pda,
             best_solution,
             attestation,
             bus,
             operator.get_boost_mine_accounts(),
         );
         let rpc_client = &operator.rpc_client;
+        let (cu_limit, cu_price) = operator.submit_compute_budget(&[pool_proof_pda, bus]).await;
+        log::info!("submit cu limit: {} price: {}", cu_limit, cu_price);
+        let submit_started_at = tokio::time::Instant::now();
         let sig = tx::submit::submit_and_confirm_instructions(
-            &operator.keypair,
+            operator.signer.as_ref(),
             rpc_client,
             &[auth_ix, submit_ix],
-            1_500_000,
-            500_000,
+            cu_limit,
+            cu_price,
+            SUBMIT_RETRY,
         )
-        .await?;
-        log::info!("{:?}", sig);
+        .await
+        .inspect_err(|_| metrics::submit_failed())?;
+        metrics::submit_landed();
+        operator.record_submit_latency(submit_started_at.elapsed());
+        tracing::info!(
+            last_hash_at = self.challenge.lash_hash_at,
+            signature = %sig,
+            total_score = self.total_score,
+            "submit landed"
+        );
+        // track the round until its rewards arrive,
+        // whether the submit settled is only checked once they're attributed.
+        // reading back the pool reward as a fallback in case they never do.
+        // the read is only trusted if the pool posts this round's attestation,
+        // a failure here is logged and never fails the landed submit.
+        let reward = match operator.get_pool().await {
+            Ok(pool) => {
+                let reward = confirmed_reward(&pool, &attestation);
+                if reward.is_none() {
+                    log::error!(
+                        "pool attestation {:?} does not match the submitted round",
+                        pool.attestation
+                    );
+                    metrics::attestation_unconfirmed();
+                }
+                reward
+            }
+            Err(err) => {
+                log::error!("failed to read pool reward after submit: {:?}", err);
+                None
+            }
+        };
+        self.pending_rewards.insert(
+            self.challenge.lash_hash_at as u64,
+            PendingRewards {
+                submitted_at: std::time::Instant::now(),
+                config,
+                reward,
+                held: None,
+            },
+        );
         // reset
-        self.reset(operator).await?;
+        self.reset(operator, Some(attestation)).await?;
         Ok(())
     }
 
-    pub async fn distribute_rewards(
-        &mut self,
+    /// Expires the submitted rounds whose rewards webhook hasn't arrived within the timeout,
+    /// so that their contributions don't pile up in memory forever.
+    /// If the rewards fallback is enabled, the round is distributed from the pool reward
+    /// read back after its submit, attributing the whole reward to miners,
+    /// otherwise the round is dropped and must be reconciled by hand.
+    pub async fn expire_pending_rewards(&mut self, operator: &Operator) -> Result<(), Error> {
+        self.release_held_rewards(operator).await;
+        let now = std::time::Instant::now();
+        for last_hash_at in expired_rounds(&self.pending_rewards, now, self.rewards_timeout) {
+            let Some(pending) = self.pending_rewards.remove(&last_hash_at) else {
+                continue;
+            };
+            log::error!(
+                "rewards for round {} not received within {:?}",
+                last_hash_at,
+                self.rewards_timeout
+            );
+            match (operator.rewards_fallback, pending.reward) {
+                (true, Some(reward)) => {
+                    if let Err(err) = self.verify_settled(operator, last_hash_at).await {
+                        log::error!("{:?}", err);
+                        log::error!("dropping contributions for round {}", last_hash_at);
+                        self.contributions.remove(&last_hash_at);
+                        continue;
+                    }
+                    log::warn!(
+                        "distributing round {} from on-chain pool reward: {}",
+                        last_hash_at,
+                        reward
+                    );
+                    let rewards = Rewards {
+                        base: reward,
+                        boost_1: None,
+                        boost_2: None,
+                        boost_3: None,
+                        last_hash_at,
+                    };
+                    if let Err(err) = self
+                        .distribute_rewards_with(operator, pending.config.as_ref(), &rewards)
+                        .instrument(distribution_span(&rewards))
+                        .await
+                    {
+                        log::error!("fallback distribution failed: {:?}", err);
+                        self.contributions.remove(&last_hash_at);
+                    }
+                }
+                _ => {
+                    log::error!("dropping contributions for round {}", last_hash_at);
+                    self.contributions.remove(&last_hash_at);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Logs what would have been submitted and how a nominal reward of one ORE
+    /// would be attributed.
+    fn log_dry_run(
+        &self,
         operator: &Operator,
-        rewards: &Rewards,
+        winner: &Winner,
+        attestation: &[u8; 32],
     ) -> Result<(), Error> {
-        let (pool_pda, _) = ore_pool_api::state::pool_pda(operator.keypair.pubkey());
-        // compute attributions for miners
-        log::info!("reward: {:?}", rewards);
-        log::info!("// miner ////////////////////////");
+        let last_hash_at = self.challenge.lash_hash_at as u64;
+        log::info!("// dry run ////////////////////////");
+        log::info!("winner difficulty: {}", winner.difficulty);
+        log::info!("attestation: {:?}", attestation);
+        let rewards = Rewards {
+            base: ONE_ORE,
+            boost_1: None,
+            boost_2: None,
+            boost_3: None,
+            last_hash_at,
+        };
+        let pool_pda = operator.pool;
+        let config = operator.config();
+        let operator_commission = config.effective_commission(self.total_score);
+        let mut addresses = MemberAddresses::new();
+        let distribution = self.rewards_distribution(
+            pool_pda,
+            &rewards,
+            operator_commission,
+            config.as_ref(),
+            operator.max_member_score_pct,
+            operator.min_member_age_secs,
+            &mut addresses,
+        )?;
+        for (member, reward) in distribution.iter() {
+            tracing::info!(member = %member, reward, "dry run reward");
+        }
+        let (operator_member, operator_reward) = self.rewards_distribution_operator(
+            pool_pda,
+            operator.signer.pubkey(),
+            &rewards,
+            &[distribution.as_slice()],
+            &mut addresses,
+        )?;
+        tracing::info!(
+            member = %operator_member,
+            reward = operator_reward,
+            "dry run operator reward"
+        );
+        Ok(())
+    }
+
+    /// Distributes the rewards of a submitted round,
+    /// with the config as of its submit.
+    pub async fn distribute_rewards(
+        &mut self,
+        operator: &Operator,
+        rewards: &Rewards,
+    ) -> Result<(), Error> {
+        // a round that isn't settled yet is held for the sweep, and expires if it never is
+        if let Err(err) = self.verify_settled(operator, rewards.last_hash_at).await {
+            if let Some(pending) = self.pending_rewards.get_mut(&rewards.last_hash_at) {
+                log::warn!(
+                    "holding rewards for round {} until its submit settles: {:?}",
+                    rewards.last_hash_at,
+                    err
+                );
+                pending.held = Some(rewards.clone());
+                return Ok(());
+            }
+            return Err(err);
+        }
+        let config = match self.pending_rewards.remove(&rewards.last_hash_at) {
+            Some(pending) => pending.config,
+            None => operator.config(),
+        };
+        self.distribute_rewards_with(operator, config.as_ref(), rewards)
+            .instrument(distribution_span(rewards))
+            .await
+    }
+
+    /// Distributes the held rewards of the rounds whose submit has settled since they arrived.
+    async fn release_held_rewards(&mut self, operator: &Operator) {
+        let mut held: Vec<LastHashAt> = self
+            .pending_rewards
+            .iter()
+            .filter(|(_, p)| p.held.is_some())
+            .map(|(last_hash_at, _)| *last_hash_at)
+            .collect();
+        held.sort_unstable();
+        for last_hash_at in held {
+            match operator.submit_settled(last_hash_at as i64).await {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(err) => {
+                    log::error!("{:?}", err);
+                    continue;
+                }
+            }
+            let Some(PendingRewards {
+                config,
+                held: Some(rewards),
+                ..
+            }) = self.pending_rewards.remove(&last_hash_at)
+            else {
+                continue;
+            };
+            log::info!("releasing held rewards for round {}", last_hash_at);
+            if let Err(err) = self
+                .distribute_rewards_with(operator, config.as_ref(), &rewards)
+                .instrument(distribution_span(&rewards))
+                .await
+            {
+                log::error!("held distribution failed: {:?}", err);
+            }
+        }
+    }
+
+    /// Re-verifies that the round's submit is settled at the rewards commitment
+    /// before its rewards are written, a submit reorged out since must not be attributed.
+    async fn verify_settled(&self, operator: &Operator, last_hash_at: u64) -> Result<(), Error> {
+        let round = last_hash_at as i64;
+        if operator.submit_settled(round).await? {
+            Ok(())
+        } else {
+            metrics::submit_unsettled();
+            Err(Error::SubmitUnsettled(round))
+        }
+    }
+
+    async fn distribute_rewards_with(
+        &mut self,
+        operator: &Operator,
+        config: &ReloadableConfig,
+        rewards: &Rewards,
+    ) -> Result<(), Error> {
+        let pool_pda = operator.pool;
+        // select operator commission for the round's total score
+        let round_score: u64 = self
+            .contributions
+            .get(&rewards.last_hash_at)
+            .map(|c| c.iter().map(|c| c.score).sum())
+            .unwrap_or(0);
+        let operator_commission = config.effective_commission(round_score);
+        tracing::info!(
+            operator_commission,
+            total_score = round_score,
+            "effective operator commission"
+        );
+        // compute attributions for miners
+        tracing::info!(
+            base = rewards.base,
+            boost_1 = rewards.boost_1.map(|b| b.reward),
+            boost_2 = rewards.boost_2.map(|b| b.reward),
+            boost_3 = rewards.boost_3.map(|b| b.reward),
+            "rewards"
+        );
+        log::info!("// miner ////////////////////////");
+        let mut addresses = MemberAddresses::new();
         let rewards_distribution = self.rewards_distribution(
             pool_pda,
             rewards,
-            operator.operator_commission,
-            operator.staker_commission,
+            operator_commission,
+            config,
+            operator.max_member_score_pct,
+            operator.min_member_age_secs,
+            &mut addresses,
         )?;
         log::info!("// staker ////////////////////////");
         // compute attributions for stakers
-        let rewards_distribution_boost_1 =
-            self.rewards_distribution_boost(pool_pda, rewards.boost_1, operator.staker_commission)?;
-        let rewards_distribution_boost_2 =
-            self.rewards_distribution_boost(pool_pda, rewards.boost_2, operator.staker_commission)?;
-        let rewards_distribution_boost_3 =
-            self.rewards_distribution_boost(pool_pda, rewards.boost_3, operator.staker_commission)?;
+        // optionally excluding the operator's own stake
+        let excluded_staker = operator
+            .exclude_operator_stake
+            .then_some(operator.signer.pubkey());
+        // and redirecting the reward of boosts without eligible stake
+        let unstaked = self.unstaked_recipients(
+            operator.unstaked_recipient,
+            operator.signer.pubkey(),
+            rewards.last_hash_at,
+        );
+        let rewards_distribution_boost_1 = self.rewards_distribution_boost(
+            pool_pda,
+            rewards.boost_1,
+            config,
+            excluded_staker,
+            unstaked.as_slice(),
+            &mut addresses,
+        )?;
+        let rewards_distribution_boost_2 = self.rewards_distribution_boost(
+            pool_pda,
+            rewards.boost_2,
+            config,
+            excluded_staker,
+            unstaked.as_slice(),
+            &mut addresses,
+        )?;
+        let rewards_distribution_boost_3 = self.rewards_distribution_boost(
+            pool_pda,
+            rewards.boost_3,
+            config,
+            excluded_staker,
+            unstaked.as_slice(),
+            &mut addresses,
+        )?;
         log::info!("// operator ////////////////////////");
         // compute attribution for operator
         let rewards_distribution_operator = self.rewards_distribution_operator(
             pool_pda,
-            operator.keypair.pubkey(),
+            operator.signer.pubkey(),
             rewards,
-            operator.operator_commission,
+            &[
+                rewards_distribution.as_slice(),
+                rewards_distribution_boost_1.as_slice(),
+                rewards_distribution_boost_2.as_slice(),
+                rewards_distribution_boost_3.as_slice(),
+            ],
+            &mut addresses,
+        )?;
+        // hold back dust attributions
+        let operator_member = rewards_distribution_operator.0.clone();
+        let settlement = settle_dust(
+            vec![
+                rewards_distribution,
+                rewards_distribution_boost_1,
+                rewards_distribution_boost_2,
+                rewards_distribution_boost_3,
+                vec![rewards_distribution_operator],
+            ],
+            &self.carry_over,
+            operator.min_attribution,
+            operator.dust_recipient,
+            operator_member.as_str(),
         );
         // write rewards to db
+        let last_hash_at = rewards.last_hash_at as i64;
         let mut db_client = operator.db_client.get().await?;
-        database::write_member_total_balances(&mut db_client, rewards_distribution).await?;
-        database::write_member_total_balances(&mut db_client, rewards_distribution_boost_1).await?;
-        database::write_member_total_balances(&mut db_client, rewards_distribution_boost_2).await?;
-        database::write_member_total_balances(&mut db_client, rewards_distribution_boost_3).await?;
-        database::write_member_total_balances(&mut db_client, vec![rewards_distribution_operator])
-            .await?;
+        database::write_all_balances(
+            &mut db_client,
+            last_hash_at,
+            vec![settlement.balances],
+            settlement.carry_over.as_slice(),
+        )
+        .await?;
+        // and only then apply the carry-over in memory, to match the db
+        apply_carry_over(&mut self.carry_over, settlement.carry_over);
+        // sample for reward estimates
+        self.record_reward_sample(rewards, round_score);
         // clean up contributions
         let contributions = &mut self.contributions;
         let _ = contributions.remove(&rewards.last_hash_at);
         Ok(())
     }
 
+    fn record_reward_sample(&mut self, rewards: &Rewards, total_score: u64) {
+        let boost = rewards.boost_1.map(|b| b.reward).unwrap_or(0)
+            + rewards.boost_2.map(|b| b.reward).unwrap_or(0)
+            + rewards.boost_3.map(|b| b.reward).unwrap_or(0);
+        let sample = RewardSample {
+            mine: rewards.base.saturating_sub(boost),
+            boost,
+            total_score,
+        };
+        while self.reward_samples.len() >= REWARD_SAMPLES_SIZE {
+            self.reward_samples.pop_front();
+        }
+        self.reward_samples.push_back(sample);
+    }
+
+    /// Estimates the per-round reward of a contribution of the difficulty,
+    /// from the average of the recently distributed rounds.
+    /// None until a round has been distributed.
+    pub fn estimate_reward(&self, operator: &Operator, difficulty: u32) -> Option<RewardEstimate> {
+        let rounds_sampled = self.reward_samples.len();
+        if rounds_sampled == 0 {
+            return None;
+        }
+        let average = |f: fn(&RewardSample) -> u64| -> u64 {
+            let sum: u128 = self.reward_samples.iter().map(|s| f(s) as u128).sum();
+            (sum / rounds_sampled as u128) as u64
+        };
+        let mine = average(|s| s.mine);
+        let boost = average(|s| s.boost);
+        let average_round_score = average(|s| s.total_score);
+        let score = self.score_curve.score(difficulty);
+        let round_score = average_round_score.saturating_add(score);
+        let config = operator.config();
+        let operator_commission = config.effective_commission(round_score);
+        let average_miner_rewards =
+            miner_rewards(mine, boost, operator_commission, config.staker_commission);
+        let estimated_reward = if round_score == 0 {
+            0
+        } else {
+            (average_miner_rewards as u128 * score as u128 / round_score as u128) as u64
+        };
+        Some(RewardEstimate {
+            difficulty,
+            score,
+            average_round_score,
+            average_miner_rewards,
+            estimated_reward,
+            rounds_sampled,
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn rewards_distribution(
         &self,
         pool: Pubkey,
         rewards: &Rewards,
         operator_commission: u64,
-        staker_commission: u64,
+        config: &ReloadableConfig,
+        max_member_score_pct: Option<u64>,
+        min_member_age_secs: u64,
+        addresses: &mut MemberAddresses,
     ) -> Result<Vec<(String, u64)>, Error> {
-        let contributions = &self.contributions;
-        let contributions = contributions
-            .get(&rewards.last_hash_at)
-            .ok_or(Error::Internal(
-                "missing contributions at reward hash".to_string(),
-            ))?;
-        // compute denominator
-        let denominator: u64 = contributions.iter().map(|c| c.score).sum();
-        let denominator: u128 = denominator as u128;
+        // sorted so that the remainder goes to the same member whatever the set's order
+        let contributions = sorted_by_member(self.contributions.get(&rewards.last_hash_at).ok_or(
+            Error::Internal("missing contributions at reward hash".to_string()),
+        )?);
+        // redistribute the share of members below the min age
+        let scores: Vec<u64> = contributions.iter().map(|c| c.score).collect();
+        let scores = if min_member_age_secs > 0 {
+            let first_seen: Vec<i64> = contributions
+                .iter()
+                .map(|c| {
+                    self.first_seen
+                        .get(&c.member)
+                        .copied()
+                        .unwrap_or(rewards.last_hash_at as i64)
+                })
+                .collect();
+            tenured_scores(
+                scores,
+                first_seen.as_slice(),
+                rewards.last_hash_at as i64,
+                min_member_age_secs,
+            )
+        } else {
+            scores
+        };
+        // cap scores
+        let scores = match max_member_score_pct {
+            Some(max_pct) => cap_scores(scores.as_slice(), max_pct),
+            None => scores,
+        };
         // compute base mine rewards
-        let mine_rewards = rewards.base
-            - rewards.boost_1.map(|b| b.reward).unwrap_or(0)
-            - rewards.boost_2.map(|b| b.reward).unwrap_or(0)
-            - rewards.boost_3.map(|b| b.reward).unwrap_or(0);
-        log::info!("base reward denominator: {}", denominator);
+        let mine_rewards = mine_rewards(rewards)?;
         // compute miner split
-        let miner_commission = 100 - operator_commission;
-        log::info!("miner commission: {}", miner_commission);
-        let miner_rewards = (mine_rewards * miner_commission / 100) as u128;
-        log::info!("miner rewards as commission for miners: {}", miner_rewards);
-        // compute miner split from stake rewards
-        let miner_rewards_from_stake_1 = Self::split_stake_rewards_for_miners(
-            rewards.boost_1,
-            operator_commission,
-            staker_commission,
-        );
-        let miner_rewards_from_stake_2 = Self::split_stake_rewards_for_miners(
-            rewards.boost_2,
-            operator_commission,
-            staker_commission,
-        );
-        let miner_rewards_from_stake_3 = Self::split_stake_rewards_for_miners(
-            rewards.boost_3,
-            operator_commission,
-            staker_commission,
+        let miner_commission = 100u64.saturating_sub(operator_commission);
+        let miner_rewards = mine_rewards as u128 * miner_commission as u128 / 100;
+        tracing::info!(
+            miner_commission,
+            miner_rewards = miner_rewards as u64,
+            "miner rewards from mine"
         );
+        // compute miner split from stake rewards
+        let miner_rewards_from_stake_1 =
+            Self::split_stake_rewards_for_miners(rewards.boost_1, operator_commission, config);
+        let miner_rewards_from_stake_2 =
+            Self::split_stake_rewards_for_miners(rewards.boost_2, operator_commission, config);
+        let miner_rewards_from_stake_3 =
+            Self::split_stake_rewards_for_miners(rewards.boost_3, operator_commission, config);
         let total_rewards = miner_rewards
             + miner_rewards_from_stake_1
             + miner_rewards_from_stake_2
             + miner_rewards_from_stake_3;
-        log::info!("total rewards as commission for miners: {}", total_rewards);
-        let contributions = contributions.iter();
+        tracing::info!(
+            total_rewards = total_rewards as u64,
+            "total rewards for miners"
+        );
+        let attributed = distribute_with_remainder(total_rewards as u64, scores.as_slice());
         let distribution = contributions
-            .map(|c| {
-                log::info!("raw base reward score: {}", c.score);
-                let score = (c.score as u128).saturating_mul(total_rewards);
-                let score = score.checked_div(denominator).unwrap_or(0);
-                log::info!("attributed base reward score: {}", score);
-                let (member_pda, _) = ore_pool_api::state::member_pda(c.member, pool);
-                (member_pda.to_string(), score as u64)
+            .iter()
+            .zip(scores)
+            .zip(attributed)
+            .map(|((c, capped_score), reward)| {
+                tracing::info!(
+                    member = %c.member,
+                    request_id = %c.request_id,
+                    score = c.score,
+                    capped_score,
+                    reward,
+                    "attributed reward"
+                );
+                (member_address(addresses, c.member, pool), reward)
             })
             .collect();
         Ok(distribution)
@@ -392,23 +1610,28 @@ impl Aggregator {
     fn split_stake_rewards_for_miners(
         boost_event: Option<ore_api::event::BoostEvent>,
         operator_commission: u64,
-        staker_commission: u64,
+        config: &ReloadableConfig,
     ) -> u128 {
         let miner_rewards_from_stake: u128 = match boost_event {
             Some(boost_event) => {
-                log::info!("{:?}", boost_event);
-                let miner_commission_for_stake: u128 =
-                    (100 - operator_commission - staker_commission) as u128;
-                log::info!("miner commission for stake: {}", miner_commission_for_stake);
+                let staker_commission = config.staker_commission_for(&boost_event.mint);
+                let miner_commission_for_stake: u128 = 100u64
+                    .saturating_sub(operator_commission)
+                    .saturating_sub(staker_commission)
+                    as u128;
                 let stake_rewards = boost_event.reward as u128;
-                stake_rewards * miner_commission_for_stake / 100
+                let miner_rewards_from_stake = stake_rewards * miner_commission_for_stake / 100;
+                tracing::info!(
+                    mint = %boost_event.mint,
+                    boost_reward = boost_event.reward,
+                    miner_commission = miner_commission_for_stake as u64,
+                    miner_rewards = miner_rewards_from_stake as u64,
+                    "miner rewards from stake"
+                );
+                miner_rewards_from_stake
             }
             None => 0,
         };
-        log::info!(
-            "stake rewards as commission for miners: {}",
-            miner_rewards_from_stake
-        );
         miner_rewards_from_stake
     }
 
@@ -416,20 +1639,24 @@ impl Aggregator {
         &self,
         pool: Pubkey,
         boost_event: Option<ore_api::event::BoostEvent>,
-        staker_commission: u64,
+        config: &ReloadableConfig,
+        excluded_staker: Option<Pubkey>,
+        unstaked: &[(Pubkey, u64)],
+        addresses: &mut MemberAddresses,
     ) -> Result<Vec<(String, u64)>, Error> {
         match boost_event {
             None => Ok(vec![]),
             Some(boost_event) => {
-                log::info!("{:?}", boost_event);
                 let total_reward = boost_event.reward as u128;
-                let staker_commission: u128 = staker_commission as u128;
-                log::info!("staker commission: {}", staker_commission);
+                let staker_commission: u128 =
+                    config.staker_commission_for(&boost_event.mint) as u128;
                 let staker_rewards = total_reward * staker_commission / 100;
-                log::info!("total rewards from stake: {}", total_reward);
-                log::info!(
-                    "total rewards as commission for stakers: {}",
-                    staker_rewards
+                tracing::info!(
+                    mint = %boost_event.mint,
+                    boost_reward = boost_event.reward,
+                    staker_commission = staker_commission as u64,
+                    staker_rewards = staker_rewards as u64,
+                    "staker rewards"
                 );
                 let stakers = self
                     .stake
@@ -438,22 +1665,48 @@ impl Aggregator {
                         "missing staker balances: {:?}",
                         boost_event.mint,
                     )))?;
-                let denominator_iter = stakers.iter();
-                let distribution_iter = stakers.iter();
-                let denominator: u64 = denominator_iter.map(|(_, balance)| balance).sum();
-                let denominator = denominator as u128;
-                log::info!("staked reward denominator: {}", denominator);
-                let res = distribution_iter
-                    .map(|(stake_authority, balance)| {
-                        log::info!("staked balance: {:?}", (stake_authority, balance));
-                        let balance = *balance as u128;
-                        let score = balance.saturating_mul(staker_rewards);
-                        log::info!("scaled score from stake: {}", score);
-                        let score = score.checked_div(denominator).unwrap_or(0);
-                        log::info!("attributed reward from stake: {}", score);
-                        let (member_pda, _) =
-                            ore_pool_api::state::member_pda(*stake_authority, pool);
-                        (member_pda.to_string(), score as u64)
+                let stakers = eligible_stakers(stakers, excluded_staker.as_ref());
+                let balances: Vec<u64> = stakers.iter().map(|(_, balance)| **balance).collect();
+                // no eligible stake to attribute to,
+                // redirect rather than drop the staker reward
+                if balances.iter().all(|balance| *balance == 0) {
+                    tracing::warn!(
+                        mint = %boost_event.mint,
+                        staker_rewards = staker_rewards as u64,
+                        "no eligible stake for boost, redirecting staker reward"
+                    );
+                    let weights: Vec<u64> = unstaked.iter().map(|(_, weight)| *weight).collect();
+                    let attributed =
+                        distribute_with_remainder(staker_rewards as u64, weights.as_slice());
+                    let res = unstaked
+                        .iter()
+                        .zip(attributed)
+                        .map(|((authority, _), reward)| {
+                            tracing::info!(
+                                mint = %boost_event.mint,
+                                authority = %authority,
+                                reward,
+                                "redirected reward from stake"
+                            );
+                            (member_address(addresses, *authority, pool), reward)
+                        })
+                        .collect();
+                    return Ok(res);
+                }
+                let attributed =
+                    distribute_with_remainder(staker_rewards as u64, balances.as_slice());
+                let res = stakers
+                    .into_iter()
+                    .zip(attributed)
+                    .map(|((stake_authority, balance), reward)| {
+                        tracing::info!(
+                            mint = %boost_event.mint,
+                            authority = %stake_authority,
+                            balance = *balance,
+                            reward,
+                            "attributed reward from stake"
+                        );
+                        (member_address(addresses, *stake_authority, pool), reward)
                     })
                     .collect();
                 Ok(res)
@@ -461,101 +1714,196 @@ impl Aggregator {
         }
     }
 
+    /// The (authority, weight) of whoever receives the staker reward of a boost without eligible stake.
+    /// Falls back to the operator if the round has no miners to redirect to.
+    fn unstaked_recipients(
+        &self,
+        recipient: UnstakedRecipient,
+        pool_authority: Pubkey,
+        last_hash_at: LastHashAt,
+    ) -> Vec<(Pubkey, u64)> {
+        let miners: Vec<(Pubkey, u64)> = match recipient {
+            UnstakedRecipient::Operator => vec![],
+            UnstakedRecipient::Miners => self
+                .contributions
+                .get(&last_hash_at)
+                .map(|c| {
+                    sorted_by_member(c)
+                        .iter()
+                        .map(|c| (c.member, c.score))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        };
+        if miners.iter().all(|(_, score)| *score == 0) {
+            return vec![(pool_authority, 1)];
+        }
+        miners
+    }
+
+    /// The operator's share of the round, whatever is left of the rewards
+    /// once miners and stakers are attributed. The operator commission is derived
+    /// rather than computed, so that it also takes the truncation of the other splits
+    /// and the attributions sum exactly to the rewards.
     fn rewards_distribution_operator(
         &self,
         pool: Pubkey,
         pool_authority: Pubkey,
         rewards: &Rewards,
-        operator_commission: u64,
-    ) -> (String, u64) {
-        // compute split from mine rewards
-        let mine_rewards = rewards.base
-            - rewards.boost_1.map(|b| b.reward).unwrap_or(0)
-            - rewards.boost_2.map(|b| b.reward).unwrap_or(0)
-            - rewards.boost_3.map(|b| b.reward).unwrap_or(0);
-        let mine_rewards = mine_rewards * operator_commission / 100;
-        // compute split from stake rewads
-        let mut stake_rewards = 0;
-        if let Some(boost_event) = rewards.boost_1 {
-            let r = boost_event.reward * operator_commission / 100;
-            log::info!(
-                "staker rewards for operator: {} from {:?}",
-                r,
-                boost_event.mint
-            );
-            stake_rewards += r;
-        }
-        if let Some(boost_event) = rewards.boost_2 {
-            let r = boost_event.reward * operator_commission / 100;
-            log::info!(
-                "staker rewards for operator: {} from {:?}",
-                r,
-                boost_event.mint
-            );
-            stake_rewards += r;
-        }
-        if let Some(boost_event) = rewards.boost_3 {
-            let r = boost_event.reward * operator_commission / 100;
-            log::info!(
-                "staker rewards for operator: {} from {:?}",
-                r,
-                boost_event.mint
-            );
-            stake_rewards += r;
-        }
-        log::info!("operator commission: {}", operator_commission);
-        log::info!("mine rewards for operator: {}", mine_rewards);
-        log::info!("stake rewards for operator: {}", stake_rewards);
-        let total_rewards = mine_rewards + stake_rewards;
-        log::info!("total rewards for operator: {}", total_rewards);
-        let (member_pda, _) = ore_pool_api::state::member_pda(pool_authority, pool);
-        (member_pda.to_string(), total_rewards)
+        attributed: &[&[(String, u64)]],
+        addresses: &mut MemberAddresses,
+    ) -> Result<(String, u64), Error> {
+        let attributed: u128 = attributed
+            .iter()
+            .flat_map(|distribution| distribution.iter())
+            .map(|(_, reward)| *reward as u128)
+            .sum();
+        let total_rewards =
+            (rewards.base as u128)
+                .checked_sub(attributed)
+                .ok_or(Error::Internal(format!(
+                    "attributed rewards exceed base rewards: {} > {}",
+                    attributed, rewards.base
+                )))? as u64;
+        tracing::info!(
+            base = rewards.base,
+            attributed = attributed as u64,
+            total_rewards,
+            "rewards for operator"
+        );
+        Ok((
+            member_address(addresses, pool_authority, pool),
+            total_rewards,
+        ))
     }
 
     async fn find_bus(&self, operator: &Operator) -> Result<Pubkey, Error> {
         // Fetch the bus with the largest balance
         let rpc_client = &operator.rpc_client;
         let accounts = rpc_client.get_multiple_accounts(&BUS_ADDRESSES).await?;
-        let mut top_bus_balance: u64 = 0;
-        let bus_index = rand::thread_rng().gen_range(0..BUS_COUNT);
-        let mut top_bus = BUS_ADDRESSES[bus_index];
-        for account in accounts.into_iter().flatten() {
-            if let Ok(bus) = Bus::try_from_bytes(&account.data) {
-                if bus.rewards.gt(&top_bus_balance) {
-                    top_bus_balance = bus.rewards;
-                    top_bus = BUS_ADDRESSES[bus.id as usize];
+        let balances = accounts
+            .into_iter()
+            .flatten()
+            .filter_map(|account| Bus::try_from_bytes(&account.data).ok().copied())
+            .map(|bus| (bus.id as usize, bus.rewards));
+        match top_bus(balances) {
+            Some((id, rewards)) => {
+                if rewards < operator.min_bus_rewards {
+                    return Err(Error::Internal(format!(
+                        "top bus rewards below floor: {} < {}",
+                        rewards, operator.min_bus_rewards
+                    )));
                 }
+                Ok(BUS_ADDRESSES[id])
+            }
+            None => {
+                // no bus could be read, so fall back to a random one
+                log::error!("no buses deserialized, selecting random bus");
+                let bus_index = rand::thread_rng().gen_range(0..BUS_COUNT);
+                Ok(BUS_ADDRESSES[bus_index])
             }
         }
-        Ok(top_bus)
     }
 
-    fn attestation(&mut self) -> Result<[u8; 32], Error> {
-        let mut hasher = Sha3_256::new();
-        let contributions = self.get_current_contributions()?;
-        let num_contributions = contributions.len();
-        log::info!("num contributions: {}", num_contributions);
-        for contribution in contributions.iter() {
-            let hex_string: String =
-                contribution
-                    .solution
-                    .d
-                    .iter()
-                    .fold(String::new(), |mut acc, byte| {
-                        acc.push_str(&format!("{:02x}", byte));
-                        acc
-                    });
-            let line = format!(
-                "{} {} {}\n",
+    /// The attestation block of the current round and its contributions hash,
+    /// see [`ContributionsHash`]. Built once when the round closes.
+    /// The attestation block is a version header followed by one line per contribution
+    /// (member, hex digest, nonce), sorted by member so that the block is canonical.
+    /// The contributions hash is the sha3 hash of this block.
+    fn attestation(&self) -> (String, [u8; 32]) {
+        self.contributions_hash.finalize()
+    }
+
+    /// The attestation block of a round whose contributions are still in memory,
+    /// as entries alongside the contributions hash over the block.
+    pub fn get_attestation(&self, last_hash_at: i64) -> Result<Option<Attestation>, Error> {
+        let contributions = match self.contributions.get(&(last_hash_at as u64)) {
+            Some(contributions) => sorted_by_member(contributions),
+            None => return Ok(None),
+        };
+        let mut hash = ContributionsHash::default();
+        let mut entries = Vec::with_capacity(contributions.len());
+        for contribution in contributions {
+            hash.insert(
                 contribution.member,
-                hex_string,
-                u64::from_le_bytes(contribution.solution.n)
+                attestation_line(contribution, self.attest_arrivals),
             );
-            hasher.update(&line);
+            entries.push(AttestationEntry {
+                member: contribution.member,
+                digest: hex_digest(&contribution.solution),
+                nonce: u64::from_le_bytes(contribution.solution.n),
+                received_at: self.attest_arrivals.then_some(contribution.received_at),
+            });
+        }
+        Ok(Some(Attestation {
+            last_hash_at,
+            version: Attestation::VERSION,
+            entries,
+            hash: hash.finalize().1,
+        }))
+    }
+
+    /// When each contribution to a round arrived, oldest first,
+    /// while the round's contributions are retained.
+    pub fn get_timeline(&self, last_hash_at: i64) -> Option<Vec<TimelineEntry>> {
+        let contributions = self.contributions.get(&(last_hash_at as u64))?;
+        let mut timeline: Vec<TimelineEntry> = contributions
+            .iter()
+            .map(|contribution| TimelineEntry {
+                member: contribution.member,
+                received_at: contribution.received_at,
+                nonce: u64::from_le_bytes(contribution.solution.n),
+                score: contribution.score,
+            })
+            .collect();
+        timeline.sort_by_key(|entry| (entry.received_at, entry.member));
+        Some(timeline)
+    }
+
+    /// Publishes the attestation block to s3 (if configured) on a separate task,
+    /// so that the submit transaction is not blocked. Failures are logged only.
+    fn publish_attestation(&self, operator: &Operator, block: String) {
+        if let Some(client) = self.attestation_client.clone() {
+            let last_hash_at = self.challenge.lash_hash_at;
+            let db_client = operator.db_client.clone();
+            tokio::task::spawn(async move {
+                match client.publish(&db_client, last_hash_at, block).await {
+                    Ok(url) => log::info!("published attestation: {}", url),
+                    Err(err) => log::error!("failed to publish attestation: {:?}", err),
+                }
+            });
+        }
+    }
+
+    /// Writes the first-seen of the members who first contributed in the current round
+    /// on a separate task. Failures are logged only, the member is then seen again later.
+    fn persist_first_seen(&self, operator: &Operator) {
+        let last_hash_at = self.challenge.lash_hash_at;
+        let Some(contributions) = self.contributions.get(&(last_hash_at as u64)) else {
+            return;
+        };
+        let pool_pda = operator.pool;
+        let addresses: Vec<String> = contributions
+            .iter()
+            .filter(|c| self.first_seen.get(&c.member) == Some(&last_hash_at))
+            .map(|c| {
+                let (member_pda, _) = ore_pool_api::state::member_pda(c.member, pool_pda);
+                member_pda.to_string()
+            })
+            .collect();
+        if addresses.is_empty() {
+            return;
         }
-        let mut attestation: [u8; 32] = [0; 32];
-        attestation.copy_from_slice(&hasher.finalize()[..]);
-        Ok(attestation)
+        let db_client = operator.db_client.clone();
+        tokio::task::spawn(async move {
+            let res = async {
+                let conn = db_client.get().await?;
+                database::write_first_seen(&conn, addresses.as_slice(), last_hash_at).await
+            };
+            if let Err(err) = res.await {
+                log::error!("failed to write first seen: {:?}", err);
+            }
+        });
     }
 
     fn get_current_contributions(&mut self) -> Result<&mut MinerContributions, Error> {
@@ -567,10 +1915,21 @@ impl Aggregator {
         Ok(contributions)
     }
 
-    async fn reset(&mut self, operator: &Operator) -> Result<(), Error> {
+    /// Closes the current round, with its contributions hash if it had a winner.
+    async fn reset(
+        &mut self,
+        operator: &Operator,
+        attestation: Option<[u8; 32]>,
+    ) -> Result<(), Error> {
         log::info!("//////////////////////////////////////////");
         log::info!("resetting");
         log::info!("//////////////////////////////////////////");
+        // retain the closing round in the history
+        self.record_round(attestation);
+        // remember who contributed for the first time
+        self.persist_first_seen(operator);
+        // learn when the closing round's contributions arrived
+        self.arrivals.finish_round();
         // update challenge
         self.update_challenge(operator).await?;
         // allocate key for new contributions
@@ -579,17 +1938,107 @@ impl Aggregator {
         log::info!("//////////////////////////////////////////");
         log::info!("new contributions key: {:?}", last_hash_at);
         log::info!("//////////////////////////////////////////");
-        if let Some(_) = contributions.insert(last_hash_at, HashSet::new()) {
-            log::error!("contributions at last-hash-at already exist");
-        }
+        // the contributions of a submitted round are held until its submit settles,
+        // so they're still here if the submit was reorged out and its challenge is current again
+        let reorged = contributions.insert(last_hash_at, HashSet::new());
         // reset accumulators
         let pool = operator.get_pool().await?;
         self.total_score = 0;
         self.winner = None;
+        self.solutions.clear();
+        self.difficulties = DifficultyHistogram::default();
+        self.contributions_hash = ContributionsHash::default();
         self.num_members = pool.last_total_members;
+        metrics::reset_round_difficulty();
+        metrics::set_total_score(self.total_score);
+        metrics::set_num_members(self.num_members);
+        if let Some(reorged) = reorged {
+            log::warn!(
+                "submit of round {} was reorged out, restoring its {} contributions",
+                last_hash_at,
+                reorged.len()
+            );
+            self.restore_round(reorged);
+        }
         Ok(())
     }
 
+    /// Restores the contributions of a round whose submit was reorged out,
+    /// once its challenge is current again.
+    /// The solutions are still valid for the challenge, so the round is submitted again
+    /// rather than started over, and is tracked afresh from that submit.
+    fn restore_round(&mut self, contributions: MinerContributions) {
+        let last_hash_at = self.challenge.lash_hash_at;
+        self.pending_rewards.remove(&(last_hash_at as u64));
+        // the round is recorded again when it closes
+        self.history
+            .retain(|round| round.challenge.lash_hash_at != last_hash_at);
+        for contribution in contributions.iter() {
+            let difficulty = contribution.solution.to_hash().difficulty();
+            self.solutions.insert(solution_key(&contribution.solution));
+            let line = attestation_line(contribution, self.attest_arrivals);
+            self.contributions_hash.insert(contribution.member, line);
+            let (bucket, count) = self.difficulties.record(difficulty);
+            metrics::set_round_difficulty(bucket, count);
+            self.total_score = self.total_score.saturating_add(contribution.score);
+        }
+        self.winner = winner_by_arrival(self.winner_strategy, &contributions);
+        self.contributions
+            .insert(last_hash_at as u64, contributions);
+        metrics::set_total_score(self.total_score);
+    }
+
+    /// Looks up a closed round in the challenge history.
+    pub fn get_round(&self, last_hash_at: i64) -> Option<RoundSummary> {
+        self.history
+            .iter()
+            .find(|round| round.challenge.lash_hash_at == last_hash_at)
+            .copied()
+    }
+
+    /// Pushes the current round onto the challenge history,
+    /// evicting the oldest round if the history is full.
+    fn record_round(&mut self, attestation: Option<[u8; 32]>) {
+        let round = RoundSummary {
+            challenge: self.challenge,
+            total_score: self.total_score,
+            winner_difficulty: self.winner.map(|w| w.difficulty),
+            attestation,
+        };
+        while self.history_size > 0 && self.history.len() >= self.history_size {
+            self.history.pop_front();
+        }
+        if self.history_size > 0 {
+            self.history.push_back(round);
+        }
+    }
+
+    /// Discards the current round without submitting if it has no winner,
+    /// e.g. if every contribution was filtered out, rather than failing mid-round.
+    /// The challenge stays the same, the proof only advances on a submit,
+    /// so members may contribute to it again.
+    fn close_without_winner(&mut self) -> bool {
+        if self.winner.is_some() {
+            return false;
+        }
+        let last_hash_at = self.challenge.lash_hash_at as u64;
+        let num_contributions = self.contributions.get(&last_hash_at).map_or(0, |c| c.len());
+        log::error!(
+            "no winner for round {} with {} contributions and total score {}, discarding without submitting",
+            last_hash_at,
+            num_contributions,
+            self.total_score
+        );
+        self.contributions.insert(last_hash_at, HashSet::new());
+        self.total_score = 0;
+        self.solutions.clear();
+        self.difficulties = DifficultyHistogram::default();
+        self.contributions_hash = ContributionsHash::default();
+        metrics::reset_round_difficulty();
+        metrics::set_total_score(self.total_score);
+        true
+    }
+
     fn winner(&self) -> Result<Winner, Error> {
         self.winner
             .ok_or(Error::Internal("no solutions were submitted".to_string()))
@@ -602,19 +2051,93 @@ impl Aggregator {
         Ok(needs_reset)
     }
 
+    /// Moves on to the next challenge in a new epoch,
+    /// and publishes it to websocket subscribers, if any.
+    /// The min difficulty of the challenge is the protocol min, raised by the override if set.
+    fn advance_challenge(&mut self, challenge: Challenge) {
+        self.protocol_min_difficulty = challenge.min_difficulty;
+        self.challenge = Challenge {
+            min_difficulty: effective_min_difficulty(
+                challenge.min_difficulty,
+                self.min_difficulty_override,
+            ),
+            epoch: self.challenge.epoch + 1,
+            ..challenge
+        };
+        self.round_started = std::time::Instant::now();
+        let _ = self.challenge_tx.send(self.challenge);
+    }
+
+    /// Resumes contributions paused after repeated submit failures,
+    /// e.g. once the cause of the failures is fixed.
+    pub fn reset_breaker(&mut self) {
+        self.breaker.reset();
+        metrics::set_paused(false);
+    }
+
+    /// Sets or clears the min difficulty override, applied to the current challenge right away.
+    /// An override at or below the protocol min has no effect,
+    /// and clearing the override reverts to the protocol min.
+    pub fn set_min_difficulty_override(&mut self, min_difficulty: Option<u64>) {
+        self.min_difficulty_override = min_difficulty;
+        let effective = effective_min_difficulty(self.protocol_min_difficulty, min_difficulty);
+        log::info!(
+            "min difficulty override: {:?} protocol min: {} effective: {}",
+            min_difficulty,
+            self.protocol_min_difficulty,
+            effective
+        );
+        if effective != self.challenge.min_difficulty {
+            self.challenge.min_difficulty = effective;
+            let _ = self.challenge_tx.send(self.challenge);
+        }
+    }
+
     async fn update_challenge(&mut self, operator: &Operator) -> Result<(), Error> {
         let max_retries = 10;
         let mut retries = 0;
         let last_hash_at = self.challenge.lash_hash_at;
+        if operator.dry_run {
+            // nothing was submitted so the proof won't advance,
+            // advance the round locally against the same challenge
+            let clock = operator.get_clock().await?;
+            let last_hash_at = clock.unix_timestamp.max(last_hash_at + 1);
+            let min_difficulty = operator.min_difficulty().await?;
+            let cutoff_time = operator.get_cutoff_at(last_hash_at).await?;
+            self.advance_challenge(Challenge {
+                lash_hash_at: last_hash_at,
+                min_difficulty,
+                cutoff_time,
+                min_score: operator.config().min_score,
+                ..self.challenge
+            });
+            return Ok(());
+        }
+        // track the rpc slot across retries,
+        // so that a lagging node fails fast rather than exhausting the retries.
+        // the slot is read from one pinned endpoint, since endpoints lag each other
+        // by a few slots and a round-robin read would look like a regression.
+        let endpoint = operator.rpc_client.pin();
+        let mut last_slot = None;
         loop {
+            let slot = operator.rpc_client.get_slot_pinned(endpoint).await?;
+            if !slot_advanced(last_slot, slot) {
+                log::error!("rpc slot not advancing: {}", slot);
+                return Err(Error::StaleRpc(slot));
+            }
+            last_slot = Some(slot);
             let proof = operator.get_proof().await?;
             if proof.last_hash_at != last_hash_at {
                 let cutoff_time = operator.get_cutoff(&proof).await?;
                 let min_difficulty = operator.min_difficulty().await?;
-                self.challenge.challenge = proof.challenge;
-                self.challenge.lash_hash_at = proof.last_hash_at;
-                self.challenge.min_difficulty = min_difficulty;
-                self.challenge.cutoff_time = cutoff_time;
+                self.advance_challenge(Challenge {
+                    challenge: proof.challenge,
+                    lash_hash_at: proof.last_hash_at,
+                    min_difficulty,
+                    cutoff_time,
+                    min_score: operator.config().min_score,
+                    epoch: self.challenge.epoch,
+                });
                 return Ok(());
             } else {
                 retries += 1;
@@ -626,3 +2149,1782 @@ impl Aggregator {
         }
     }
 }
+
+/// The protocol min difficulty, raised to the override if higher.
+fn effective_min_difficulty(protocol_min: u64, min_override: Option<u64>) -> u64 {
+    min_override.map_or(protocol_min, |min| min.max(protocol_min))
+}
+
+/// The balance increments of a round after holding back dust.
+struct Settlement {
+    /// The balance increment written per member address.
+    balances: Vec<(String, u64)>,
+
+    /// The new carry-over of each member address whose carry-over changed.
+    carry_over: Vec<(String, u64)>,
+}
+
+/// Merges the round's attributions per member, adding any carry-over,
+/// and holds back the totals below the min attribution,
+/// either carrying them over to the member's next round or forfeiting them to the operator.
+/// The operator's own attribution is always written.
+fn settle_dust(
+    batches: Vec<Vec<(String, u64)>>,
+    carry_over: &HashMap<String, u64>,
+    min_attribution: u64,
+    recipient: DustRecipient,
+    operator_member: &str,
+) -> Settlement {
+    let mut merged = std::collections::BTreeMap::<String, u64>::new();
+    for (address, amount) in batches.into_iter().flatten() {
+        let total = merged.entry(address).or_insert(0);
+        *total = total.saturating_add(amount);
+    }
+    let mut balances = Vec::with_capacity(merged.len());
+    let mut changed = vec![];
+    let mut forfeited = 0u64;
+    for (address, amount) in merged {
+        if address == operator_member {
+            balances.push((address, amount));
+            continue;
+        }
+        let carried = carry_over.get(&address).copied().unwrap_or(0);
+        let total = amount.saturating_add(carried);
+        if total >= min_attribution {
+            if carried > 0 {
+                changed.push((address.clone(), 0));
+            }
+            balances.push((address, total));
+            continue;
+        }
+        log::info!("holding back dust attribution: {} {}", address, total);
+        match recipient {
+            DustRecipient::CarryOver => {
+                if total != carried {
+                    changed.push((address, total));
+                }
+            }
+            DustRecipient::Operator => {
+                forfeited = forfeited.saturating_add(total);
+                if carried > 0 {
+                    changed.push((address, 0));
+                }
+            }
+        }
+    }
+    if forfeited > 0 {
+        log::info!("dust forfeited to operator: {}", forfeited);
+        match balances
+            .iter_mut()
+            .find(|(address, _)| address == operator_member)
+        {
+            Some((_, amount)) => *amount = amount.saturating_add(forfeited),
+            None => balances.push((operator_member.to_string(), forfeited)),
+        }
+    }
+    Settlement {
+        balances,
+        carry_over: changed,
+    }
+}
+
+/// Applies the changed carry-over, dropping the members that were paid out.
+fn apply_carry_over(carry_over: &mut HashMap<String, u64>, changed: Vec<(String, u64)>) {
+    for (address, amount) in changed {
+        if amount == 0 {
+            carry_over.remove(&address);
+        } else {
+            carry_over.insert(address, amount);
+        }
+    }
+}
+
+/// The reward of the round read back from the pool account after its submit,
+/// if the pool posts the submitted attestation.
+/// This is a comparison on the server only, the program stores the attestation as submitted
+/// without checking it. The attestation is the hash of the round's contributions, so it differs
+/// round to round, and a mismatch means the read is stale and holds a previous round's reward.
+fn confirmed_reward(pool: &ore_pool_api::state::Pool, attestation: &[u8; 32]) -> Option<u64> {
+    if pool.attestation != *attestation {
+        return None;
+    }
+    Some(pool.reward)
+}
+
+/// The epoch of the first challenge after a start.
+/// Each new challenge increments the epoch by one and moves `last_hash_at` on by
+/// at least a second, so seeding from `last_hash_at` keeps the epoch increasing across restarts.
+fn seed_epoch(last_hash_at: i64) -> u64 {
+    last_hash_at.max(0) as u64
+}
+
+/// Whether or not the rpc slot advanced since the previous read, if any.
+fn slot_advanced(previous: Option<u64>, slot: u64) -> bool {
+    match previous {
+        Some(previous) => slot > previous,
+        None => true,
+    }
+}
+
+/// Zeroes the scores of the members first seen less than the min age before the round,
+/// so that their share is redistributed to the tenured members.
+/// Keeps every score if no member is tenured, rather than attribute the round to no one.
+fn tenured_scores(
+    scores: Vec<u64>,
+    first_seen: &[i64],
+    last_hash_at: i64,
+    min_age_secs: u64,
+) -> Vec<u64> {
+    let tenured: Vec<u64> = scores
+        .iter()
+        .zip(first_seen)
+        .map(|(score, first_seen)| {
+            let age = last_hash_at.saturating_sub(*first_seen).max(0) as u64;
+            if age >= min_age_secs {
+                *score
+            } else {
+                0
+            }
+        })
+        .collect();
+    if tenured.iter().all(|score| *score == 0) {
+        return scores;
+    }
+    tenured
+}
+
+/// Caps a score so that it's at most `max_pct` percent of the running total once added,
+/// given the total score of the other contributions so far.
+/// The first contribution of a round has nothing to be capped against and is kept as is,
+/// so the distribution caps again against the final total, see [`cap_scores`].
+fn capped_score(score: u64, others_score: u64, max_pct: u64) -> u64 {
+    if max_pct >= 100 || others_score == 0 {
+        return score;
+    }
+    let cap = others_score as u128 * max_pct as u128 / (100 - max_pct) as u128;
+    score.min(cap.min(u64::MAX as u128) as u64)
+}
+
+/// Caps each score at `max_pct` percent of the total score.
+/// The excess is redistributed proportionally to the uncapped scores,
+/// which may push them over the cap in turn, so repeat until stable.
+/// If every score would be capped, the total is split evenly.
+fn cap_scores(scores: &[u64], max_pct: u64) -> Vec<u64> {
+    let total: u128 = scores.iter().map(|s| *s as u128).sum();
+    let cap = total * (max_pct.min(100) as u128) / 100;
+    let mut capped = vec![false; scores.len()];
+    loop {
+        let num_capped = capped.iter().filter(|c| **c).count() as u128;
+        let remaining = total.saturating_sub(cap * num_capped);
+        let uncapped_total: u128 = scores
+            .iter()
+            .zip(capped.iter())
+            .filter(|(_, c)| !**c)
+            .map(|(s, _)| *s as u128)
+            .sum();
+        if uncapped_total == 0 {
+            let num_scores = scores.len().max(1) as u128;
+            return scores.iter().map(|_| (total / num_scores) as u64).collect();
+        }
+        let effective: Vec<u128> = scores
+            .iter()
+            .zip(capped.iter())
+            .map(|(s, c)| match c {
+                true => cap,
+                false => (*s as u128) * remaining / uncapped_total,
+            })
+            .collect();
+        let mut changed = false;
+        for (e, c) in effective.iter().zip(capped.iter_mut()) {
+            if !*c && *e > cap {
+                *c = true;
+                changed = true;
+            }
+        }
+        if !changed {
+            return effective.into_iter().map(|e| e as u64).collect();
+        }
+    }
+}
+
+/// The stakers eligible for the staker commission,
+/// all but the excluded staker (if any) so that the denominator excludes their balance.
+/// Sorted by authority, so that the remainder goes to the same staker whatever the map's order.
+fn eligible_stakers<'a>(
+    stakers: &'a StakerBalances,
+    excluded: Option<&Pubkey>,
+) -> Vec<(&'a Pubkey, &'a u64)> {
+    let mut stakers: Vec<(&Pubkey, &u64)> = stakers
+        .iter()
+        .filter(|(authority, _)| Some(*authority) != excluded)
+        .collect();
+    stakers.sort_by_key(|(authority, _)| **authority);
+    stakers
+}
+
+/// The running winner once the contender has been inserted, per the strategy.
+/// The weighted strategy tracks the max difficulty until the round is closed,
+/// as its fallback, see [`weighted_winner`].
+fn contend(strategy: WinnerStrategy, current: Option<Winner>, contender: Winner) -> Winner {
+    let current = match current {
+        Some(current) => current,
+        None => return contender,
+    };
+    match strategy {
+        WinnerStrategy::MaxDifficulty | WinnerStrategy::Weighted { .. } => {
+            if contender.beats(&current) {
+                contender
+            } else {
+                current
+            }
+        }
+        WinnerStrategy::Earliest { min_difficulty } => {
+            if current.difficulty >= min_difficulty {
+                current
+            } else if contender.difficulty >= min_difficulty || contender.beats(&current) {
+                contender
+            } else {
+                current
+            }
+        }
+    }
+}
+
+/// The winner of the contributions contended in order of arrival, ties by member.
+fn winner_by_arrival(
+    strategy: WinnerStrategy,
+    contributions: &MinerContributions,
+) -> Option<Winner> {
+    let mut contributions: Vec<&Contribution> = contributions.iter().collect();
+    contributions.sort_by_key(|c| (c.received_at, c.member));
+    contributions.into_iter().fold(None, |winner, c| {
+        let contender = Winner {
+            solution: c.solution,
+            difficulty: c.solution.to_hash().difficulty(),
+            request_id: c.request_id,
+        };
+        Some(contend(strategy, winner, contender))
+    })
+}
+
+/// Picks a contribution at or above the min difficulty at random, weighted by score.
+/// Returns `None` if none qualify.
+fn weighted_winner(
+    contributions: &MinerContributions,
+    min_difficulty: u32,
+    rng: &mut impl Rng,
+) -> Option<Winner> {
+    // sorted so that the pick only depends on the rng
+    let candidates: Vec<(Winner, u64)> = sorted_by_member(contributions)
+        .into_iter()
+        .filter_map(|c| {
+            let difficulty = c.solution.to_hash().difficulty();
+            let winner = Winner {
+                solution: c.solution,
+                difficulty,
+                request_id: c.request_id,
+            };
+            (difficulty >= min_difficulty).then_some((winner, c.score))
+        })
+        .collect();
+    let weights = WeightedIndex::new(candidates.iter().map(|(_, score)| *score)).ok()?;
+    Some(candidates[weights.sample(rng)].0)
+}
+
+fn sorted_by_member(contributions: &MinerContributions) -> Vec<&Contribution> {
+    let mut contributions: Vec<&Contribution> = contributions.iter().collect();
+    contributions.sort_by_key(|c| c.member);
+    contributions
+}
+
+/// A line of the attestation block: member, hex digest, and nonce,
+/// followed by the arrival time if opted in.
+fn attestation_line(contribution: &Contribution, with_arrival: bool) -> String {
+    let line = format!(
+        "{} {} {}",
+        contribution.member,
+        hex_digest(&contribution.solution),
+        u64::from_le_bytes(contribution.solution.n)
+    );
+    match with_arrival {
+        true => format!("{} {}", line, contribution.received_at),
+        false => line,
+    }
+}
+
+/// Identifies a solution by its digest and nonce.
+fn solution_key(solution: &Solution) -> [u8; 24] {
+    let mut key = [0; 24];
+    key[..16].copy_from_slice(&solution.d);
+    key[16..].copy_from_slice(&solution.n);
+    key
+}
+
+fn hex_digest(solution: &Solution) -> String {
+    solution.d.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{:02x}", byte);
+        hex
+    })
+}
+
+/// Splits `total` proportionally to `weights`, truncating each share.
+/// The truncation remainder is assigned to the largest weight (the first if tied)
+/// so that the shares sum exactly to `total`.
+/// If every weight is zero there is no one to attribute to and all shares are zero.
+fn distribute_with_remainder(total: u64, weights: &[u64]) -> Vec<u64> {
+    let denominator: u128 = weights.iter().map(|w| *w as u128).sum();
+    if denominator == 0 {
+        return vec![0; weights.len()];
+    }
+    let mut shares: Vec<u64> = weights
+        .iter()
+        .map(|w| ((*w as u128) * (total as u128) / denominator) as u64)
+        .collect();
+    let attributed: u64 = shares.iter().sum();
+    let remainder = total - attributed;
+    if let Some(largest) = weights
+        .iter()
+        .enumerate()
+        .max_by(|(a_index, a), (b_index, b)| a.cmp(b).then(b_index.cmp(a_index)))
+        .map(|(index, _)| index)
+    {
+        shares[largest] += remainder;
+    }
+    shares
+}
+
+/// Selects the (id, rewards) of the bus with the most rewards.
+/// Ties go to the lowest id.
+fn top_bus(balances: impl Iterator<Item = (usize, u64)>) -> Option<(usize, u64)> {
+    balances
+        .filter(|(id, _)| *id < BUS_COUNT)
+        .max_by(|(a_id, a_rewards), (b_id, b_rewards)| {
+            a_rewards.cmp(b_rewards).then(b_id.cmp(a_id))
+        })
+}
+
+/// The rewards of a round from mining, the base rewards net of the boost rewards.
+/// Errors rather than underflowing if the boost rewards exceed the base rewards.
+fn mine_rewards(rewards: &Rewards) -> Result<u64, Error> {
+    [rewards.boost_1, rewards.boost_2, rewards.boost_3]
+        .iter()
+        .flatten()
+        .try_fold(rewards.base, |mine, boost| mine.checked_sub(boost.reward))
+        .ok_or(Error::Internal(format!(
+            "boost rewards exceed base rewards: {}",
+            rewards.base
+        )))
+}
+
+/// The rewards paid out to miners from the mine and boost rewards of a round,
+/// net of the operator and staker commissions.
+fn miner_rewards(mine: u64, boost: u64, operator_commission: u64, staker_commission: u64) -> u64 {
+    let from_mine = mine as u128 * 100u64.saturating_sub(operator_commission) as u128 / 100;
+    let from_boost = boost as u128
+        * 100u64
+            .saturating_sub(operator_commission)
+            .saturating_sub(staker_commission) as u128
+        / 100;
+    (from_mine + from_boost) as u64
+}
+
+/// The expected best difficulty found in a round by a miner of the hashrate,
+/// where each hash meets a difficulty with probability 2^-difficulty.
+pub fn expected_difficulty(hashrate: u64) -> u32 {
+    let round_secs = 60u64.saturating_sub(BUFFER_CLIENT);
+    hashrate.saturating_mul(round_secs).max(1).ilog2()
+}
+
+/// The submitted rounds that have waited on their rewards for longer than the timeout,
+/// oldest first.
+fn expired_rounds(
+    pending: &HashMap<LastHashAt, PendingRewards>,
+    now: std::time::Instant,
+    timeout: std::time::Duration,
+) -> Vec<LastHashAt> {
+    let mut expired: Vec<LastHashAt> = pending
+        .iter()
+        .filter(|(_, p)| now.saturating_duration_since(p.submitted_at) > timeout)
+        .map(|(last_hash_at, _)| *last_hash_at)
+        .collect();
+    expired.sort_unstable();
+    expired
+}
+
+#[cfg(test)]
+mod tests {
+    use ore_pool_types::ContributePayload;
+    use solana_sdk::{signature::Keypair, signer::Signer};
+
+    use super::*;
+    use crate::{
+        breaker::DEFAULT_SUBMIT_FAILURE_THRESHOLD,
+        validation::{Validator, Verdict},
+    };
+
+    fn test_aggregator() -> Aggregator {
+        let challenge = Challenge {
+            challenge: [0; 32],
+            lash_hash_at: 0,
+            min_difficulty: 0,
+            cutoff_time: 0,
+            min_score: 0,
+            epoch: 0,
+        };
+        let mut contributions = HashMap::new();
+        contributions.insert(challenge.lash_hash_at as u64, HashSet::new());
+        Aggregator {
+            challenge,
+            contributions,
+            total_score: 0,
+            winner: None,
+            winner_strategy: WinnerStrategy::MaxDifficulty,
+            score_curve: Curve::Exponential,
+            max_member_score_pct: None,
+            num_members: 0,
+            stake: HashMap::new(),
+            attestation_client: None,
+            history: VecDeque::new(),
+            history_size: 2,
+            reward_samples: VecDeque::new(),
+            forensics: VecDeque::new(),
+            forensics_size: 0,
+            challenge_tx: tokio::sync::broadcast::channel(1).0,
+            pending_rewards: HashMap::new(),
+            rewards_timeout: std::time::Duration::from_secs(DEFAULT_REWARDS_TIMEOUT_SECS),
+            arrivals: ArrivalCurve::new(0),
+            solutions: HashSet::new(),
+            carry_over: HashMap::new(),
+            first_seen: HashMap::new(),
+            difficulties: DifficultyHistogram::default(),
+            contributions_hash: ContributionsHash::default(),
+            round_started: std::time::Instant::now(),
+            attest_arrivals: false,
+            protocol_min_difficulty: 0,
+            min_difficulty_override: None,
+            breaker: Breaker::new(DEFAULT_SUBMIT_FAILURE_THRESHOLD),
+            deadline: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn waits_for_missing_proof() {
+        let checks = std::sync::atomic::AtomicU32::new(0);
+        // the proof is created on the third check
+        let fetch = || async {
+            let check = checks.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            Ok((check >= 2).then_some(check))
+        };
+        let found = wait_for_initialization(fetch, |_| std::time::Duration::ZERO).await;
+        assert_eq!(found.unwrap(), 2);
+        // errors aren't waited out
+        let fetch = || async { Err::<Option<()>, _>(Error::Internal("rpc".to_string())) };
+        assert!(
+            wait_for_initialization(fetch, |_| std::time::Duration::ZERO)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn zero_cutoff_at_round_start_is_fetched_again() {
+        let checks = std::sync::atomic::AtomicU32::new(0);
+        // the challenge moves on by the third fetch
+        let fetch = || async {
+            match checks.fetch_add(1, std::sync::atomic::Ordering::Relaxed) {
+                0 | 1 => Ok((0, true)),
+                _ => Ok((45, true)),
+            }
+        };
+        let (_, cutoff_time) = wait_for_cutoff(fetch, 5, std::time::Duration::ZERO)
+            .await
+            .unwrap();
+        assert_eq!(cutoff_time, 45);
+        assert_eq!(checks.load(std::sync::atomic::Ordering::Relaxed), 3);
+        // a cutoff that stays at zero is given up on, and the round proceeds
+        let checks = std::sync::atomic::AtomicU32::new(0);
+        let fetch = || async {
+            checks.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            Ok((0, true))
+        };
+        let (_, cutoff_time) = wait_for_cutoff(fetch, 5, std::time::Duration::ZERO)
+            .await
+            .unwrap();
+        assert_eq!(cutoff_time, 0);
+        assert_eq!(checks.load(std::sync::atomic::Ordering::Relaxed), 5);
+        // a zero cutoff past the deadline means the pool was idle, and isn't fetched again
+        let checks = std::sync::atomic::AtomicU32::new(0);
+        let fetch = || async {
+            checks.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            Ok((0, false))
+        };
+        let (_, cutoff_time) = wait_for_cutoff(fetch, 5, std::time::Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert_eq!(cutoff_time, 0);
+        assert_eq!(checks.load(std::sync::atomic::Ordering::Relaxed), 1);
+        // a single check doesn't wait
+        let fetch = || async { Ok((0, true)) };
+        let (_, cutoff_time) = wait_for_cutoff(fetch, 1, std::time::Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert_eq!(cutoff_time, 0);
+        // errors aren't waited out
+        let fetch = || async { Err(Error::Internal("rpc".to_string())) };
+        assert!(wait_for_cutoff(fetch, 5, std::time::Duration::ZERO)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn forced_reset_restarts_the_round_against_its_own_cutoff() {
+        let aggregator = tokio::sync::RwLock::new(test_aggregator());
+        let (_tx, mut rx) = tokio::sync::mpsc::channel::<Forwarded>(1);
+        let shutdown = tokio::sync::Notify::new();
+        let subscribe = || async {
+            let read = aggregator.read().await;
+            (read.challenge.epoch, read.challenge_tx.subscribe())
+        };
+        // the round closes 30 seconds in, but is reset out of band 10 seconds in
+        let (epoch, mut challenges) = subscribe().await;
+        let clock = RoundClock {
+            timer: tokio::time::Instant::now(),
+            cutoff_time: 30,
+            early_stop: None,
+        };
+        let reset = async {
+            tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
+            let mut aggregator = aggregator.write().await;
+            let next = Challenge {
+                lash_hash_at: 60,
+                ..aggregator.challenge
+            };
+            aggregator.advance_challenge(next);
+        };
+        let (end, _) = tokio::join!(
+            collect_round(
+                &aggregator,
+                &mut rx,
+                &shutdown,
+                &mut challenges,
+                epoch,
+                &clock
+            ),
+            reset
+        );
+        assert_eq!(end.unwrap(), RoundEnd::Reset);
+        assert_eq!(clock.timer.elapsed().as_secs(), 10);
+        // the next round runs to its own cutoff, past the deadline of the reset round
+        let (epoch, mut challenges) = subscribe().await;
+        let clock = RoundClock {
+            timer: tokio::time::Instant::now(),
+            cutoff_time: 30,
+            early_stop: None,
+        };
+        let end = collect_round(
+            &aggregator,
+            &mut rx,
+            &shutdown,
+            &mut challenges,
+            epoch,
+            &clock,
+        )
+        .await;
+        assert_eq!(end.unwrap(), RoundEnd::Cutoff);
+        assert_eq!(clock.timer.elapsed().as_secs(), 30);
+        // a challenge published within the epoch doesn't reset the round
+        let (epoch, mut challenges) = subscribe().await;
+        let clock = RoundClock {
+            timer: tokio::time::Instant::now(),
+            cutoff_time: 30,
+            early_stop: None,
+        };
+        aggregator
+            .write()
+            .await
+            .set_min_difficulty_override(Some(10));
+        let end = collect_round(
+            &aggregator,
+            &mut rx,
+            &shutdown,
+            &mut challenges,
+            epoch,
+            &clock,
+        )
+        .await;
+        assert_eq!(end.unwrap(), RoundEnd::Cutoff);
+    }
+
+    #[test]
+    fn initialization_delay_backs_off_to_max() {
+        assert_eq!(initialization_delay(0).as_secs(), INIT_POLL_BASE_SECS);
+        assert_eq!(initialization_delay(1).as_secs(), INIT_POLL_BASE_SECS * 2);
+        assert_eq!(initialization_delay(64).as_secs(), INIT_POLL_MAX_SECS);
+    }
+
+    #[test]
+    fn round_without_winner_is_discarded_without_submitting() {
+        let mut aggregator = test_aggregator();
+        let last_hash_at = aggregator.challenge.lash_hash_at as u64;
+        let members = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let contributions = distinct_contributions(&members, 10);
+        aggregator.total_score = contributions.iter().map(|c| c.score).sum();
+        aggregator.contributions.insert(
+            last_hash_at,
+            contributions.into_iter().collect::<HashSet<_>>(),
+        );
+        // contributions but no winner
+        assert!(aggregator.close_without_winner());
+        assert_eq!(aggregator.total_score, 0);
+        assert!(aggregator.contributions[&last_hash_at].is_empty());
+        assert!(aggregator.winner().is_err());
+        // against the same challenge
+        assert_eq!(aggregator.challenge.lash_hash_at as u64, last_hash_at);
+        // a round with a winner is left to be submitted
+        let winner = contribution(members[0], 10);
+        aggregator
+            .contributions
+            .insert(last_hash_at, HashSet::from([winner]));
+        aggregator.total_score = winner.score;
+        aggregator.winner = Some(Winner {
+            solution: winner.solution,
+            difficulty: 10,
+            request_id: winner.request_id,
+        });
+        assert!(!aggregator.close_without_winner());
+        assert_eq!(aggregator.total_score, winner.score);
+        assert_eq!(aggregator.contributions[&last_hash_at].len(), 1);
+    }
+
+    #[test]
+    fn stats_report_the_contribution_loop_deadline() {
+        let mut aggregator = test_aggregator();
+        assert_eq!(aggregator.stats().remaining_time, 0);
+        // e.g. an adaptive cutoff well before the fixed margin
+        aggregator.deadline =
+            Some(tokio::time::Instant::now() + tokio::time::Duration::from_secs(12));
+        let remaining_time = aggregator.stats().remaining_time;
+        assert!(remaining_time == 11 || remaining_time == 12);
+        aggregator.deadline =
+            Some(tokio::time::Instant::now() - tokio::time::Duration::from_secs(1));
+        assert_eq!(aggregator.stats().remaining_time, 0);
+    }
+
+    #[test]
+    fn stats_report_the_paused_breaker() {
+        let mut aggregator = test_aggregator();
+        for _ in 0..DEFAULT_SUBMIT_FAILURE_THRESHOLD {
+            assert!(!aggregator.stats().paused);
+            aggregator.breaker.record_failure();
+        }
+        let stats = aggregator.stats();
+        assert!(stats.paused);
+        assert_eq!(
+            stats.consecutive_submit_failures,
+            DEFAULT_SUBMIT_FAILURE_THRESHOLD
+        );
+        aggregator.reset_breaker();
+        assert!(!aggregator.stats().paused);
+    }
+
+    #[test]
+    fn stake_totals_per_boost() {
+        let mut aggregator = test_aggregator();
+        let config = test_config(10);
+        assert!(aggregator.stake_totals(&config).is_empty());
+        let boost = Pubkey::new_unique();
+        aggregator.stake.insert(
+            boost,
+            HashMap::from([
+                (Pubkey::new_unique(), 100),
+                (Pubkey::new_unique(), 250),
+                (Pubkey::new_unique(), 0),
+            ]),
+        );
+        let totals = aggregator.stake_totals(&config);
+        let stake = &totals[&boost.to_string()];
+        assert_eq!(stake.total_staked, 350);
+        assert_eq!(stake.num_stakers, 2);
+        assert_eq!(stake.staker_commission, 10);
+    }
+
+    #[test]
+    fn epoch_increments_once_per_challenge() {
+        let mut aggregator = test_aggregator();
+        let mut rx = aggregator.challenge_tx.subscribe();
+        for epoch in 1..=3 {
+            let next = Challenge {
+                lash_hash_at: aggregator.challenge.lash_hash_at + 60,
+                ..aggregator.challenge
+            };
+            aggregator.advance_challenge(next);
+            assert_eq!(aggregator.challenge.epoch, epoch);
+            assert_eq!(aggregator.challenge.lash_hash_at, epoch as i64 * 60);
+            // subscribers see the new epoch
+            assert_eq!(rx.try_recv().unwrap().epoch, epoch);
+        }
+        // the epoch of the next challenge is ignored
+        let next = Challenge {
+            epoch: 100,
+            ..aggregator.challenge
+        };
+        aggregator.advance_challenge(next);
+        assert_eq!(aggregator.challenge.epoch, 4);
+    }
+
+    #[test]
+    fn epoch_keeps_increasing_across_restarts() {
+        let mut aggregator = test_aggregator();
+        aggregator.challenge.epoch = seed_epoch(1_000);
+        for round in 1..=3 {
+            let next = Challenge {
+                lash_hash_at: 1_000 + round * 60,
+                ..aggregator.challenge
+            };
+            aggregator.advance_challenge(next);
+        }
+        // restarted in the last round
+        assert!(seed_epoch(aggregator.challenge.lash_hash_at) > aggregator.challenge.epoch);
+    }
+
+    #[tokio::test]
+    async fn min_difficulty_override_rejects_below_and_accepts_above() {
+        let mut aggregator = test_aggregator();
+        aggregator.advance_challenge(Challenge {
+            min_difficulty: 1,
+            ..aggregator.challenge
+        });
+        let pool = Pubkey::new_unique();
+        let keypair = Keypair::new();
+        // above the protocol min, so the override can sit right at it
+        let solution = (0u64..)
+            .map(|n| Solution::new([0; 16], n.to_le_bytes()))
+            .find(|solution| solution.to_hash().difficulty() > 1)
+            .unwrap();
+        let difficulty = solution.to_hash().difficulty() as u64;
+        let message =
+            ContributePayload::message(&pool, aggregator.challenge.lash_hash_at, &solution);
+        let payload = ContributePayload {
+            authority: keypair.pubkey(),
+            solution,
+            signature: keypair.sign_message(message.as_slice()),
+        };
+        let validator = Validator::new(1, Curve::Exponential);
+        let below_difficulty = |verdict: Verdict| matches!(verdict, Verdict::BelowDifficulty(_));
+        // raised above the solution
+        aggregator.set_min_difficulty_override(Some(difficulty + 1));
+        assert_eq!(aggregator.challenge.min_difficulty, difficulty + 1);
+        let verdict = validator
+            .validate(&payload, aggregator.challenge)
+            .await
+            .unwrap();
+        assert!(below_difficulty(verdict));
+        // at the solution, it passes the difficulty check on to the digest check
+        aggregator.set_min_difficulty_override(Some(difficulty));
+        let verdict = validator
+            .validate(&payload, aggregator.challenge)
+            .await
+            .unwrap();
+        assert!(!below_difficulty(verdict));
+        // kept across challenges
+        aggregator.advance_challenge(Challenge {
+            min_difficulty: 1,
+            ..aggregator.challenge
+        });
+        assert_eq!(aggregator.challenge.min_difficulty, difficulty);
+        // never lowers the protocol min, and clearing reverts to it
+        aggregator.set_min_difficulty_override(Some(0));
+        assert_eq!(aggregator.challenge.min_difficulty, 1);
+        aggregator.set_min_difficulty_override(Some(difficulty + 1));
+        aggregator.set_min_difficulty_override(None);
+        assert_eq!(aggregator.challenge.min_difficulty, 1);
+    }
+
+    #[test]
+    fn stalled_or_regressed_slot_has_not_advanced() {
+        assert!(slot_advanced(None, 100));
+        assert!(slot_advanced(Some(100), 102));
+        assert!(!slot_advanced(Some(100), 100));
+        assert!(!slot_advanced(Some(100), 99));
+    }
+
+    #[test]
+    fn reward_is_confirmed_by_submitted_attestation() {
+        let solution = Solution::new([1; 16], [2; 8]);
+        let attestation = [3; 32];
+        let mut pool: ore_pool_api::state::Pool = bytemuck::Zeroable::zeroed();
+        pool.reward = 100;
+        // a stale read still holds the previous round
+        assert_eq!(confirmed_reward(&pool, &attestation), None);
+        let submit = ore_pool_api::sdk::submit(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            solution,
+            attestation,
+            Pubkey::new_unique(),
+            vec![],
+        );
+        let args = ore_pool_api::instruction::Submit::try_from_bytes(&submit.data[1..]).unwrap();
+        pool.attestation = args.attestation;
+        assert_eq!(confirmed_reward(&pool, &attestation), Some(100));
+    }
+
+    #[test]
+    fn reward_is_not_confirmed_by_another_rounds_attestation() {
+        let mut pool: ore_pool_api::state::Pool = bytemuck::Zeroable::zeroed();
+        pool.reward = 100;
+        pool.attestation = [3; 32];
+        assert_eq!(confirmed_reward(&pool, &[4; 32]), None);
+        // nor by one differing in a single byte
+        let mut attestation = pool.attestation;
+        attestation[31] ^= 1;
+        assert_eq!(confirmed_reward(&pool, &attestation), None);
+    }
+
+    /// Settles a round and applies its carry-over, as the distribution does.
+    fn settle_round(
+        carry_over: &mut HashMap<String, u64>,
+        attributions: Vec<(&str, u64)>,
+        min_attribution: u64,
+        recipient: DustRecipient,
+    ) -> Vec<(String, u64)> {
+        let batch = attributions
+            .into_iter()
+            .map(|(address, amount)| (address.to_string(), amount))
+            .collect();
+        let settlement = settle_dust(vec![batch], carry_over, min_attribution, recipient, "op");
+        apply_carry_over(carry_over, settlement.carry_over);
+        settlement.balances
+    }
+
+    #[test]
+    fn dust_carries_over_until_threshold() {
+        let mut carry_over = HashMap::new();
+        let recipient = DustRecipient::CarryOver;
+        let balances = settle_round(
+            &mut carry_over,
+            vec![("a", 40), ("b", 200), ("op", 5)],
+            100,
+            recipient,
+        );
+        assert_eq!(
+            balances,
+            vec![("b".to_string(), 200), ("op".to_string(), 5)]
+        );
+        assert_eq!(carry_over.get("a"), Some(&40));
+        // accumulates across rounds
+        let balances = settle_round(&mut carry_over, vec![("a", 50), ("op", 5)], 100, recipient);
+        assert_eq!(balances, vec![("op".to_string(), 5)]);
+        assert_eq!(carry_over.get("a"), Some(&90));
+        // and is paid out in full once over the threshold
+        let balances = settle_round(&mut carry_over, vec![("a", 10), ("op", 5)], 100, recipient);
+        assert_eq!(
+            balances,
+            vec![("a".to_string(), 100), ("op".to_string(), 5)]
+        );
+        assert!(carry_over.is_empty());
+    }
+
+    #[test]
+    fn dust_is_forfeited_to_operator() {
+        let mut carry_over = HashMap::from([("a".to_string(), 30)]);
+        let balances = settle_round(
+            &mut carry_over,
+            vec![("a", 40), ("b", 20), ("op", 5)],
+            100,
+            DustRecipient::Operator,
+        );
+        // the operator is written even below the threshold
+        assert_eq!(balances, vec![("op".to_string(), 95)]);
+        assert!(carry_over.is_empty());
+    }
+
+    #[test]
+    fn dust_disabled_writes_every_attribution() {
+        let mut carry_over = HashMap::new();
+        let balances = settle_round(
+            &mut carry_over,
+            vec![("b", 0), ("a", 1), ("a", 2), ("op", 0)],
+            0,
+            DustRecipient::CarryOver,
+        );
+        assert_eq!(
+            balances,
+            vec![
+                ("a".to_string(), 3),
+                ("b".to_string(), 0),
+                ("op".to_string(), 0)
+            ]
+        );
+        assert!(carry_over.is_empty());
+    }
+
+    fn test_config(staker_commission: u64) -> ReloadableConfig {
+        ReloadableConfig {
+            operator_commission: 5,
+            operator_commission_tiers: None,
+            staker_commission,
+            staker_commission_overrides: HashMap::new(),
+            min_score: 0,
+            submit_cu_limit: 0,
+            submit_cu_price: 0,
+            submit_cu_price_max: None,
+        }
+    }
+
+    #[test]
+    fn expired_rounds_are_past_timeout_oldest_first() {
+        let now = std::time::Instant::now();
+        let timeout = std::time::Duration::from_secs(300);
+        let config = std::sync::Arc::new(test_config(90));
+        let pending_since = |secs: u64| PendingRewards {
+            submitted_at: now - std::time::Duration::from_secs(secs),
+            config: config.clone(),
+            reward: None,
+            held: None,
+        };
+        let pending = HashMap::from([
+            (3, pending_since(301)),
+            (1, pending_since(900)),
+            (2, pending_since(300)),
+            (4, pending_since(10)),
+        ]);
+        assert_eq!(expired_rounds(&pending, now, timeout), vec![1, 3]);
+        assert!(expired_rounds(&HashMap::new(), now, timeout).is_empty());
+    }
+
+    #[test]
+    fn reorged_round_is_restored() {
+        let mut aggregator = test_aggregator();
+        let last_hash_at = aggregator.challenge.lash_hash_at as u64;
+        let members = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut contributions = distinct_contributions(&members, 10);
+        for contribution in contributions.iter_mut() {
+            aggregator.insert(contribution).unwrap();
+        }
+        let total_score = aggregator.total_score;
+        let winner = aggregator.winner().unwrap();
+        let (_, attestation) = aggregator.attestation();
+        // the round is submitted and reset
+        aggregator.record_round(Some(attestation));
+        aggregator.pending_rewards.insert(
+            last_hash_at,
+            PendingRewards {
+                submitted_at: std::time::Instant::now(),
+                config: std::sync::Arc::new(test_config(90)),
+                reward: None,
+                held: None,
+            },
+        );
+        let held = aggregator
+            .contributions
+            .insert(last_hash_at, HashSet::new())
+            .unwrap();
+        aggregator.total_score = 0;
+        aggregator.winner = None;
+        aggregator.solutions.clear();
+        aggregator.difficulties = DifficultyHistogram::default();
+        aggregator.contributions_hash = ContributionsHash::default();
+        // the submit is reorged out, and the round's challenge is current again
+        aggregator.restore_round(held);
+        assert_eq!(aggregator.total_score, total_score);
+        assert_eq!(
+            solution_key(&aggregator.winner().unwrap().solution),
+            solution_key(&winner.solution)
+        );
+        assert_eq!(aggregator.contributions[&last_hash_at].len(), 2);
+        assert_eq!(aggregator.attestation().1, attestation);
+        // tracked afresh from the next submit
+        assert!(aggregator.pending_rewards.is_empty());
+        assert!(aggregator.get_round(last_hash_at as i64).is_none());
+        // a solution already in the round is still a duplicate
+        let mut copied = Contribution {
+            member: Pubkey::new_unique(),
+            ..contributions[0]
+        };
+        assert_eq!(
+            aggregator.insert(&mut copied).unwrap(),
+            Inserted::DuplicateSolution
+        );
+    }
+
+    /// Searches nonces for a solution of exactly the target difficulty.
+    fn solution_with_difficulty(difficulty: u32) -> Solution {
+        (0u64..)
+            .map(|n| Solution::new([0; 16], n.to_le_bytes()))
+            .find(|s| s.to_hash().difficulty() == difficulty)
+            .unwrap()
+    }
+
+    fn contribution(member: Pubkey, difficulty: u32) -> Contribution {
+        Contribution {
+            member,
+            score: 2u64.pow(difficulty),
+            solution: solution_with_difficulty(difficulty),
+            request_id: RequestId::default(),
+            received_at: 0,
+        }
+    }
+
+    /// Contributions of the same difficulty with a distinct solution per member.
+    fn distinct_contributions(members: &[Pubkey], difficulty: u32) -> Vec<Contribution> {
+        (0u64..)
+            .map(|n| Solution::new([0; 16], n.to_le_bytes()))
+            .filter(|s| s.to_hash().difficulty() == difficulty)
+            .zip(members.iter())
+            .map(|(solution, member)| Contribution {
+                solution,
+                ..contribution(*member, difficulty)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn equal_difficulty_tie_goes_to_lower_nonce() {
+        let solutions: Vec<Solution> = (0u64..)
+            .map(|n| Solution::new([0; 16], n.to_le_bytes()))
+            .filter(|s| s.to_hash().difficulty() == 3)
+            .take(2)
+            .collect();
+        let (lower, higher) = (solutions[0], solutions[1]);
+        let contributions = [
+            Contribution {
+                member: Pubkey::new_unique(),
+                score: 8,
+                solution: lower,
+                request_id: RequestId::default(),
+                received_at: 0,
+            },
+            Contribution {
+                member: Pubkey::new_unique(),
+                score: 8,
+                solution: higher,
+                request_id: RequestId::default(),
+                received_at: 0,
+            },
+        ];
+        let mut forward = test_aggregator();
+        for c in contributions.iter() {
+            forward.insert(&mut { *c }).unwrap();
+        }
+        let mut reverse = test_aggregator();
+        for c in contributions.iter().rev() {
+            reverse.insert(&mut { *c }).unwrap();
+        }
+        assert_eq!(forward.winner.unwrap().solution.n, lower.n);
+        assert_eq!(reverse.winner.unwrap().solution.n, lower.n);
+    }
+
+    #[test]
+    fn insert_ignores_lower_resubmission() {
+        let mut aggregator = test_aggregator();
+        let member = Pubkey::new_unique();
+        aggregator.insert(&mut contribution(member, 4)).unwrap();
+        assert_eq!(
+            aggregator.insert(&mut contribution(member, 2)).unwrap(),
+            Inserted::Superseded
+        );
+        assert_eq!(aggregator.total_score, 16);
+        assert_eq!(aggregator.winner.unwrap().difficulty, 4);
+    }
+
+    #[test]
+    fn insert_replaces_higher_resubmission() {
+        let mut aggregator = test_aggregator();
+        let member = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        aggregator.insert(&mut contribution(member, 2)).unwrap();
+        aggregator.insert(&mut contribution(other, 3)).unwrap();
+        assert_eq!(aggregator.winner.unwrap().difficulty, 3);
+        aggregator.insert(&mut contribution(member, 5)).unwrap();
+        assert_eq!(aggregator.total_score, 8 + 32);
+        assert_eq!(aggregator.winner.unwrap().difficulty, 5);
+        assert_eq!(aggregator.get_contribution(&member).unwrap().score, 32);
+    }
+
+    #[test]
+    fn insert_rejects_duplicate_solution_from_another_member() {
+        let mut aggregator = test_aggregator();
+        let first = Pubkey::new_unique();
+        let second = Pubkey::new_unique();
+        let solution = contribution(first, 4).solution;
+        aggregator.insert(&mut contribution(first, 4)).unwrap();
+        assert_eq!(
+            aggregator.insert(&mut contribution(second, 4)).unwrap(),
+            Inserted::DuplicateSolution
+        );
+        assert_eq!(aggregator.total_score, 16);
+        assert_eq!(
+            aggregator.get_contribution(&first).unwrap().solution.n,
+            solution.n
+        );
+        assert!(aggregator.get_contribution(&second).is_none());
+        // a distinct solution from the second member is still accepted
+        let mut distinct = contribution(second, 5);
+        assert_eq!(
+            aggregator.insert(&mut distinct).unwrap(),
+            Inserted::Accepted
+        );
+        assert_eq!(aggregator.total_score, 16 + 32);
+    }
+
+    /// Inserts a fixed contribution set of difficulties 2, 5, 3, 4 in that order.
+    fn strategy_aggregator(strategy: WinnerStrategy) -> (Aggregator, Vec<Pubkey>) {
+        let mut aggregator = test_aggregator();
+        aggregator.winner_strategy = strategy;
+        let members: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        for (member, difficulty) in members.iter().zip([2, 5, 3, 4]) {
+            aggregator
+                .insert(&mut contribution(*member, difficulty))
+                .unwrap();
+        }
+        (aggregator, members)
+    }
+
+    #[test]
+    fn max_difficulty_strategy_picks_highest() {
+        let (aggregator, _) = strategy_aggregator(WinnerStrategy::MaxDifficulty);
+        assert_eq!(aggregator.winner.unwrap().difficulty, 5);
+    }
+
+    #[test]
+    fn earliest_strategy_picks_first_qualifying() {
+        let (aggregator, _) = strategy_aggregator(WinnerStrategy::Earliest { min_difficulty: 3 });
+        assert_eq!(aggregator.winner.unwrap().difficulty, 5);
+        // falls back to the max difficulty
+        let (aggregator, _) = strategy_aggregator(WinnerStrategy::Earliest { min_difficulty: 6 });
+        assert_eq!(aggregator.winner.unwrap().difficulty, 5);
+        // a later, higher difficulty doesn't replace a qualifying winner
+        let mut aggregator = test_aggregator();
+        aggregator.winner_strategy = WinnerStrategy::Earliest { min_difficulty: 3 };
+        for difficulty in [2, 3, 5, 4] {
+            aggregator
+                .insert(&mut contribution(Pubkey::new_unique(), difficulty))
+                .unwrap();
+        }
+        assert_eq!(aggregator.winner.unwrap().difficulty, 3);
+    }
+
+    #[test]
+    fn earliest_strategy_picks_again_when_the_winner_is_replaced() {
+        let mut aggregator = test_aggregator();
+        aggregator.winner_strategy = WinnerStrategy::Earliest { min_difficulty: 3 };
+        let first = Pubkey::new_unique();
+        let second = Pubkey::new_unique();
+        aggregator.insert(&mut contribution(first, 3)).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        aggregator.insert(&mut contribution(second, 4)).unwrap();
+        assert_eq!(aggregator.winner.unwrap().difficulty, 3);
+        // the winner's solution is replaced, so it can't be submitted
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        aggregator.insert(&mut contribution(first, 6)).unwrap();
+        let winner = aggregator.winner.unwrap();
+        assert_eq!(winner.difficulty, 4);
+        assert_eq!(
+            winner.solution.n,
+            aggregator.get_contribution(&second).unwrap().solution.n
+        );
+    }
+
+    #[test]
+    fn weighted_strategy_picks_qualifying_by_score() {
+        use rand::SeedableRng;
+        let strategy = WinnerStrategy::Weighted { min_difficulty: 4 };
+        let (mut aggregator, _) = strategy_aggregator(strategy);
+        // tracks the max difficulty until the round is closed
+        assert_eq!(aggregator.winner.unwrap().difficulty, 5);
+        let contributions = aggregator.get_current_contributions().unwrap();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+        let mut picks = HashMap::new();
+        for _ in 0..3_000 {
+            let winner = weighted_winner(contributions, 4, &mut rng).unwrap();
+            *picks.entry(winner.difficulty).or_insert(0u64) += 1;
+        }
+        // only difficulties 4 and 5 qualify, with scores 16 and 32
+        assert_eq!(picks.len(), 2);
+        assert!(picks[&5] > picks[&4] * 3 / 2);
+        assert!(weighted_winner(contributions, 6, &mut rng).is_none());
+    }
+
+    #[test]
+    fn recovery_from_pool_and_proof() {
+        assert_eq!(recovery(0, 0, 100), Recovery::Fresh);
+        assert_eq!(recovery(100, 7, 100), Recovery::Resumed);
+        assert_eq!(recovery(40, 7, 100), Recovery::Diverged);
+    }
+
+    #[test]
+    fn miner_rewards_net_of_commissions() {
+        assert_eq!(miner_rewards(1_000, 0, 5, 90), 950);
+        assert_eq!(miner_rewards(1_000, 1_000, 5, 90), 950 + 50);
+        assert_eq!(miner_rewards(1_000, 1_000, 0, 0), 2_000);
+    }
+
+    #[test]
+    fn expected_difficulty_from_hashrate() {
+        // 53 seconds at 1 hash per second
+        assert_eq!(expected_difficulty(1), 5);
+        assert_eq!(expected_difficulty(1_000), 15);
+        assert_eq!(expected_difficulty(0), 0);
+    }
+
+    #[test]
+    fn forensics_log_is_bounded() {
+        let mut aggregator = test_aggregator();
+        aggregator
+            .insert(&mut contribution(Pubkey::new_unique(), 2))
+            .unwrap();
+        assert!(aggregator.forensics.is_empty());
+        aggregator.forensics_size = 2;
+        let members: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        for mut contribution in distinct_contributions(&members, 3) {
+            aggregator.insert(&mut contribution).unwrap();
+        }
+        // rejected resubmissions aren't recorded
+        aggregator.insert(&mut contribution(members[2], 2)).unwrap();
+        let logged: Vec<Pubkey> = aggregator.forensics.iter().map(|e| e.member).collect();
+        assert_eq!(logged, members[1..].to_vec());
+        assert!(aggregator.forensics.iter().all(|e| e.difficulty == 3));
+    }
+
+    #[test]
+    fn record_round_evicts_oldest() {
+        let mut aggregator = test_aggregator();
+        for last_hash_at in 0..3 {
+            aggregator.challenge.lash_hash_at = last_hash_at;
+            aggregator
+                .contributions
+                .insert(last_hash_at as u64, HashSet::new());
+            aggregator
+                .insert(&mut contribution(Pubkey::new_unique(), 2))
+                .unwrap();
+            let (_, attestation) = aggregator.attestation();
+            aggregator.record_round(Some(attestation));
+            aggregator.total_score = 0;
+            aggregator.winner = None;
+            aggregator.solutions.clear();
+            aggregator.contributions_hash = ContributionsHash::default();
+        }
+        assert_eq!(aggregator.history.len(), 2);
+        assert!(aggregator.get_round(0).is_none());
+        let round = aggregator.get_round(2).unwrap();
+        assert_eq!(round.total_score, 4);
+        assert_eq!(round.winner_difficulty, Some(2));
+        assert!(round.attestation.is_some());
+    }
+
+    #[test]
+    fn attestation_is_independent_of_insert_order() {
+        let contributions: Vec<Contribution> = (2..8)
+            .map(|difficulty| contribution(Pubkey::new_unique(), difficulty))
+            .collect();
+        let mut forward = test_aggregator();
+        for c in contributions.iter() {
+            forward.insert(&mut { *c }).unwrap();
+        }
+        let mut reverse = test_aggregator();
+        for c in contributions.iter().rev() {
+            reverse.insert(&mut { *c }).unwrap();
+        }
+        let (block, attestation) = forward.attestation();
+        assert_eq!(attestation, reverse.attestation().1);
+        // the attestation is the hash of the published block
+        assert_eq!(attestation, crate::contributions_hash::hash_block(&block));
+    }
+
+    #[test]
+    fn attestation_matches_batch() {
+        let mut aggregator = test_aggregator();
+        let members: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        for (member, difficulty) in members.iter().zip(2..) {
+            aggregator
+                .insert(&mut contribution(*member, difficulty))
+                .unwrap();
+        }
+        // a replaced contribution, an ignored lower resubmission, and a duplicate solution
+        aggregator.insert(&mut contribution(members[0], 6)).unwrap();
+        aggregator.insert(&mut contribution(members[1], 2)).unwrap();
+        aggregator
+            .insert(&mut contribution(Pubkey::new_unique(), 4))
+            .unwrap();
+        let (block, attestation) = aggregator.attestation();
+        assert_eq!(block.lines().count(), 1 + 3);
+        assert_eq!(attestation, crate::contributions_hash::hash_block(&block));
+        // discarding the round starts the hash over
+        aggregator.winner = None;
+        assert!(aggregator.close_without_winner());
+        assert_eq!(aggregator.contributions_hash, ContributionsHash::default());
+    }
+
+    #[test]
+    fn timeline_is_ordered_by_arrival() {
+        let mut aggregator = test_aggregator();
+        aggregator.round_started = std::time::Instant::now() - std::time::Duration::from_secs(5);
+        let first = Pubkey::new_unique();
+        let second = Pubkey::new_unique();
+        aggregator.insert(&mut contribution(first, 2)).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        aggregator.insert(&mut contribution(second, 3)).unwrap();
+        let last_hash_at = aggregator.challenge.lash_hash_at;
+        let timeline = aggregator.get_timeline(last_hash_at).unwrap();
+        let members: Vec<Pubkey> = timeline.iter().map(|entry| entry.member).collect();
+        assert_eq!(members, vec![first, second]);
+        assert!(timeline[0].received_at >= 5_000);
+        assert!(timeline[1].received_at > timeline[0].received_at);
+        assert_eq!(timeline[1].score, 8);
+        // only retained rounds have a timeline
+        assert!(aggregator.get_timeline(last_hash_at + 1).is_none());
+    }
+
+    #[test]
+    fn arrivals_are_only_attested_if_opted_in() {
+        let member = Pubkey::new_unique();
+        let mut aggregator = test_aggregator();
+        aggregator.insert(&mut contribution(member, 2)).unwrap();
+        let (block, _) = aggregator.attestation();
+        assert_eq!(block.lines().last().unwrap().split_whitespace().count(), 3);
+        let mut attested = test_aggregator();
+        attested.attest_arrivals = true;
+        attested.insert(&mut contribution(member, 2)).unwrap();
+        let (block, attestation) = attested.attestation();
+        let received_at = attested.get_contribution(&member).unwrap().received_at;
+        assert_eq!(
+            block.split_whitespace().last(),
+            Some(received_at.to_string().as_str())
+        );
+        // the contributions hash is over the lines with the arrival time
+        assert_eq!(attestation, crate::contributions_hash::hash_block(&block));
+        let last_hash_at = attested.challenge.lash_hash_at;
+        let served = attested.get_attestation(last_hash_at).unwrap().unwrap();
+        assert_eq!(served.entries[0].received_at, Some(received_at));
+    }
+
+    #[test]
+    fn served_attestation_matches_block() {
+        let mut aggregator = test_aggregator();
+        for difficulty in 2..6 {
+            aggregator
+                .insert(&mut contribution(Pubkey::new_unique(), difficulty))
+                .unwrap();
+        }
+        let last_hash_at = aggregator.challenge.lash_hash_at;
+        let served = aggregator.get_attestation(last_hash_at).unwrap().unwrap();
+        let (block, attestation) = aggregator.attestation();
+        assert_eq!(served.hash, attestation);
+        // the entries are the lines of the block
+        let lines: Vec<String> = served
+            .entries
+            .iter()
+            .map(|e| format!("{} {} {}", e.member, e.digest, e.nonce))
+            .collect();
+        assert_eq!(served.version, Attestation::VERSION);
+        assert_eq!(
+            block,
+            format!("{}\n{}\n", Attestation::header(), lines.join("\n"))
+        );
+        assert!(aggregator
+            .get_attestation(last_hash_at + 1)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn excluded_staker_is_left_out_of_the_denominator() {
+        let operator = Pubkey::new_unique();
+        let staker = Pubkey::new_unique();
+        let stakers: StakerBalances = HashMap::from([(operator, 300), (staker, 100)]);
+        // included, the staker gets a quarter
+        let included = eligible_stakers(&stakers, None);
+        let balances: Vec<u64> = included.iter().map(|(_, b)| **b).collect();
+        let shares = distribute_with_remainder(1_000, balances.as_slice());
+        let index = included.iter().position(|(a, _)| **a == staker).unwrap();
+        assert_eq!(shares[index], 250);
+        // excluded, the staker gets it all
+        let eligible = eligible_stakers(&stakers, Some(&operator));
+        assert_eq!(eligible, vec![(&staker, &100)]);
+        let balances: Vec<u64> = eligible.iter().map(|(_, b)| **b).collect();
+        assert_eq!(
+            distribute_with_remainder(1_000, balances.as_slice()),
+            vec![1_000]
+        );
+    }
+
+    fn boost_event(mint: Pubkey, reward: u64) -> ore_api::event::BoostEvent {
+        let mut event: ore_api::event::BoostEvent = bytemuck::Zeroable::zeroed();
+        event.mint = mint;
+        event.reward = reward;
+        event
+    }
+
+    #[test]
+    fn empty_stakers_redirect_staker_reward() {
+        let mut aggregator = test_aggregator();
+        let pool = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        aggregator.stake.insert(mint, HashMap::new());
+        let config = test_config(90);
+        // to the operator
+        let operator = Pubkey::new_unique();
+        let unstaked = aggregator.unstaked_recipients(UnstakedRecipient::Operator, operator, 0);
+        let distribution = aggregator
+            .rewards_distribution_boost(
+                pool,
+                Some(boost_event(mint, 1_000)),
+                &config,
+                None,
+                unstaked.as_slice(),
+                &mut MemberAddresses::new(),
+            )
+            .unwrap();
+        let (operator_pda, _) = ore_pool_api::state::member_pda(operator, pool);
+        assert_eq!(distribution, vec![(operator_pda.to_string(), 900)]);
+        // to the miners, by score
+        let member = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        aggregator.insert(&mut contribution(member, 3)).unwrap();
+        aggregator.insert(&mut contribution(other, 1)).unwrap();
+        let unstaked = aggregator.unstaked_recipients(UnstakedRecipient::Miners, operator, 0);
+        let distribution = aggregator
+            .rewards_distribution_boost(
+                pool,
+                Some(boost_event(mint, 1_000)),
+                &config,
+                None,
+                unstaked.as_slice(),
+                &mut MemberAddresses::new(),
+            )
+            .unwrap();
+        let total: u64 = distribution.iter().map(|(_, reward)| *reward).sum();
+        assert_eq!(total, 900);
+        let (member_pda, _) = ore_pool_api::state::member_pda(member, pool);
+        let (other_pda, _) = ore_pool_api::state::member_pda(other, pool);
+        assert!(distribution.contains(&(member_pda.to_string(), 720)));
+        assert!(distribution.contains(&(other_pda.to_string(), 180)));
+    }
+
+    #[test]
+    fn staker_commission_override_applies_to_its_boost() {
+        let mut aggregator = test_aggregator();
+        let pool = Pubkey::new_unique();
+        let (overridden, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let staker = Pubkey::new_unique();
+        for mint in [overridden, other] {
+            aggregator
+                .stake
+                .insert(mint, HashMap::from([(staker, 100)]));
+        }
+        let mut config = test_config(90);
+        config.staker_commission_overrides.insert(overridden, 50);
+        let (staker_pda, _) = ore_pool_api::state::member_pda(staker, pool);
+        // stakers of the overridden boost get the override
+        let distribution = aggregator
+            .rewards_distribution_boost(
+                pool,
+                Some(boost_event(overridden, 1_000)),
+                &config,
+                None,
+                &[],
+                &mut MemberAddresses::new(),
+            )
+            .unwrap();
+        assert_eq!(distribution, vec![(staker_pda.to_string(), 500)]);
+        // and miners the rest
+        assert_eq!(
+            Aggregator::split_stake_rewards_for_miners(
+                Some(boost_event(overridden, 1_000)),
+                5,
+                &config
+            ),
+            450
+        );
+        // other boosts fall back to the staker commission
+        let distribution = aggregator
+            .rewards_distribution_boost(
+                pool,
+                Some(boost_event(other, 1_000)),
+                &config,
+                None,
+                &[],
+                &mut MemberAddresses::new(),
+            )
+            .unwrap();
+        assert_eq!(distribution, vec![(staker_pda.to_string(), 900)]);
+        assert_eq!(
+            Aggregator::split_stake_rewards_for_miners(Some(boost_event(other, 1_000)), 5, &config),
+            50
+        );
+    }
+
+    // a benchmark rather than a test:
+    // cargo test --release -p ore-pool-server member_addresses_bench -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn member_addresses_bench() {
+        const MEMBERS: usize = 5_000;
+        let mut aggregator = test_aggregator();
+        let pool = Pubkey::new_unique();
+        let operator = Pubkey::new_unique();
+        let members: Vec<Pubkey> = (0..MEMBERS).map(|_| Pubkey::new_unique()).collect();
+        // every member mines and stakes in every boost
+        let contributions = aggregator.contributions.entry(0).or_default();
+        for member in members.iter() {
+            contributions.insert(contribution(*member, 3));
+        }
+        let mints = [
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+        for mint in mints {
+            let balances = members.iter().map(|member| (*member, 100)).collect();
+            aggregator.stake.insert(mint, balances);
+        }
+        let rewards = Rewards {
+            base: ONE_ORE * 10,
+            boost_1: Some(boost_event(mints[0], ONE_ORE)),
+            boost_2: Some(boost_event(mints[1], ONE_ORE)),
+            boost_3: Some(boost_event(mints[2], ONE_ORE)),
+            last_hash_at: 0,
+        };
+        let config = test_config(50);
+        // derives the addresses per distribution unless shared
+        let distribute = |shared: bool| {
+            let mut addresses = MemberAddresses::new();
+            let fresh = |addresses: &mut MemberAddresses| {
+                if !shared {
+                    addresses.clear();
+                }
+            };
+            let started_at = std::time::Instant::now();
+            let mut distributions = vec![aggregator
+                .rewards_distribution(pool, &rewards, 5, &config, None, 0, &mut addresses)
+                .unwrap()];
+            for boost in [rewards.boost_1, rewards.boost_2, rewards.boost_3] {
+                fresh(&mut addresses);
+                distributions.push(
+                    aggregator
+                        .rewards_distribution_boost(pool, boost, &config, None, &[], &mut addresses)
+                        .unwrap(),
+                );
+            }
+            fresh(&mut addresses);
+            let attributed: Vec<&[(String, u64)]> =
+                distributions.iter().map(|d| d.as_slice()).collect();
+            let operator_reward = aggregator
+                .rewards_distribution_operator(
+                    pool,
+                    operator,
+                    &rewards,
+                    attributed.as_slice(),
+                    &mut addresses,
+                )
+                .unwrap();
+            distributions.push(vec![operator_reward]);
+            (started_at.elapsed(), distributions)
+        };
+        let (uncached, expected) = distribute(false);
+        let (cached, distributions) = distribute(true);
+        println!(
+            "{} members, uncached: {:?}, cached: {:?}",
+            MEMBERS, uncached, cached
+        );
+        assert_eq!(distributions, expected);
+    }
+
+    #[test]
+    fn miners_stakers_and_operator_sum_to_base_rewards() {
+        let mut aggregator = test_aggregator();
+        let pool = Pubkey::new_unique();
+        let operator = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let mut members: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        for mut contribution in distinct_contributions(members.as_slice(), 3) {
+            aggregator.insert(&mut contribution).unwrap();
+        }
+        let stakers = members.iter().map(|member| (*member, 1)).collect();
+        aggregator.stake.insert(mint, stakers);
+        let mut rewards = Rewards {
+            base: 1_003,
+            boost_1: Some(boost_event(mint, 101)),
+            boost_2: None,
+            boost_3: None,
+            last_hash_at: 0,
+        };
+        let config = test_config(90);
+        let mut addresses = MemberAddresses::new();
+        let miners = aggregator
+            .rewards_distribution(pool, &rewards, 7, &config, None, 0, &mut addresses)
+            .unwrap();
+        let stakers = aggregator
+            .rewards_distribution_boost(pool, rewards.boost_1, &config, None, &[], &mut addresses)
+            .unwrap();
+        let (_, operator_reward) = aggregator
+            .rewards_distribution_operator(
+                pool,
+                operator,
+                &rewards,
+                &[miners.as_slice(), stakers.as_slice()],
+                &mut addresses,
+            )
+            .unwrap();
+        let sum = |distribution: &[(String, u64)]| -> u64 {
+            distribution.iter().map(|(_, reward)| *reward).sum()
+        };
+        assert_eq!(sum(&miners), 841);
+        assert_eq!(sum(&stakers), 90);
+        assert_eq!(operator_reward, 72);
+        assert_eq!(sum(&miners) + sum(&stakers) + operator_reward, rewards.base);
+        // the tied remainder goes to the lowest member, whatever the set's order
+        members.sort();
+        let (first, _) = ore_pool_api::state::member_pda(members[0], pool);
+        assert_eq!(miners[0], (first.to_string(), 281));
+        // boost rewards above the base rewards are rejected rather than underflowing
+        rewards.boost_1 = Some(boost_event(mint, 1_004));
+        assert!(aggregator
+            .rewards_distribution(pool, &rewards, 7, &config, None, 0, &mut addresses)
+            .is_err());
+    }
+
+    #[test]
+    fn distribute_with_remainder_sums_to_total() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1_000 {
+            let len = rng.gen_range(1..50);
+            let weights: Vec<u64> = (0..len)
+                .map(|_| rng.gen_range(0..u32::MAX as u64))
+                .collect();
+            let total = rng.gen_range(0..u64::MAX / 2);
+            let shares = distribute_with_remainder(total, weights.as_slice());
+            if weights.iter().all(|w| *w == 0) {
+                continue;
+            }
+            assert_eq!(shares.iter().sum::<u64>(), total);
+        }
+    }
+
+    #[test]
+    fn distribute_with_remainder_assigns_to_largest() {
+        let shares = distribute_with_remainder(10, &[1, 2, 1]);
+        assert_eq!(shares, vec![2, 6, 2]);
+    }
+
+    #[test]
+    fn top_bus_selects_max_rewards() {
+        let balances = vec![(0, 10), (1, 500), (2, 0), (3, 499), (4, 500)];
+        assert_eq!(top_bus(balances.into_iter()), Some((1, 500)));
+        assert_eq!(top_bus(std::iter::empty()), None);
+    }
+
+    #[test]
+    fn new_member_share_is_redistributed_to_tenured() {
+        let mut aggregator = test_aggregator();
+        aggregator.challenge.lash_hash_at = 1_000;
+        aggregator.contributions.insert(1_000, HashSet::new());
+        let (tenured, new) = (Pubkey::new_unique(), Pubkey::new_unique());
+        aggregator.first_seen.insert(tenured, 0);
+        for mut contribution in distinct_contributions(&[tenured, new], 3) {
+            aggregator.insert(&mut contribution).unwrap();
+        }
+        // first seen this round
+        assert_eq!(aggregator.first_seen[&new], 1_000);
+        let rewards = Rewards {
+            base: 1_000,
+            boost_1: None,
+            boost_2: None,
+            boost_3: None,
+            last_hash_at: 1_000,
+        };
+        let pool = Pubkey::new_unique();
+        let attributed = |aggregator: &Aggregator, min_age: u64| -> HashMap<String, u64> {
+            aggregator
+                .rewards_distribution(
+                    pool,
+                    &rewards,
+                    0,
+                    &test_config(0),
+                    None,
+                    min_age,
+                    &mut MemberAddresses::new(),
+                )
+                .unwrap()
+                .into_iter()
+                .collect()
+        };
+        let address = |member: Pubkey| ore_pool_api::state::member_pda(member, pool).0.to_string();
+        let distribution = attributed(&aggregator, 600);
+        assert_eq!(distribution[&address(tenured)], 1_000);
+        assert_eq!(distribution[&address(new)], 0);
+        // split by score once disabled
+        let distribution = attributed(&aggregator, 0);
+        assert_eq!(distribution[&address(tenured)], 500);
+        assert_eq!(distribution[&address(new)], 500);
+    }
+
+    #[test]
+    fn tenured_scores_keep_all_without_tenured_members() {
+        assert_eq!(tenured_scores(vec![5, 7], &[90, 100], 100, 60), vec![5, 7]);
+        assert_eq!(tenured_scores(vec![5, 7], &[40, 100], 100, 60), vec![5, 0]);
+    }
+
+    #[test]
+    fn cap_scores_single_dominant_member() {
+        let scores = vec![1_000, 10, 10, 10, 10];
+        let capped = cap_scores(scores.as_slice(), 50);
+        assert_eq!(capped, vec![520, 130, 130, 130, 130]);
+        assert_eq!(capped.iter().sum::<u64>(), scores.iter().sum::<u64>());
+    }
+
+    #[test]
+    fn cap_scores_all_equal() {
+        let scores = vec![100, 100, 100, 100];
+        let capped = cap_scores(scores.as_slice(), 50);
+        assert_eq!(capped, scores);
+    }
+
+    #[test]
+    fn capped_score_limits_share_of_running_total() {
+        // the first contribution has nothing to be capped against
+        assert_eq!(capped_score(1_000, 0, 25), 1_000);
+        // a dominant contribution is capped at 25% of the running total
+        assert_eq!(capped_score(1_000, 300, 25), 100);
+        // equal contributions stay under the cap
+        assert_eq!(capped_score(100, 300, 25), 100);
+        assert_eq!(capped_score(1_000, 300, 100), 1_000);
+    }
+
+    #[test]
+    fn insert_stores_capped_score() {
+        let mut aggregator = test_aggregator();
+        aggregator.max_member_score_pct = Some(50);
+        let last_hash_at = aggregator.challenge.lash_hash_at as u64;
+        let members = [
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+        let mut contributions = distinct_contributions(&members, 10);
+        for contribution in contributions.iter_mut() {
+            contribution.score = 100;
+        }
+        contributions[2].score = 10_000;
+        for contribution in contributions.iter_mut() {
+            aggregator.insert(contribution).unwrap();
+        }
+        // the dominant member is capped at half of the running total
+        let stored = aggregator.contributions[&last_hash_at]
+            .get(&contributions[2])
+            .unwrap()
+            .score;
+        assert_eq!(stored, 200);
+        assert_eq!(aggregator.total_score, 400);
+        // all equal contributions are stored as is
+        let mut aggregator = test_aggregator();
+        aggregator.max_member_score_pct = Some(50);
+        let mut contributions = distinct_contributions(&members, 10);
+        for contribution in contributions.iter_mut() {
+            contribution.score = 100;
+            aggregator.insert(contribution).unwrap();
+        }
+        assert_eq!(aggregator.total_score, 300);
+    }
+}
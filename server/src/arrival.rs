@@ -0,0 +1,183 @@
+use std::collections::VecDeque;
+
+/// The share of the learned score (in basis points) left to arrive
+/// below which waiting any longer isn't worth the risk of a late submit.
+const NEGLIGIBLE_SCORE_BPS: u64 = 100;
+
+/// The max offset (in seconds) tracked per round,
+/// later arrivals are counted in the last bucket.
+const MAX_OFFSET_SECS: usize = 120;
+
+/// Learns when contributions arrive within a round,
+/// so that a round can be closed once the score still expected to arrive is negligible.
+///
+/// A round closed early never observes its tail, so learning from it
+/// would pull the curve earlier and earlier. Those rounds are left out,
+/// and once every window a round runs to the full cutoff to keep the curve fresh.
+pub struct ArrivalCurve {
+    /// The score that arrived per second into the round, per recent round, oldest first.
+    rounds: VecDeque<Vec<u64>>,
+
+    /// The score that arrived per second into the current round.
+    current: Vec<u64>,
+
+    /// The number of rounds learned from, disabled if zero.
+    window: usize,
+
+    /// Whether the current round was closed early.
+    censored: bool,
+
+    /// The number of rounds closed early since the last round that ran to the cutoff.
+    rounds_since_full: usize,
+}
+
+impl ArrivalCurve {
+    pub fn new(window: usize) -> Self {
+        Self {
+            rounds: VecDeque::with_capacity(window),
+            current: vec![],
+            window,
+            censored: false,
+            rounds_since_full: 0,
+        }
+    }
+
+    /// Records score that arrived some seconds into the current round.
+    pub fn record(&mut self, offset_secs: u64, score: u64) {
+        if self.window == 0 {
+            return;
+        }
+        let bucket = (offset_secs as usize).min(MAX_OFFSET_SECS);
+        if self.current.len() <= bucket {
+            self.current.resize(bucket + 1, 0);
+        }
+        self.current[bucket] = self.current[bucket].saturating_add(score);
+    }
+
+    /// Marks the current round as closed before its cutoff.
+    pub fn censor(&mut self) {
+        self.censored = true;
+    }
+
+    /// Learns from the closing round, unless it was closed early, and starts the next.
+    pub fn finish_round(&mut self) {
+        let current = std::mem::take(&mut self.current);
+        if self.window == 0 {
+            return;
+        }
+        if std::mem::take(&mut self.censored) {
+            self.rounds_since_full = self.rounds_since_full.saturating_add(1);
+            return;
+        }
+        self.rounds_since_full = 0;
+        if self.rounds.len() >= self.window {
+            self.rounds.pop_front();
+        }
+        self.rounds.push_back(current);
+    }
+
+    /// The seconds into the round after which the score still expected to arrive is negligible.
+    /// None until the window is full, when a full round is due, or if nothing was learned.
+    pub fn early_stop(&self) -> Option<u64> {
+        if self.window == 0 || self.rounds.len() < self.window {
+            return None;
+        }
+        if self.rounds_since_full >= self.window {
+            return None;
+        }
+        let mut histogram = vec![0u64; MAX_OFFSET_SECS + 1];
+        for round in self.rounds.iter() {
+            for (bucket, score) in round.iter().enumerate() {
+                histogram[bucket] = histogram[bucket].saturating_add(*score);
+            }
+        }
+        let total = histogram
+            .iter()
+            .fold(0u64, |total, score| total.saturating_add(*score));
+        if total == 0 {
+            return None;
+        }
+        let negligible = (total as u128 * NEGLIGIBLE_SCORE_BPS as u128 / 10_000) as u64;
+        let mut arrived = 0u64;
+        for (bucket, score) in histogram.iter().enumerate() {
+            arrived = arrived.saturating_add(*score);
+            if total - arrived <= negligible {
+                // close at the end of the bucket
+                return Some(bucket as u64 + 1);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn learn(curve: &mut ArrivalCurve, arrivals: &[(u64, u64)]) {
+        for (offset, score) in arrivals {
+            curve.record(*offset, *score);
+        }
+        curve.finish_round();
+    }
+
+    #[test]
+    fn disabled_without_window() {
+        let mut curve = ArrivalCurve::new(0);
+        learn(&mut curve, &[(10, 100)]);
+        assert_eq!(curve.early_stop(), None);
+    }
+
+    #[test]
+    fn early_stop_waits_for_full_window() {
+        let mut curve = ArrivalCurve::new(2);
+        learn(&mut curve, &[(10, 100)]);
+        assert_eq!(curve.early_stop(), None);
+        learn(&mut curve, &[(12, 100)]);
+        assert_eq!(curve.early_stop(), Some(13));
+    }
+
+    #[test]
+    fn early_stop_ignores_negligible_tail() {
+        let mut curve = ArrivalCurve::new(1);
+        learn(&mut curve, &[(5, 500), (20, 9_400), (50, 100)]);
+        assert_eq!(curve.early_stop(), Some(21));
+        let mut curve = ArrivalCurve::new(1);
+        learn(&mut curve, &[(5, 500), (20, 9_400), (50, 101)]);
+        assert_eq!(curve.early_stop(), Some(51));
+    }
+
+    #[test]
+    fn oldest_round_is_evicted() {
+        let mut curve = ArrivalCurve::new(1);
+        learn(&mut curve, &[(40, 100)]);
+        learn(&mut curve, &[(10, 100)]);
+        assert_eq!(curve.early_stop(), Some(11));
+    }
+
+    #[test]
+    fn censored_rounds_are_not_learned() {
+        let mut curve = ArrivalCurve::new(1);
+        learn(&mut curve, &[(30, 100)]);
+        curve.record(5, 100);
+        curve.censor();
+        curve.finish_round();
+        assert_eq!(curve.early_stop(), None);
+        // the full round that follows is learned
+        learn(&mut curve, &[(20, 100)]);
+        assert_eq!(curve.early_stop(), Some(21));
+    }
+
+    #[test]
+    fn full_round_is_due_once_per_window() {
+        let mut curve = ArrivalCurve::new(2);
+        learn(&mut curve, &[(30, 100)]);
+        learn(&mut curve, &[(30, 100)]);
+        for _ in 0..2 {
+            assert_eq!(curve.early_stop(), Some(31));
+            curve.censor();
+            curve.finish_round();
+        }
+        assert_eq!(curve.early_stop(), None);
+    }
+}
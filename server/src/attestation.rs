@@ -0,0 +1,118 @@
+use std::io::{Read, Write};
+
+use aws_sdk_s3::primitives::ByteStream;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+
+use crate::{config::S3Config, database, error::Error};
+
+const S3_KEY_PREFIX: &str = "attestations";
+
+/// client for publishing attestation blocks to s3
+#[derive(Clone)]
+pub struct Client {
+    s3_client: aws_sdk_s3::Client,
+    /// the bucket attestation blocks are written to
+    bucket: String,
+    /// the region of the bucket, used for building object urls
+    region: String,
+}
+
+impl Client {
+    pub async fn new(s3: &S3Config) -> Self {
+        let bucket = s3.bucket.clone();
+        let region = s3.region.clone();
+        let config = aws_config::defaults(aws_config::BehaviorVersion::latest())
+            .region(aws_config::Region::new(region.clone()))
+            .load()
+            .await;
+        let s3_client = aws_sdk_s3::Client::new(&config);
+        Self {
+            s3_client,
+            bucket,
+            region,
+        }
+    }
+
+    /// uploads the attestation block gzip-compressed and writes the object url to the db,
+    /// keyed by the last-hash-at of the round.
+    pub async fn publish(
+        &self,
+        db_client: &deadpool_postgres::Pool,
+        last_hash_at: i64,
+        block: String,
+    ) -> Result<String, Error> {
+        let key = object_key(last_hash_at);
+        let body = compress(block.as_str())?;
+        self.s3_client
+            .put_object()
+            .bucket(self.bucket.as_str())
+            .key(key.as_str())
+            .content_type("text/plain")
+            .content_encoding("gzip")
+            .body(ByteStream::from(body))
+            .send()
+            .await?;
+        let url = format!(
+            "https://{}.s3.{}.amazonaws.com/{}",
+            self.bucket, self.region, key
+        );
+        let conn = db_client.get().await?;
+        database::write_attestation(&conn, last_hash_at, url.as_str(), true).await?;
+        Ok(url)
+    }
+
+    /// downloads a published attestation block and decompresses it.
+    pub async fn fetch(&self, last_hash_at: i64) -> Result<String, Error> {
+        let object = self
+            .s3_client
+            .get_object()
+            .bucket(self.bucket.as_str())
+            .key(object_key(last_hash_at))
+            .send()
+            .await?;
+        let bytes = object.body.collect().await?.into_bytes();
+        decompress(&bytes)
+    }
+}
+
+fn object_key(last_hash_at: i64) -> String {
+    format!("{}/{}.txt.gz", S3_KEY_PREFIX, last_hash_at)
+}
+
+fn compress(block: &str) -> Result<Vec<u8>, Error> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(block.as_bytes())?;
+    Ok(encoder.finish()?)
+}
+
+fn decompress(bytes: &[u8]) -> Result<String, Error> {
+    let mut block = String::new();
+    GzDecoder::new(bytes).read_to_string(&mut block)?;
+    Ok(block)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write;
+
+    use super::*;
+
+    #[test]
+    fn compressed_block_round_trips() {
+        let block = (0..1_000).fold(String::new(), |mut block, n| {
+            let _ = writeln!(block, "member{:04} {:064x} {:016x}", n, n, n);
+            block
+        });
+        let compressed = compress(block.as_str()).unwrap();
+        assert!(compressed.len() < block.len() / 2);
+        assert_eq!(decompress(compressed.as_slice()).unwrap(), block);
+        // an empty round
+        let compressed = compress("").unwrap();
+        assert_eq!(decompress(compressed.as_slice()).unwrap(), "");
+    }
+
+    #[test]
+    fn plain_block_does_not_decompress() {
+        assert!(decompress(b"member 00 00\n").is_err());
+    }
+}
@@ -0,0 +1,108 @@
+/// The consecutive submit failures after which contributions are paused by default.
+pub const DEFAULT_SUBMIT_FAILURE_THRESHOLD: u32 = 5;
+
+/// Pauses contributions after repeated submit failures,
+/// so that members aren't mining for rounds that can't land (e.g. the rpc is down,
+/// or the pool authority can't pay the fee).
+/// The round keeps being submitted while paused, and the breaker closes again
+/// on the first successful submit, or when reset by an admin.
+#[derive(Debug)]
+pub struct Breaker {
+    /// The consecutive failures that trip the breaker, disabled if zero.
+    threshold: u32,
+
+    /// The submit failures since the last success.
+    consecutive_failures: u32,
+}
+
+impl Breaker {
+    pub fn new(threshold: u32) -> Self {
+        Self {
+            threshold,
+            consecutive_failures: 0,
+        }
+    }
+
+    /// Whether or not contributions are paused.
+    pub fn is_open(&self) -> bool {
+        self.threshold > 0 && self.consecutive_failures >= self.threshold
+    }
+
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures
+    }
+
+    pub fn record_success(&mut self) {
+        if self.is_open() {
+            log::info!("submit landed, resuming contributions");
+        }
+        self.consecutive_failures = 0;
+    }
+
+    pub fn record_failure(&mut self) {
+        let was_open = self.is_open();
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        if !was_open && self.is_open() {
+            log::error!(
+                "{} consecutive submit failures, pausing contributions",
+                self.consecutive_failures
+            );
+        }
+    }
+
+    /// Closes the breaker by hand, e.g. once the cause of the failures is fixed.
+    pub fn reset(&mut self) {
+        if self.is_open() {
+            log::info!("breaker reset, resuming contributions");
+        }
+        self.consecutive_failures = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trips_after_consecutive_failures_and_recovers_on_success() {
+        let mut breaker = Breaker::new(3);
+        breaker.record_failure();
+        breaker.record_failure();
+        assert!(!breaker.is_open());
+        // a success in between starts the count over
+        breaker.record_success();
+        breaker.record_failure();
+        breaker.record_failure();
+        assert!(!breaker.is_open());
+        breaker.record_failure();
+        assert!(breaker.is_open());
+        // further failures keep it open
+        breaker.record_failure();
+        assert!(breaker.is_open());
+        assert_eq!(breaker.consecutive_failures(), 4);
+        breaker.record_success();
+        assert!(!breaker.is_open());
+        assert_eq!(breaker.consecutive_failures(), 0);
+    }
+
+    #[test]
+    fn admin_reset_closes_the_breaker() {
+        let mut breaker = Breaker::new(1);
+        breaker.record_failure();
+        assert!(breaker.is_open());
+        breaker.reset();
+        assert!(!breaker.is_open());
+        // and it trips again on the next failure
+        breaker.record_failure();
+        assert!(breaker.is_open());
+    }
+
+    #[test]
+    fn zero_threshold_never_trips() {
+        let mut breaker = Breaker::new(0);
+        for _ in 0..100 {
+            breaker.record_failure();
+        }
+        assert!(!breaker.is_open());
+    }
+}
@@ -0,0 +1,153 @@
+use ore_api::consts::{MINT_ADDRESS, ONE_ORE};
+use ore_pool_api::state::{member_pda, share_pda, Member, Share};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+use steel::AccountDeserialize;
+
+use crate::{database, error::Error, operator::Operator, tx};
+
+/// The min balance worth compounding,
+/// below which the transaction fee the operator pays isn't worth the restake.
+/// Thresholds below this are rejected at startup.
+pub const MIN_AUTO_COMPOUND: u64 = ONE_ORE / 100;
+
+/// Compounds share accounts with the claim cpi, so fewer fit in a transaction than attributions.
+const NUM_COMPOUNDS_PER_TX: usize = 5;
+
+/// The number of accounts fetched per rpc call.
+const NUM_ACCOUNTS_PER_FETCH: usize = 100;
+
+const COMPOUND_RETRY: tx::submit::Retry = tx::submit::Retry {
+    max_retries: 3,
+    base_delay_ms: 1_000,
+    max_jitter_ms: 500,
+};
+
+/// Restakes the rewards of the members that opted in into their ORE share,
+/// once their claimable balance crosses the threshold.
+///
+/// The operator signs the compound instruction on the members' behalf.
+/// The program only lets it move a member's balance into that member's own share,
+/// so the operator can't redirect the rewards, and the member can unstake at any time.
+/// The opt-in is held off-chain, set by a payload signed by the member authority,
+/// so members trust the operator to honor opting out, as they already do for attribution.
+pub struct AutoCompound {
+    /// The min claimable balance for a member to be compounded.
+    threshold: u64,
+}
+
+impl AutoCompound {
+    /// Disabled unless a threshold is configured.
+    pub fn new(threshold: Option<u64>) -> Option<Self> {
+        threshold.map(|threshold| Self { threshold })
+    }
+
+    /// Compounds every eligible member, in batches.
+    /// A failed batch is logged and retried on the next epoch,
+    /// since the compound instruction only moves whatever balance is there.
+    pub async fn run(&self, operator: &Operator) -> Result<(), Error> {
+        let db_client = operator.db_client.get().await?;
+        let authorities = database::read_auto_compound_members(&db_client).await?;
+        drop(db_client);
+        if authorities.is_empty() {
+            return Ok(());
+        }
+        let signer = operator.signer.pubkey();
+        let pool_pda = operator.pool;
+        let mut eligible = vec![];
+        for chunk in authorities.chunks(NUM_ACCOUNTS_PER_FETCH / 2) {
+            // fetch members and their ORE shares in one call
+            let addresses: Vec<Pubkey> = chunk
+                .iter()
+                .flat_map(|authority| {
+                    [
+                        member_pda(*authority, pool_pda).0,
+                        share_pda(*authority, pool_pda, MINT_ADDRESS).0,
+                    ]
+                })
+                .collect();
+            let accounts = operator
+                .rpc_client
+                .get_multiple_accounts(addresses.as_slice())
+                .await?;
+            let candidates = chunk
+                .iter()
+                .zip(accounts.chunks(2))
+                .map(|(authority, pair)| {
+                    let balance = pair[0]
+                        .as_ref()
+                        .and_then(|a| Member::try_from_bytes(a.data.as_slice()).ok())
+                        .map(|m| m.balance);
+                    let has_share = pair[1]
+                        .as_ref()
+                        .is_some_and(|a| Share::try_from_bytes(a.data.as_slice()).is_ok());
+                    (*authority, balance, has_share)
+                });
+            eligible.extend(eligible_members(candidates, self.threshold));
+        }
+        log::info!("auto compounding members: {}", eligible.len());
+        let mut batch: tx::batch::Batch<Instruction> = tx::batch::Batch::new(NUM_COMPOUNDS_PER_TX);
+        let mut batches = vec![];
+        for authority in eligible {
+            let ix = ore_pool_api::sdk::compound(signer, pool_pda, authority, None);
+            batches.extend(batch.push(ix));
+        }
+        batches.extend(batch.finish());
+        for ixs in batches {
+            match tx::submit::submit_and_confirm_instructions(
+                operator.signer.as_ref(),
+                &operator.rpc_client,
+                ixs.as_slice(),
+                1_500_000,
+                20_000,
+                COMPOUND_RETRY,
+            )
+            .await
+            {
+                Ok(sig) => log::info!("auto compound: {:?}", sig),
+                Err(err) => log::error!("auto compound: {:?}", err),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The members with a claimable balance at the threshold and an ORE share to compound into.
+/// Members without a share are skipped, they must open one before anything can be restaked.
+fn eligible_members(
+    candidates: impl Iterator<Item = (Pubkey, Option<u64>, bool)>,
+    threshold: u64,
+) -> Vec<Pubkey> {
+    candidates
+        .filter_map(
+            |(authority, balance, has_share)| match (balance, has_share) {
+                (Some(balance), true) if balance >= threshold => Some(authority),
+                (Some(_), false) => {
+                    log::warn!("auto compound: no ORE share for member {}", authority);
+                    None
+                }
+                _ => None,
+            },
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eligible_members_meet_threshold_with_share() {
+        let members: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        let candidates = vec![
+            (members[0], Some(MIN_AUTO_COMPOUND), true),
+            (members[1], Some(MIN_AUTO_COMPOUND - 1), true),
+            (members[2], Some(MIN_AUTO_COMPOUND * 2), false),
+            (members[3], None, true),
+            (members[4], Some(MIN_AUTO_COMPOUND * 2), true),
+        ];
+        assert_eq!(
+            eligible_members(candidates.into_iter(), MIN_AUTO_COMPOUND),
+            vec![members[0], members[4]]
+        );
+    }
+}
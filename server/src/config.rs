@@ -0,0 +1,1337 @@
+use std::{collections::HashMap, str::FromStr, time::Duration};
+
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+
+use crate::{
+    access::DEFAULT_ACCESS_REFRESH_SECS,
+    aggregator::{
+        DEFAULT_CHALLENGE_HISTORY_SIZE, DEFAULT_FORENSICS_LOG_SIZE, DEFAULT_REWARDS_TIMEOUT_SECS,
+        MAX_DIFFICULTY,
+    },
+    breaker::DEFAULT_SUBMIT_FAILURE_THRESHOLD,
+    compound::MIN_AUTO_COMPOUND,
+    error::Error,
+    jail::{DEFAULT_JAIL_COOLDOWN_SECS, DEFAULT_JAIL_THRESHOLD, DEFAULT_JAIL_WINDOW_SECS},
+    operator::{DustRecipient, UnstakedRecipient, WinnerStrategy, BUFFER_OPERATOR},
+    rate_limit::DEFAULT_CONTRIBUTE_RATE_PER_SEC,
+    rpc::RpcPolicy,
+    scoring::Curve,
+    signer::{DEFAULT_REMOTE_SIGNER_CONNECT_TIMEOUT_MS, DEFAULT_REMOTE_SIGNER_TIMEOUT_MS},
+    stake::{
+        DEFAULT_STAKE_POLL_SECS, DEFAULT_STAKE_REFRESH_SECS, DEFAULT_STAKE_REFRESH_THRESHOLD_PCT,
+    },
+    utils::{parse_allowed_origins, AllowedOrigins},
+};
+
+/// The default compute unit limit for submits.
+const SUBMIT_CU_LIMIT: u32 = 1_500_000;
+/// The default compute unit price (in micro-lamports) for submits.
+const SUBMIT_CU_PRICE: u64 = 500_000;
+/// The default number of contributions buffered for the aggregator.
+const CONTRIBUTIONS_CAPACITY: usize = 10_000;
+/// The default max number of open websocket connections.
+const MAX_WS_CONNECTIONS: usize = 10_000;
+/// The default number of boosts whose stakers are fetched at once.
+const STAKE_FETCH_CONCURRENCY: usize = 4;
+/// The length (in seconds) of a round, the cutoff margin must leave part of it.
+const ROUND_SECS: u64 = 60;
+/// The default port listened on if no bind address is configured.
+const PORT: u16 = 8080;
+
+/// Env vars by name.
+pub type ConfigVars = HashMap<String, String>;
+
+/// The server config, loaded from the environment once at startup.
+/// Every var is validated up front, and all of the missing or invalid vars
+/// are reported together, so that a misconfigured server fails at boot.
+#[derive(Debug)]
+pub struct Config {
+    /// Where the pool authority signs from.
+    pub signer: SignerConfig,
+
+    /// The authority the pool was launched with, which its address is derived from,
+    /// if it has since been transferred to the signer.
+    pub pool_launch_authority: Option<Pubkey>,
+
+    /// The Solana RPC urls, the primary first.
+    pub rpc_urls: Vec<String>,
+
+    /// Which RPC url a call is sent to first.
+    pub rpc_policy: RpcPolicy,
+
+    /// The Postgres url.
+    pub db_url: String,
+
+    /// The optional Postgres url of a read replica for read-heavy endpoints.
+    pub db_read_url: Option<String>,
+
+    /// The boost mints to accept stake for and their optional attribution epochs (in minutes).
+    pub boosts: Vec<(Pubkey, Option<u64>)>,
+
+    /// How often (in minutes) all members are attributed.
+    pub attribution_epoch: u64,
+
+    /// How often (in minutes) pending stake is committed.
+    pub stake_commit_epoch: u64,
+
+    /// The max number of contributions buffered for the aggregator.
+    pub contributions_capacity: usize,
+
+    /// The max number of open websocket connections, across the challenge and balance streams.
+    pub max_ws_connections: usize,
+
+    /// The min seconds before the on-chain deadline to stop accepting contributions.
+    pub min_cutoff_margin: u64,
+
+    /// The requested seconds past the cutoff that late contributions are still accepted for.
+    pub late_grace_secs: u64,
+
+    /// The optional env file the reloadable config is read from, overriding the environment.
+    pub config_file: Option<String>,
+
+    /// The config that can be reloaded without a restart.
+    pub reloadable: ReloadableConfig,
+
+    /// The max share of the total score in % percentage
+    /// that any single member may be attributed per challenge.
+    pub max_member_score_pct: Option<u64>,
+
+    /// Who receives a boost's staker reward when the boost has no eligible stake.
+    pub unstaked_recipient: UnstakedRecipient,
+
+    /// The min seconds since a member first contributed before they are attributed, disabled if zero.
+    pub min_member_age_secs: u64,
+
+    /// The min balance increment written for a member per round, disabled if zero.
+    pub min_attribution: u64,
+
+    /// What becomes of the attributions below the min attribution.
+    pub dust_recipient: DustRecipient,
+
+    /// How the solution submitted on-chain is selected from the contributions.
+    pub winner_strategy: WinnerStrategy,
+
+    /// The min rewards a bus must hold to be submitted to.
+    pub min_bus_rewards: u64,
+
+    /// Whether or not to exclude the operator's own stake from the staker commission.
+    pub exclude_operator_stake: bool,
+
+    /// Whether or not to reject contributions from members registered after the round started.
+    pub require_full_round: bool,
+
+    /// Whether or not only allowlisted authorities may register and contribute.
+    pub allowlist_only: bool,
+
+    /// How often the access lists are re-read from the db.
+    pub access_refresh: std::time::Duration,
+
+    /// Whether or not to skip sending submit transactions.
+    pub dry_run: bool,
+
+    /// Whether or not to distribute expired rounds from the on-chain pool reward.
+    pub rewards_fallback: bool,
+
+    /// The number of closed rounds retained in the challenge history.
+    pub challenge_history_size: usize,
+
+    /// The number of accepted contributions retained in the forensics log.
+    pub forensics_log_size: usize,
+
+    /// Whether or not each contribution's arrival time is included in the attestation block.
+    pub attest_arrivals: bool,
+
+    /// How long a submitted round waits on its rewards webhook before it is expired.
+    pub rewards_timeout: std::time::Duration,
+
+    /// The commitment a landed submit must reach before its round is reset and attributed.
+    pub rewards_commitment: CommitmentConfig,
+
+    /// The consecutive submit failures after which contributions are paused, disabled if zero.
+    pub submit_failure_threshold: u32,
+
+    /// The number of recent rounds the adaptive cutoff learns from, disabled if zero.
+    pub adaptive_cutoff_window: usize,
+
+    /// The max number of boosts whose stakers are fetched at once.
+    pub stake_fetch_concurrency: usize,
+
+    /// How often the staker balances are re-fetched regardless of boost totals.
+    pub stake_refresh_interval: Duration,
+
+    /// How often the boost totals are polled for material changes.
+    pub stake_poll_interval: Duration,
+
+    /// The change in a boost's total deposits in % percentage that forces an early refresh.
+    pub stake_refresh_threshold_pct: u64,
+
+    /// The Helius webhook that rewards and share account events are delivered by.
+    pub helius: HeliusConfig,
+
+    /// The s3 bucket attestation blocks are published to, if any.
+    pub s3: Option<S3Config>,
+
+    /// The tls cert and key, if tls is terminated in the server rather than behind a proxy.
+    pub tls: Option<TlsConfig>,
+
+    /// The origins allowed by cors.
+    pub allowed_origins: AllowedOrigins,
+
+    /// The address the server listens on.
+    pub bind_addr: String,
+
+    /// How logs are formatted.
+    pub log_format: LogFormat,
+
+    /// The max number of contributions validated at once.
+    pub validation_workers: usize,
+
+    /// The curve mapping solution difficulty to contribution score.
+    pub score_curve: Curve,
+
+    /// The number of contributions per second a member may sustain.
+    pub contribute_rate_per_sec: f64,
+
+    /// The number of invalid submissions within the jail window before a member is jailed.
+    pub jail_threshold: usize,
+
+    /// The sliding window over which invalid submissions are counted.
+    pub jail_window: Duration,
+
+    /// How long a jailed member is rejected for.
+    pub jail_cooldown: Duration,
+
+    /// The min claimable balance for a member to be auto compounded, disabled if not set.
+    pub auto_compound_threshold: Option<u64>,
+
+    /// The bearer token of the admin endpoints, disabled if not set.
+    pub admin_token: Option<String>,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self, Error> {
+        let env: ConfigVars = std::env::vars().collect();
+        Self::load(&env)
+    }
+
+    /// Loads the config from the environment and the config file it names, if any.
+    fn load(env: &ConfigVars) -> Result<Self, Error> {
+        let mut vars = Vars::new(env);
+        let config_file = vars.optional::<String>("CONFIG_FILE");
+        let file = match read_config_file(config_file.as_deref()) {
+            Ok(file) => file,
+            Err(err) => {
+                vars.invalid("CONFIG_FILE", format!("could not be read: {}", err));
+                HashMap::new()
+            }
+        };
+        // a remote signer takes the place of the keypair file
+        let signer = match vars.optional::<String>("REMOTE_SIGNER_URL") {
+            Some(url) => {
+                if !(url.starts_with("http://") || url.starts_with("https://")) {
+                    vars.invalid("REMOTE_SIGNER_URL", format!("must be an http url: {}", url));
+                }
+                let connect_timeout_ms = vars.or(
+                    "REMOTE_SIGNER_CONNECT_TIMEOUT_MS",
+                    DEFAULT_REMOTE_SIGNER_CONNECT_TIMEOUT_MS,
+                );
+                if connect_timeout_ms == 0 {
+                    vars.invalid(
+                        "REMOTE_SIGNER_CONNECT_TIMEOUT_MS",
+                        "must be at least 1".to_string(),
+                    );
+                }
+                let timeout_ms =
+                    vars.or("REMOTE_SIGNER_TIMEOUT_MS", DEFAULT_REMOTE_SIGNER_TIMEOUT_MS);
+                if timeout_ms == 0 {
+                    vars.invalid("REMOTE_SIGNER_TIMEOUT_MS", "must be at least 1".to_string());
+                }
+                SignerConfig::Remote {
+                    url,
+                    pubkey: vars.required("REMOTE_SIGNER_PUBKEY"),
+                    auth_token: vars.optional("REMOTE_SIGNER_TOKEN"),
+                    connect_timeout: std::time::Duration::from_millis(connect_timeout_ms),
+                    timeout: std::time::Duration::from_millis(timeout_ms),
+                    receipts: vars.flag("REMOTE_SIGNER_RECEIPTS"),
+                }
+            }
+            None => SignerConfig::Keypair {
+                path: vars.required("KEYPAIR_PATH"),
+            },
+        };
+        let pool_launch_authority = vars.optional("POOL_LAUNCH_AUTHORITY");
+        // RPC_URLS takes precedence over RPC_URL
+        let (rpc_urls_var, rpc_urls): (&str, Vec<String>) =
+            match vars.optional::<String>("RPC_URLS") {
+                Some(urls) => (
+                    "RPC_URLS",
+                    urls.split(',')
+                        .map(|url| url.trim().to_string())
+                        .filter(|url| !url.is_empty())
+                        .collect(),
+                ),
+                None => ("RPC_URL", vec![vars.required::<String>("RPC_URL")]),
+            };
+        if rpc_urls.is_empty() {
+            vars.invalid(rpc_urls_var, "must list at least one url".to_string());
+        }
+        for url in rpc_urls.iter() {
+            if !(url.is_empty() || url.starts_with("http://") || url.starts_with("https://")) {
+                vars.invalid(rpc_urls_var, format!("must be an http url: {}", url));
+            }
+        }
+        let rpc_policy = match vars.optional::<String>("RPC_POLICY") {
+            None => RpcPolicy::Primary,
+            Some(str) => match str.as_str() {
+                "primary" => RpcPolicy::Primary,
+                "round-robin" => RpcPolicy::RoundRobin,
+                _ => {
+                    vars.invalid("RPC_POLICY", format!("invalid: {}", str));
+                    RpcPolicy::Primary
+                }
+            },
+        };
+        let db_url = vars.required("DB_URL");
+        let db_read_url = vars.optional("DATABASE_READ_URL");
+        let mut boosts = vec![];
+        for (boost_var, epoch_var) in [
+            ("BOOST_ONE", "ATTR_EPOCH_BOOST_ONE"),
+            ("BOOST_TWO", "ATTR_EPOCH_BOOST_TWO"),
+            ("BOOST_THREE", "ATTR_EPOCH_BOOST_THREE"),
+        ] {
+            if let Some(mint) = vars.optional::<Pubkey>(boost_var) {
+                let epoch = vars.optional::<u64>(epoch_var);
+                if epoch == Some(0) {
+                    vars.invalid(epoch_var, "must be at least 1".to_string());
+                }
+                boosts.push((mint, epoch));
+            }
+        }
+        let attribution_epoch = vars.epoch("ATTR_EPOCH");
+        let stake_commit_epoch = vars.epoch("STAKE_EPOCH");
+        let contributions_capacity = vars.or("CONTRIBUTIONS_CAPACITY", CONTRIBUTIONS_CAPACITY);
+        if contributions_capacity == 0 {
+            vars.invalid("CONTRIBUTIONS_CAPACITY", "must be at least 1".to_string());
+        }
+        let max_ws_connections = vars.or("MAX_WS_CONNECTIONS", MAX_WS_CONNECTIONS);
+        if max_ws_connections == 0 {
+            vars.invalid("MAX_WS_CONNECTIONS", "must be at least 1".to_string());
+        }
+        let min_cutoff_margin = vars.or("MIN_CUTOFF_MARGIN", BUFFER_OPERATOR);
+        if min_cutoff_margin >= ROUND_SECS {
+            vars.invalid(
+                "MIN_CUTOFF_MARGIN",
+                format!("must be less than {}", ROUND_SECS),
+            );
+        }
+        let late_grace_secs = vars.or("LATE_GRACE_SECS", 0);
+        let max_member_score_pct = vars.optional::<u64>("MAX_MEMBER_SCORE_PCT");
+        if let Some(pct) = max_member_score_pct {
+            if pct == 0 || pct > 100 {
+                vars.invalid("MAX_MEMBER_SCORE_PCT", "must be from 1 to 100".to_string());
+            }
+        }
+        let unstaked_recipient = match vars.optional::<String>("UNSTAKED_BOOST_RECIPIENT") {
+            None => UnstakedRecipient::Operator,
+            Some(str) => match str.as_str() {
+                "operator" => UnstakedRecipient::Operator,
+                "miners" => UnstakedRecipient::Miners,
+                _ => {
+                    vars.invalid("UNSTAKED_BOOST_RECIPIENT", format!("invalid: {}", str));
+                    UnstakedRecipient::Operator
+                }
+            },
+        };
+        let min_member_age_secs = vars.or("MIN_MEMBER_AGE_SECS", 0);
+        let min_attribution = vars.or("MIN_ATTRIBUTION", 0);
+        let dust_recipient = match vars.optional::<String>("DUST_RECIPIENT") {
+            None => DustRecipient::CarryOver,
+            Some(str) => match str.as_str() {
+                "carry-over" => DustRecipient::CarryOver,
+                "operator" => DustRecipient::Operator,
+                _ => {
+                    vars.invalid("DUST_RECIPIENT", format!("invalid: {}", str));
+                    DustRecipient::CarryOver
+                }
+            },
+        };
+        let winner_strategy = match vars.optional::<String>("WINNER_STRATEGY") {
+            None => WinnerStrategy::MaxDifficulty,
+            Some(str) => match str.as_str() {
+                "max-difficulty" => WinnerStrategy::MaxDifficulty,
+                "weighted" => WinnerStrategy::Weighted {
+                    min_difficulty: vars.required("WINNER_MIN_DIFFICULTY"),
+                },
+                "earliest" => WinnerStrategy::Earliest {
+                    min_difficulty: vars.required("WINNER_MIN_DIFFICULTY"),
+                },
+                _ => {
+                    vars.invalid("WINNER_STRATEGY", format!("invalid: {}", str));
+                    WinnerStrategy::MaxDifficulty
+                }
+            },
+        };
+        let min_bus_rewards = vars.or("MIN_BUS_REWARDS", 0);
+        let exclude_operator_stake = vars.flag("EXCLUDE_OPERATOR_STAKE");
+        let require_full_round = vars.flag("REQUIRE_FULL_ROUND");
+        let allowlist_only = vars.flag("ALLOWLIST_ONLY");
+        let access_refresh_secs = vars.or("ACCESS_REFRESH_SECS", DEFAULT_ACCESS_REFRESH_SECS);
+        if access_refresh_secs == 0 {
+            vars.invalid("ACCESS_REFRESH_SECS", "must be at least 1".to_string());
+        }
+        let access_refresh = std::time::Duration::from_secs(access_refresh_secs);
+        let dry_run = vars.flag("DRY_RUN");
+        let rewards_fallback = vars.flag("REWARDS_FALLBACK");
+        let challenge_history_size =
+            vars.or("CHALLENGE_HISTORY_SIZE", DEFAULT_CHALLENGE_HISTORY_SIZE);
+        let forensics_log_size = vars.or("FORENSICS_LOG_SIZE", DEFAULT_FORENSICS_LOG_SIZE);
+        let attest_arrivals = vars.flag("ATTEST_ARRIVALS");
+        let rewards_timeout = std::time::Duration::from_secs(
+            vars.or("REWARDS_TIMEOUT_SECS", DEFAULT_REWARDS_TIMEOUT_SECS),
+        );
+        let rewards_commitment = match vars.optional::<String>("REWARDS_COMMITMENT") {
+            None => CommitmentConfig::finalized(),
+            Some(str) => match str.as_str() {
+                "confirmed" => CommitmentConfig::confirmed(),
+                "finalized" => CommitmentConfig::finalized(),
+                _ => {
+                    vars.invalid("REWARDS_COMMITMENT", format!("invalid: {}", str));
+                    CommitmentConfig::finalized()
+                }
+            },
+        };
+        let submit_failure_threshold =
+            vars.or("SUBMIT_FAILURE_THRESHOLD", DEFAULT_SUBMIT_FAILURE_THRESHOLD);
+        let adaptive_cutoff_window = vars.or("ADAPTIVE_CUTOFF_WINDOW", 0);
+        let stake_fetch_concurrency = vars.or("STAKE_FETCH_CONCURRENCY", STAKE_FETCH_CONCURRENCY);
+        if stake_fetch_concurrency == 0 {
+            vars.invalid("STAKE_FETCH_CONCURRENCY", "must be at least 1".to_string());
+        }
+        let stake_refresh_interval =
+            Duration::from_secs(vars.or("STAKE_REFRESH_SECS", DEFAULT_STAKE_REFRESH_SECS));
+        // a zero interval would poll the boost totals in a busy loop
+        let stake_poll_secs = vars.or("STAKE_POLL_SECS", DEFAULT_STAKE_POLL_SECS);
+        if stake_poll_secs == 0 {
+            vars.invalid("STAKE_POLL_SECS", "must be at least 1".to_string());
+        }
+        let stake_poll_interval = Duration::from_secs(stake_poll_secs);
+        let stake_refresh_threshold_pct = vars.or(
+            "STAKE_REFRESH_THRESHOLD_PCT",
+            DEFAULT_STAKE_REFRESH_THRESHOLD_PCT,
+        );
+        let helius = HeliusConfig {
+            api_key: vars.required("HELIUS_API_KEY"),
+            webhook_id: vars.required("HELIUS_WEBHOOK_ID"),
+            webhook_url: vars.required("HELIUS_WEBHOOK_URL"),
+            auth_token: vars.required("HELIUS_AUTH_TOKEN"),
+        };
+        // attestations are only published if a bucket is configured
+        let s3 = vars.optional::<String>("S3_BUCKET").map(|bucket| S3Config {
+            bucket,
+            region: vars.required("AWS_REGION"),
+        });
+        let tls = match (
+            vars.optional::<String>("TLS_CERT"),
+            vars.optional::<String>("TLS_KEY"),
+        ) {
+            (Some(cert_path), Some(key_path)) => Some(TlsConfig {
+                cert_path,
+                key_path,
+            }),
+            (None, None) => None,
+            _ => {
+                vars.invalid("TLS_CERT", "and TLS_KEY must be set together".to_string());
+                None
+            }
+        };
+        // cross-origin requests are denied unless allowed
+        let allowed_origins = if vars.flag("DEV_CORS") {
+            AllowedOrigins::Any
+        } else {
+            let origins = vars
+                .optional::<String>("ALLOWED_ORIGINS")
+                .map(|str| match parse_allowed_origins(str.as_str()) {
+                    Ok(origins) => origins,
+                    Err(err) => {
+                        vars.invalid("ALLOWED_ORIGINS", err);
+                        vec![]
+                    }
+                })
+                .unwrap_or_default();
+            AllowedOrigins::List(origins)
+        };
+        // BIND_ADDR takes precedence over PORT
+        let bind_addr = match vars.optional::<String>("BIND_ADDR") {
+            Some(bind_addr) => bind_addr,
+            None => format!("0.0.0.0:{}", vars.or("PORT", PORT)),
+        };
+        let log_format = match vars.optional::<String>("LOG_FORMAT") {
+            None => LogFormat::Text,
+            Some(str) => match str.as_str() {
+                "text" | "" => LogFormat::Text,
+                "json" => LogFormat::Json,
+                _ => {
+                    vars.invalid("LOG_FORMAT", format!("invalid: {}", str));
+                    LogFormat::Text
+                }
+            },
+        };
+        // defaults to the number of cpus
+        let validation_workers = vars.or(
+            "VALIDATION_WORKERS",
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+        );
+        if validation_workers == 0 {
+            vars.invalid("VALIDATION_WORKERS", "must be at least 1".to_string());
+        }
+        let score_curve = match vars.optional::<String>("SCORE_CURVE") {
+            None => Curve::Exponential,
+            Some(str) => match str.as_str() {
+                "exponential" | "" => Curve::Exponential,
+                "linear" => Curve::Linear,
+                "capped" => {
+                    Curve::CappedExponential(vars.or("SCORE_CAP_DIFFICULTY", MAX_DIFFICULTY))
+                }
+                _ => {
+                    vars.invalid("SCORE_CURVE", format!("invalid: {}", str));
+                    Curve::Exponential
+                }
+            },
+        };
+        let contribute_rate_per_sec =
+            vars.or("CONTRIBUTE_RATE_PER_SEC", DEFAULT_CONTRIBUTE_RATE_PER_SEC);
+        if contribute_rate_per_sec.is_nan() || contribute_rate_per_sec <= 0.0 {
+            vars.invalid(
+                "CONTRIBUTE_RATE_PER_SEC",
+                format!("must be greater than 0: {}", contribute_rate_per_sec),
+            );
+        }
+        let jail_threshold = vars.or("JAIL_THRESHOLD", DEFAULT_JAIL_THRESHOLD);
+        let jail_window =
+            Duration::from_secs(vars.or("JAIL_WINDOW_SECS", DEFAULT_JAIL_WINDOW_SECS));
+        let jail_cooldown =
+            Duration::from_secs(vars.or("JAIL_COOLDOWN_SECS", DEFAULT_JAIL_COOLDOWN_SECS));
+        // below the min, the transaction fee the operator pays isn't worth the restake
+        let auto_compound_threshold = vars.optional::<u64>("AUTO_COMPOUND_THRESHOLD");
+        if auto_compound_threshold.is_some_and(|threshold| threshold < MIN_AUTO_COMPOUND) {
+            vars.invalid(
+                "AUTO_COMPOUND_THRESHOLD",
+                format!("must be at least {}", MIN_AUTO_COMPOUND),
+            );
+        }
+        let admin_token = vars
+            .optional::<String>("ADMIN_TOKEN")
+            .filter(|token| !token.is_empty());
+        // the config file overrides the environment for the reloadable config
+        let overlay = overlay(env, &file);
+        let mut reloadable_vars = Vars::new(&overlay);
+        let reloadable = ReloadableConfig::read(&mut reloadable_vars);
+        vars.errors.extend(reloadable_vars.errors);
+        vars.finish(Config {
+            signer,
+            pool_launch_authority,
+            rpc_urls,
+            rpc_policy,
+            db_url,
+            db_read_url,
+            boosts,
+            attribution_epoch,
+            stake_commit_epoch,
+            contributions_capacity,
+            max_ws_connections,
+            min_cutoff_margin,
+            late_grace_secs,
+            config_file,
+            reloadable,
+            max_member_score_pct,
+            unstaked_recipient,
+            min_member_age_secs,
+            min_attribution,
+            dust_recipient,
+            winner_strategy,
+            min_bus_rewards,
+            exclude_operator_stake,
+            require_full_round,
+            allowlist_only,
+            access_refresh,
+            dry_run,
+            rewards_fallback,
+            challenge_history_size,
+            forensics_log_size,
+            attest_arrivals,
+            rewards_timeout,
+            rewards_commitment,
+            submit_failure_threshold,
+            adaptive_cutoff_window,
+            stake_fetch_concurrency,
+            stake_refresh_interval,
+            stake_poll_interval,
+            stake_refresh_threshold_pct,
+            helius,
+            s3,
+            tls,
+            allowed_origins,
+            bind_addr,
+            log_format,
+            validation_workers,
+            score_curve,
+            contribute_rate_per_sec,
+            jail_threshold,
+            jail_window,
+            jail_cooldown,
+            auto_compound_threshold,
+            admin_token,
+        })
+    }
+}
+
+/// Where the pool authority signs from.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SignerConfig {
+    /// A keypair file, read into memory (KEYPAIR_PATH).
+    Keypair { path: String },
+
+    /// A signing service that holds the keypair (REMOTE_SIGNER_URL),
+    /// so the secret key never enters the server.
+    Remote {
+        url: String,
+        pubkey: Pubkey,
+        auth_token: Option<String>,
+        /// How long to wait on connecting to the signing service.
+        connect_timeout: std::time::Duration,
+        /// How long to wait on a signature, connecting included.
+        timeout: std::time::Duration,
+        /// Whether or not contributions are answered with receipts,
+        /// each of which costs a round trip to the signing service.
+        receipts: bool,
+    },
+}
+
+/// The Helius webhook that rewards and share account events are delivered by.
+#[derive(Debug, PartialEq, Eq)]
+pub struct HeliusConfig {
+    pub api_key: String,
+    pub webhook_id: String,
+    /// The /webhook path that the server exposes to Helius.
+    pub webhook_url: String,
+    /// The auth header Helius sends with each event.
+    pub auth_token: String,
+}
+
+/// The s3 bucket attestation blocks are published to.
+#[derive(Debug, PartialEq, Eq)]
+pub struct S3Config {
+    pub bucket: String,
+    /// The region of the bucket, used for building object urls.
+    pub region: String,
+}
+
+/// The pem files tls is terminated with.
+#[derive(Debug, PartialEq, Eq)]
+pub struct TlsConfig {
+    pub cert_path: String,
+    pub key_path: String,
+}
+
+/// How logs are formatted, either way filtered by RUST_LOG.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable text.
+    Text,
+    /// One json object per line, for log aggregators.
+    Json,
+}
+
+impl SignerConfig {
+    /// Whether or not accepted contributions are answered with a signed receipt.
+    pub fn receipts(&self) -> bool {
+        match self {
+            SignerConfig::Keypair { .. } => true,
+            SignerConfig::Remote { receipts, .. } => *receipts,
+        }
+    }
+}
+
+/// The operator config that can be changed without dropping connections,
+/// re-read on SIGHUP.
+/// Each use takes a snapshot, so that a round is settled against a single config,
+/// see [`crate::operator::Operator::config`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReloadableConfig {
+    /// The operator commission in % percentage.
+    /// Applied to the miner and staker rewards.
+    pub operator_commission: u64,
+
+    /// The optional operator commission schedule as (min total score, commission in % percentage).
+    /// Sorted ascending by min total score. Overrides the flat commission when set.
+    pub operator_commission_tiers: Option<Vec<(u64, u64)>>,
+
+    /// The staker commission in % percentage.
+    /// The rest is given to miners to incentize participation.
+    pub staker_commission: u64,
+
+    /// The staker commission in % percentage of specific boosts, keyed by mint.
+    /// Overrides the staker commission for those boosts.
+    pub staker_commission_overrides: HashMap<Pubkey, u64>,
+
+    /// The min score (per the score curve) a contribution must have to be accepted,
+    /// independent of the on-chain min difficulty.
+    pub min_score: u64,
+
+    /// The compute unit limit for submits.
+    pub submit_cu_limit: u32,
+
+    /// The compute unit price (in micro-lamports) for submits.
+    /// The floor when dynamic pricing is enabled.
+    pub submit_cu_price: u64,
+
+    /// The max compute unit price for submits.
+    /// If set, the price is scaled up from recent priority fees to at most this value.
+    pub submit_cu_price_max: Option<u64>,
+}
+
+impl ReloadableConfig {
+    /// Re-reads the reloadable config from the environment and the config file, if any.
+    pub fn reload(config_file: Option<&str>) -> Result<Self, Error> {
+        let env: ConfigVars = std::env::vars().collect();
+        let file = read_config_file(config_file)?;
+        Self::load(&overlay(&env, &file))
+    }
+
+    fn load(vars: &ConfigVars) -> Result<Self, Error> {
+        let mut vars = Vars::new(vars);
+        let config = Self::read(&mut vars);
+        vars.finish(config)
+    }
+
+    fn read(vars: &mut Vars) -> Self {
+        let operator_commission = vars.percentage("OPERATOR_COMMISSION");
+        let staker_commission = vars.percentage("STAKER_COMMISSION");
+        if operator_commission.saturating_add(staker_commission) > 100 {
+            vars.invalid(
+                "OPERATOR_COMMISSION",
+                format!(
+                    "plus the staker commission exceeds 100: {}",
+                    operator_commission
+                ),
+            );
+        }
+        let staker_commission_overrides = vars
+            .optional::<String>("STAKER_COMMISSION_OVERRIDES")
+            .and_then(|str| match parse_commission_overrides(str.as_str()) {
+                Ok(overrides) => Some(overrides),
+                Err(err) => {
+                    vars.invalid("STAKER_COMMISSION_OVERRIDES", err);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        for (mint, commission) in staker_commission_overrides.iter() {
+            if operator_commission.saturating_add(*commission) > 100 {
+                vars.invalid(
+                    "STAKER_COMMISSION_OVERRIDES",
+                    format!(
+                        "plus the operator commission exceeds 100: {}:{}",
+                        mint, commission
+                    ),
+                );
+            }
+        }
+        // every boost's staker commission must leave room for the operator commission
+        let max_staker_commission = staker_commission_overrides
+            .values()
+            .copied()
+            .fold(staker_commission, u64::max);
+        let operator_commission_tiers = vars
+            .optional::<String>("OPERATOR_COMMISSION_TIERS")
+            .and_then(|str| match parse_commission_tiers(str.as_str()) {
+                Ok(tiers) => Some(tiers),
+                Err(err) => {
+                    vars.invalid("OPERATOR_COMMISSION_TIERS", err);
+                    None
+                }
+            });
+        if let Some(tiers) = &operator_commission_tiers {
+            if let Err(err) = validate_commission_tiers(tiers.as_slice(), max_staker_commission) {
+                vars.invalid("OPERATOR_COMMISSION_TIERS", err);
+            }
+        }
+        let submit_cu_price = vars.or("SUBMIT_CU_PRICE", SUBMIT_CU_PRICE);
+        let submit_cu_price_max = vars.optional::<u64>("SUBMIT_CU_PRICE_MAX");
+        if submit_cu_price_max.is_some_and(|max| max < submit_cu_price) {
+            vars.invalid(
+                "SUBMIT_CU_PRICE_MAX",
+                format!("must be at least SUBMIT_CU_PRICE: {}", submit_cu_price),
+            );
+        }
+        ReloadableConfig {
+            operator_commission,
+            operator_commission_tiers,
+            staker_commission,
+            staker_commission_overrides,
+            min_score: vars.or("MIN_SCORE", 0),
+            submit_cu_limit: vars.or("SUBMIT_CU_LIMIT", SUBMIT_CU_LIMIT),
+            submit_cu_price,
+            submit_cu_price_max,
+        }
+    }
+
+    /// The staker commission of the boost, the override if any, otherwise the staker commission.
+    pub fn staker_commission_for(&self, mint: &Pubkey) -> u64 {
+        self.staker_commission_overrides
+            .get(mint)
+            .copied()
+            .unwrap_or(self.staker_commission)
+    }
+
+    /// The operator commission for the total score of a round.
+    /// Selects the tier with the largest min total score not exceeding the total score,
+    /// falling back to the flat commission.
+    pub fn effective_commission(&self, total_score: u64) -> u64 {
+        self.operator_commission_tiers
+            .as_ref()
+            .and_then(|tiers| {
+                tiers
+                    .iter()
+                    .rev()
+                    .find(|(min_total_score, _)| total_score >= *min_total_score)
+                    .map(|(_, commission)| *commission)
+            })
+            .unwrap_or(self.operator_commission)
+    }
+}
+
+/// Reads env vars by name, collecting every missing or invalid var
+/// rather than stopping at the first.
+/// Values of missing or invalid vars are defaulted, and discarded by [`Vars::finish`].
+struct Vars<'a> {
+    vars: &'a ConfigVars,
+    errors: Vec<String>,
+}
+
+impl<'a> Vars<'a> {
+    fn new(vars: &'a ConfigVars) -> Self {
+        Self {
+            vars,
+            errors: vec![],
+        }
+    }
+
+    fn required<T: FromStr + Default>(&mut self, name: &str) -> T {
+        let vars = self.vars;
+        match vars.get(name) {
+            Some(value) => self.parse(name, value).unwrap_or_default(),
+            None => {
+                self.errors.push(format!("{} is not set", name));
+                T::default()
+            }
+        }
+    }
+
+    fn optional<T: FromStr>(&mut self, name: &str) -> Option<T> {
+        let vars = self.vars;
+        vars.get(name).and_then(|value| self.parse(name, value))
+    }
+
+    fn or<T: FromStr>(&mut self, name: &str, default: T) -> T {
+        self.optional(name).unwrap_or(default)
+    }
+
+    /// Set to 1 to enable, or 0 (or empty) to disable.
+    fn flag(&mut self, name: &str) -> bool {
+        let vars = self.vars;
+        match vars.get(name).map(String::as_str) {
+            Some("1") => true,
+            None | Some("") | Some("0") => false,
+            Some(value) => {
+                self.invalid(name, format!("must be 0 or 1: {}", value));
+                false
+            }
+        }
+    }
+
+    /// A required epoch (in minutes), which can't be zero.
+    fn epoch(&mut self, name: &str) -> u64 {
+        let epoch = self.required(name);
+        if epoch == 0 && self.vars.contains_key(name) {
+            self.invalid(name, "must be at least 1".to_string());
+        }
+        epoch
+    }
+
+    /// A required percentage, at most 100.
+    fn percentage(&mut self, name: &str) -> u64 {
+        let pct = self.required(name);
+        if pct > 100 {
+            self.invalid(name, format!("must be at most 100: {}", pct));
+        }
+        pct
+    }
+
+    fn parse<T: FromStr>(&mut self, name: &str, value: &str) -> Option<T> {
+        match value.parse() {
+            Ok(value) => Some(value),
+            Err(_) => {
+                self.invalid(name, format!("invalid: {}", value));
+                None
+            }
+        }
+    }
+
+    fn invalid(&mut self, name: &str, reason: String) {
+        self.errors.push(format!("{} {}", name, reason));
+    }
+
+    fn finish<T>(self, value: T) -> Result<T, Error> {
+        if self.errors.is_empty() {
+            Ok(value)
+        } else {
+            Err(Error::Config(self.errors))
+        }
+    }
+}
+
+/// parses tiers formatted as "min_total_score:commission,..."
+fn parse_commission_tiers(str: &str) -> Result<Vec<(u64, u64)>, String> {
+    str.split(',')
+        .map(|tier| {
+            let (min_total_score, commission) = tier
+                .trim()
+                .split_once(':')
+                .ok_or(format!("invalid tier: {}", tier))?;
+            match (min_total_score.parse(), commission.parse()) {
+                (Ok(min_total_score), Ok(commission)) => Ok((min_total_score, commission)),
+                _ => Err(format!("invalid tier: {}", tier)),
+            }
+        })
+        .collect()
+}
+
+/// parses overrides formatted as "mint:commission,..."
+fn parse_commission_overrides(str: &str) -> Result<HashMap<Pubkey, u64>, String> {
+    str.split(',')
+        .map(|entry| {
+            let (mint, commission) = entry
+                .trim()
+                .split_once(':')
+                .ok_or(format!("invalid override: {}", entry))?;
+            match (Pubkey::from_str(mint), commission.parse::<u64>()) {
+                (Ok(mint), Ok(commission)) if commission <= 100 => Ok((mint, commission)),
+                _ => Err(format!("invalid override: {}", entry)),
+            }
+        })
+        .collect()
+}
+
+fn validate_commission_tiers(tiers: &[(u64, u64)], staker_commission: u64) -> Result<(), String> {
+    if tiers.is_empty() {
+        return Err("must not be empty".to_string());
+    }
+    if !tiers.windows(2).all(|w| w[0].0 < w[1].0) {
+        return Err("must be sorted by min total score".to_string());
+    }
+    for (_, commission) in tiers.iter() {
+        if commission.saturating_add(staker_commission) > 100 {
+            return Err(format!("invalid tier commission: {}", commission));
+        }
+    }
+    Ok(())
+}
+
+/// The environment with the vars of the config file taking precedence.
+fn overlay(env: &ConfigVars, file: &ConfigVars) -> ConfigVars {
+    let mut vars = env.clone();
+    vars.extend(file.iter().map(|(k, v)| (k.clone(), v.clone())));
+    vars
+}
+
+/// Reads the env vars of the config file, if any.
+fn read_config_file(path: Option<&str>) -> Result<ConfigVars, Error> {
+    match path {
+        Some(path) => Ok(parse_config_file(std::fs::read_to_string(path)?.as_str())),
+        None => Ok(HashMap::new()),
+    }
+}
+
+/// Parses lines formatted as KEY="value", in the same format as the .env.example,
+/// skipping blank lines, comments, and empty values so that they fall back to the environment.
+fn parse_config_file(contents: &str) -> ConfigVars {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let (key, value) = line.split_once('=')?;
+            let value = value.split(" //").next().unwrap_or_default().trim();
+            let value = value.trim_matches('"');
+            if value.is_empty() {
+                return None;
+            }
+            Some((key.trim().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> ConfigVars {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn required() -> ConfigVars {
+        vars(&[
+            ("KEYPAIR_PATH", "/etc/secrets/ore-pool-authority.json"),
+            ("RPC_URL", "https://api.mainnet-beta.solana.com"),
+            ("DB_URL", "postgres://localhost/ore"),
+            ("ATTR_EPOCH", "60"),
+            ("STAKE_EPOCH", "60"),
+            ("OPERATOR_COMMISSION", "5"),
+            ("STAKER_COMMISSION", "90"),
+            ("HELIUS_API_KEY", "helius-api-key"),
+            ("HELIUS_WEBHOOK_ID", "helius-webhook-id"),
+            (
+                "HELIUS_WEBHOOK_URL",
+                "https://pool.example.com/webhook/share-account",
+            ),
+            ("HELIUS_AUTH_TOKEN", "helius-auth-token"),
+        ])
+    }
+
+    fn errors(env: &ConfigVars) -> Vec<String> {
+        match Config::load(env) {
+            Err(Error::Config(errors)) => errors,
+            other => panic!("expected config errors: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn loads_required_with_defaults() {
+        let config = Config::load(&required()).unwrap();
+        assert_eq!(config.attribution_epoch, 60);
+        assert_eq!(config.contributions_capacity, CONTRIBUTIONS_CAPACITY);
+        assert_eq!(config.max_ws_connections, MAX_WS_CONNECTIONS);
+        assert_eq!(config.stake_fetch_concurrency, STAKE_FETCH_CONCURRENCY);
+        assert_eq!(config.min_cutoff_margin, BUFFER_OPERATOR);
+        assert_eq!(config.winner_strategy, WinnerStrategy::MaxDifficulty);
+        assert_eq!(config.min_attribution, 0);
+        assert_eq!(config.dust_recipient, DustRecipient::CarryOver);
+        assert_eq!(config.reloadable.submit_cu_limit, SUBMIT_CU_LIMIT);
+        assert!(config.boosts.is_empty());
+        assert!(!config.dry_run);
+        assert_eq!(
+            config.rpc_urls,
+            vec!["https://api.mainnet-beta.solana.com".to_string()]
+        );
+        assert_eq!(config.rpc_policy, RpcPolicy::Primary);
+        assert_eq!(config.bind_addr, format!("0.0.0.0:{}", PORT));
+        assert_eq!(config.log_format, LogFormat::Text);
+        assert_eq!(config.score_curve, Curve::Exponential);
+        assert_eq!(config.allowed_origins, AllowedOrigins::List(vec![]));
+        assert!(config.s3.is_none());
+        assert!(config.tls.is_none());
+        assert!(config.auto_compound_threshold.is_none());
+        assert!(config.admin_token.is_none());
+    }
+
+    #[test]
+    fn remote_signer_takes_the_place_of_the_keypair() {
+        let mut env = required();
+        env.remove("KEYPAIR_PATH");
+        env.insert(
+            "REMOTE_SIGNER_URL".to_string(),
+            "https://signer.example.com/sign".to_string(),
+        );
+        assert_eq!(
+            errors(&env),
+            vec!["REMOTE_SIGNER_PUBKEY is not set".to_string()]
+        );
+        let pubkey = Pubkey::new_unique();
+        env.insert("REMOTE_SIGNER_PUBKEY".to_string(), pubkey.to_string());
+        let config = Config::load(&env).unwrap();
+        assert_eq!(
+            config.signer,
+            SignerConfig::Remote {
+                url: "https://signer.example.com/sign".to_string(),
+                pubkey,
+                auth_token: None,
+                connect_timeout: std::time::Duration::from_millis(
+                    DEFAULT_REMOTE_SIGNER_CONNECT_TIMEOUT_MS
+                ),
+                timeout: std::time::Duration::from_millis(DEFAULT_REMOTE_SIGNER_TIMEOUT_MS),
+                receipts: false,
+            }
+        );
+        assert!(!config.signer.receipts());
+        env.insert("REMOTE_SIGNER_TIMEOUT_MS".to_string(), "0".to_string());
+        assert_eq!(
+            errors(&env),
+            vec!["REMOTE_SIGNER_TIMEOUT_MS must be at least 1".to_string()]
+        );
+    }
+
+    #[test]
+    fn rewards_commitment_defaults_to_finalized() {
+        let mut env = required();
+        let config = Config::load(&env).unwrap();
+        assert_eq!(config.rewards_commitment, CommitmentConfig::finalized());
+        env.insert("REWARDS_COMMITMENT".to_string(), "confirmed".to_string());
+        let config = Config::load(&env).unwrap();
+        assert_eq!(config.rewards_commitment, CommitmentConfig::confirmed());
+        env.insert("REWARDS_COMMITMENT".to_string(), "processed".to_string());
+        assert_eq!(
+            errors(&env),
+            vec!["REWARDS_COMMITMENT invalid: processed".to_string()]
+        );
+    }
+
+    #[test]
+    fn rpc_urls_take_precedence() {
+        let mut env = required();
+        env.insert(
+            "RPC_URLS".to_string(),
+            "https://a.example.com, https://b.example.com".to_string(),
+        );
+        env.insert("RPC_POLICY".to_string(), "round-robin".to_string());
+        let config = Config::load(&env).unwrap();
+        assert_eq!(
+            config.rpc_urls,
+            vec![
+                "https://a.example.com".to_string(),
+                "https://b.example.com".to_string()
+            ]
+        );
+        assert_eq!(config.rpc_policy, RpcPolicy::RoundRobin);
+        // without RPC_URL
+        env.remove("RPC_URL");
+        env.insert(
+            "RPC_URLS".to_string(),
+            "https://a.example.com,localhost".to_string(),
+        );
+        env.insert("RPC_POLICY".to_string(), "random".to_string());
+        assert_eq!(
+            errors(&env),
+            vec![
+                "RPC_URLS must be an http url: localhost",
+                "RPC_POLICY invalid: random",
+            ]
+        );
+        env.insert("RPC_URLS".to_string(), ",".to_string());
+        env.remove("RPC_POLICY");
+        assert_eq!(errors(&env), vec!["RPC_URLS must list at least one url"]);
+    }
+
+    #[test]
+    fn reports_every_missing_and_invalid_var() {
+        let mut env = required();
+        env.remove("RPC_URL");
+        env.remove("ATTR_EPOCH");
+        env.insert("STAKE_EPOCH".to_string(), "0".to_string());
+        env.insert("BOOST_ONE".to_string(), "not-a-pubkey".to_string());
+        env.insert("CONTRIBUTIONS_CAPACITY".to_string(), "0".to_string());
+        env.insert("WINNER_STRATEGY".to_string(), "weighted".to_string());
+        assert_eq!(
+            errors(&env),
+            vec![
+                "RPC_URL is not set",
+                "BOOST_ONE invalid: not-a-pubkey",
+                "ATTR_EPOCH is not set",
+                "STAKE_EPOCH must be at least 1",
+                "CONTRIBUTIONS_CAPACITY must be at least 1",
+                "WINNER_MIN_DIFFICULTY is not set",
+            ]
+        );
+    }
+
+    #[test]
+    fn server_vars_are_validated_with_the_rest() {
+        let mut env = required();
+        env.insert("PORT".to_string(), "9000".to_string());
+        env.insert("SCORE_CURVE".to_string(), "capped".to_string());
+        env.insert("SCORE_CAP_DIFFICULTY".to_string(), "20".to_string());
+        env.insert("S3_BUCKET".to_string(), "attestations".to_string());
+        env.insert("AWS_REGION".to_string(), "us-east-1".to_string());
+        env.insert("DEV_CORS".to_string(), "1".to_string());
+        env.insert("ADMIN_TOKEN".to_string(), "".to_string());
+        let config = Config::load(&env).unwrap();
+        assert_eq!(config.bind_addr, "0.0.0.0:9000");
+        assert_eq!(config.score_curve, Curve::CappedExponential(20));
+        assert_eq!(
+            config.s3,
+            Some(S3Config {
+                bucket: "attestations".to_string(),
+                region: "us-east-1".to_string(),
+            })
+        );
+        assert_eq!(config.allowed_origins, AllowedOrigins::Any);
+        assert!(config.admin_token.is_none());
+        // BIND_ADDR takes precedence over PORT
+        env.insert("BIND_ADDR".to_string(), "127.0.0.1:8080".to_string());
+        let config = Config::load(&env).unwrap();
+        assert_eq!(config.bind_addr, "127.0.0.1:8080");
+        // reported together with the rest
+        env.remove("AWS_REGION");
+        env.remove("HELIUS_AUTH_TOKEN");
+        env.remove("DEV_CORS");
+        env.insert("TLS_CERT".to_string(), "cert.pem".to_string());
+        env.insert("ALLOWED_ORIGINS".to_string(), "ore.supply".to_string());
+        env.insert("LOG_FORMAT".to_string(), "yaml".to_string());
+        env.insert("SCORE_CURVE".to_string(), "quadratic".to_string());
+        env.insert("CONTRIBUTE_RATE_PER_SEC".to_string(), "0".to_string());
+        env.insert("STAKE_POLL_SECS".to_string(), "0".to_string());
+        env.insert("AUTO_COMPOUND_THRESHOLD".to_string(), "1".to_string());
+        assert_eq!(
+            errors(&env),
+            vec![
+                "STAKE_POLL_SECS must be at least 1".to_string(),
+                "HELIUS_AUTH_TOKEN is not set".to_string(),
+                "AWS_REGION is not set".to_string(),
+                "TLS_CERT and TLS_KEY must be set together".to_string(),
+                "ALLOWED_ORIGINS invalid: ore.supply".to_string(),
+                "LOG_FORMAT invalid: yaml".to_string(),
+                "SCORE_CURVE invalid: quadratic".to_string(),
+                "CONTRIBUTE_RATE_PER_SEC must be greater than 0: 0".to_string(),
+                format!(
+                    "AUTO_COMPOUND_THRESHOLD must be at least {}",
+                    MIN_AUTO_COMPOUND
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn flags_are_0_or_1() {
+        let mut env = required();
+        env.insert("ALLOWLIST_ONLY".to_string(), "1".to_string());
+        env.insert("DRY_RUN".to_string(), "".to_string());
+        env.insert("REWARDS_FALLBACK".to_string(), "0".to_string());
+        let config = Config::load(&env).unwrap();
+        assert!(config.allowlist_only);
+        assert!(!config.dry_run);
+        assert!(!config.rewards_fallback);
+        env.insert("ALLOWLIST_ONLY".to_string(), "true".to_string());
+        env.insert("ACCESS_REFRESH_SECS".to_string(), "0".to_string());
+        assert_eq!(
+            errors(&env),
+            vec![
+                "ALLOWLIST_ONLY must be 0 or 1: true",
+                "ACCESS_REFRESH_SECS must be at least 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_out_of_range_values() {
+        let mut env = required();
+        env.insert("RPC_URL".to_string(), "localhost:8899".to_string());
+        env.insert("MIN_CUTOFF_MARGIN".to_string(), "60".to_string());
+        env.insert("MAX_MEMBER_SCORE_PCT".to_string(), "0".to_string());
+        env.insert("SUBMIT_CU_PRICE_MAX".to_string(), "1".to_string());
+        assert_eq!(errors(&env).len(), 4);
+        let mut env = required();
+        env.insert("OPERATOR_COMMISSION".to_string(), "20".to_string());
+        assert_eq!(
+            errors(&env),
+            vec!["OPERATOR_COMMISSION plus the staker commission exceeds 100: 20"]
+        );
+    }
+
+    #[test]
+    fn boosts_with_attribution_epochs() {
+        let mint = Pubkey::new_unique();
+        let mut env = required();
+        env.insert("BOOST_TWO".to_string(), mint.to_string());
+        env.insert("ATTR_EPOCH_BOOST_TWO".to_string(), "10".to_string());
+        // ignored without its boost
+        env.insert("ATTR_EPOCH_BOOST_ONE".to_string(), "0".to_string());
+        let config = Config::load(&env).unwrap();
+        assert_eq!(config.boosts, vec![(mint, Some(10))]);
+    }
+
+    #[test]
+    fn validate_commission_tiers_sorted_and_in_range() {
+        let sorted = vec![(0, 5), (1_000, 4), (10_000, 3)];
+        assert!(validate_commission_tiers(sorted.as_slice(), 90).is_ok());
+        let unsorted = vec![(1_000, 4), (0, 5)];
+        assert!(validate_commission_tiers(unsorted.as_slice(), 90).is_err());
+        let out_of_range = vec![(0, 101)];
+        assert!(validate_commission_tiers(out_of_range.as_slice(), 0).is_err());
+    }
+
+    #[test]
+    fn reloadable_config_from_file() {
+        let file = parse_config_file(
+            r#"
+            # commissions
+            OPERATOR_COMMISSION="5"
+            STAKER_COMMISSION="90" // the percentage paid out to stakers
+            OPERATOR_COMMISSION_TIERS="0:5,1000:4"
+            MIN_SCORE=""
+            SUBMIT_CU_LIMIT=200000
+            "#,
+        );
+        assert_eq!(file["STAKER_COMMISSION"], "90");
+        assert!(!file.contains_key("MIN_SCORE"));
+        let env = vars(&[
+            ("STAKER_COMMISSION", "50"),
+            ("SUBMIT_CU_PRICE", "1000"),
+            ("SUBMIT_CU_PRICE_MAX", "5000"),
+            ("MIN_SCORE", "16"),
+        ]);
+        let mut vars = overlay(&env, &file);
+        let config = ReloadableConfig::load(&vars).unwrap();
+        assert_eq!(config.effective_commission(0), 5);
+        assert_eq!(config.effective_commission(1_000), 4);
+        // the file takes precedence
+        assert_eq!(config.staker_commission, 90);
+        // empty values in the file fall back to the environment
+        assert_eq!(config.min_score, 16);
+        assert_eq!(config.submit_cu_limit, 200_000);
+        assert_eq!(config.submit_cu_price_max, Some(5_000));
+        // invalid configs are rejected rather than swapped in
+        vars.insert("STAKER_COMMISSION".to_string(), "99".to_string());
+        assert!(ReloadableConfig::load(&vars).is_err());
+    }
+
+    #[test]
+    fn staker_commission_overrides_by_mint() {
+        let (mint, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let overrides = format!("{}:50, {}:80", mint, other);
+        let mut vars = vars(&[
+            ("OPERATOR_COMMISSION", "5"),
+            ("STAKER_COMMISSION", "90"),
+            ("STAKER_COMMISSION_OVERRIDES", overrides.as_str()),
+        ]);
+        let config = ReloadableConfig::load(&vars).unwrap();
+        assert_eq!(config.staker_commission_for(&mint), 50);
+        assert_eq!(config.staker_commission_for(&other), 80);
+        assert_eq!(config.staker_commission_for(&Pubkey::new_unique()), 90);
+        // overrides must leave room for the operator commission
+        let overrides = format!("{}:96", mint);
+        vars.insert("STAKER_COMMISSION_OVERRIDES".to_string(), overrides);
+        assert!(ReloadableConfig::load(&vars).is_err());
+        // and for every tier
+        let overrides = format!("{}:95", mint);
+        vars.insert("STAKER_COMMISSION_OVERRIDES".to_string(), overrides);
+        assert!(ReloadableConfig::load(&vars).is_ok());
+        vars.insert("OPERATOR_COMMISSION_TIERS".to_string(), "0:6".to_string());
+        assert!(ReloadableConfig::load(&vars).is_err());
+        // malformed overrides are rejected
+        let out_of_range = format!("{}:101", mint);
+        for overrides in ["not-a-mint:50", "50", out_of_range.as_str()] {
+            vars.insert(
+                "STAKER_COMMISSION_OVERRIDES".to_string(),
+                overrides.to_string(),
+            );
+            assert!(ReloadableConfig::load(&vars).is_err());
+        }
+    }
+}
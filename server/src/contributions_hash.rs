@@ -0,0 +1,90 @@
+use std::collections::BTreeMap;
+
+use ore_pool_types::Attestation;
+use sha3::{Digest, Sha3_256};
+use solana_sdk::pubkey::Pubkey;
+
+/// The attestation block of a round, kept sorted by member as contributions arrive
+/// so that closing the round doesn't sort every contribution.
+///
+/// The block starts with a header naming its version, see [`Attestation::header`],
+/// followed by one line per contribution. The contributions hash is the sha3 hash
+/// of the whole block, so anyone holding the published block can recompute it
+/// with [`hash_block`]. Since lines arrive out of order, the hash can't be kept running
+/// and is taken once, when the round closes, see [`ContributionsHash::finalize`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ContributionsHash {
+    /// The line of each member's contribution.
+    lines: BTreeMap<Pubkey, String>,
+}
+
+impl ContributionsHash {
+    /// Adds a member's line, replacing the line of a replaced contribution.
+    pub fn insert(&mut self, member: Pubkey, line: String) {
+        self.lines.insert(member, line);
+    }
+
+    /// The attestation block over the lines added so far.
+    pub fn block(&self) -> String {
+        let mut block = Attestation::header();
+        block.push('\n');
+        for line in self.lines.values() {
+            block.push_str(line);
+            block.push('\n');
+        }
+        block
+    }
+
+    /// The attestation block over the lines added so far and its contributions hash,
+    /// building the block only once.
+    pub fn finalize(&self) -> (String, [u8; 32]) {
+        let block = self.block();
+        let hash = hash_block(&block);
+        (block, hash)
+    }
+}
+
+/// The contributions hash of an attestation block.
+pub fn hash_block(block: &str) -> [u8; 32] {
+    let mut hash: [u8; 32] = [0; 32];
+    hash.copy_from_slice(&Sha3_256::digest(block.as_bytes())[..]);
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_is_sorted_by_member() {
+        let members: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let mut forward = ContributionsHash::default();
+        for (index, member) in members.iter().enumerate() {
+            forward.insert(*member, format!("{} {}", member, index));
+        }
+        let mut reverse = ContributionsHash::default();
+        for (index, member) in members.iter().enumerate().rev() {
+            reverse.insert(*member, format!("{} {}", member, index));
+        }
+        assert_eq!(forward.block(), reverse.block());
+        assert_eq!(
+            forward.finalize(),
+            (forward.block(), hash_block(&forward.block()))
+        );
+        // the block is versioned
+        assert!(forward
+            .block()
+            .starts_with(&format!("{}\n", Attestation::header())));
+    }
+
+    #[test]
+    fn replaced_line_is_dropped() {
+        let member = Pubkey::new_unique();
+        let mut hash = ContributionsHash::default();
+        hash.insert(member, "first".to_string());
+        let (_, first) = hash.finalize();
+        hash.insert(member, "second".to_string());
+        assert_ne!(hash.finalize().1, first);
+        assert_eq!(hash.block(), format!("{}\nsecond\n", Attestation::header()));
+    }
+}
@@ -1,25 +1,68 @@
-use actix_web::{web, HttpResponse, Responder};
+use std::{collections::HashMap, str::FromStr, sync::Arc};
+
+use actix_web::{
+    http::{header, StatusCode},
+    web, HttpRequest, HttpResponse, Responder,
+};
+use futures::StreamExt;
 use ore_pool_types::{
-    BalanceUpdate, ContributePayload, GetMemberPayload, MemberChallenge, PoolAddress,
-    RegisterPayload, RegisterStakerPayload, Staker, UpdateBalancePayload,
+    AutoCompoundPayload, BalanceHistory, BalanceHistoryQuery, BalanceUpdate, Challenge,
+    ContributePayload, ContributionReceipt, DailyEarnings, DailyEarningsQuery, DeregisterPayload,
+    EstimateQuery, GetMemberPayload, LeaderboardEntry, LeaderboardQuery, MemberChallenge,
+    NonceRange, PendingContribution, PoolAddress, PoolAddressQuery, RegisterPayload,
+    RegisterStakerPayload, Staker, UpdateBalancePayload,
 };
-use solana_sdk::{pubkey::Pubkey, signer::Signer};
+use solana_sdk::pubkey::Pubkey;
 
 use crate::{
-    aggregator::{Aggregator, BUFFER_CLIENT},
+    access::Access,
+    aggregator::{expected_difficulty, Aggregator, Forwarded, Inserted, BUFFER_CLIENT},
     database,
-    error::Error,
+    error::{Error, ErrorBody, ErrorCode},
+    jail::Jail,
+    metrics::{self, Rejection},
+    notify::Notifier,
     operator::Operator,
-    tx, webhook, Contribution,
+    rate_limit::RateLimiter,
+    request_id::RequestId,
+    signer::{self, OperatorSigner},
+    tx,
+    validation::{Validator, Verdict},
+    webhook, Contribution,
 };
 
+/// How long the stats handler waits on the aggregator read lock.
+const STATS_LOCK_TIMEOUT_MS: u64 = 500;
+
+/// The default number of entries returned by the leaderboard.
+const LEADERBOARD_DEFAULT_LIMIT: usize = 25;
+
+/// The default and max number of events returned per page of balance history.
+const HISTORY_DEFAULT_LIMIT: usize = 50;
+const HISTORY_MAX_LIMIT: usize = 500;
+
+/// The max number of days covered by a request for daily earnings.
+const DAILY_MAX_DAYS: i64 = 366;
+
+/// The max number of solutions accepted per batch contribute.
+const CONTRIBUTE_BATCH_MAX: usize = 100;
+
+/// How old (in seconds) a signed auto-compound payload may be.
+const AUTO_COMPOUND_PAYLOAD_MAX_AGE_SECS: i64 = 300;
+
 ////////////////////////////////////////////////////////////////////////////////////
 /// HTTP HANDLERS //////////////////////////////////////////////////////////////////
 ////////////////////////////////////////////////////////////////////////////////////
 pub async fn register(
     operator: web::Data<Operator>,
+    access: web::Data<Access>,
     payload: web::Json<RegisterPayload>,
 ) -> impl Responder {
+    if let Err(err) = access.check(&payload.authority) {
+        log::error!("access denied: {:?}", payload.authority);
+        let http_response: HttpResponse = err.into();
+        return http_response;
+    }
     let operator = operator.as_ref();
     let res = register_new_member(operator, payload.into_inner()).await;
     match res {
@@ -32,6 +75,39 @@ pub async fn register(
     }
 }
 
+pub async fn deregister(
+    operator: web::Data<Operator>,
+    payload: web::Json<DeregisterPayload>,
+) -> impl Responder {
+    let operator = operator.as_ref();
+    let res = deregister_member(operator, payload.into_inner()).await;
+    match res {
+        Ok(db_member) => HttpResponse::Ok().json(&db_member),
+        Err(err) => {
+            log::error!("{:?}", err);
+            let http_response: HttpResponse = err.into();
+            http_response
+        }
+    }
+}
+
+/// Opts the member in or out of restaking their rewards each attribution epoch.
+pub async fn auto_compound(
+    operator: web::Data<Operator>,
+    payload: web::Json<AutoCompoundPayload>,
+) -> impl Responder {
+    let operator = operator.as_ref();
+    let res = set_auto_compound(operator, payload.into_inner()).await;
+    match res {
+        Ok(db_member) => HttpResponse::Ok().json(&db_member),
+        Err(err) => {
+            log::error!("{:?}", err);
+            let http_response: HttpResponse = err.into();
+            http_response
+        }
+    }
+}
+
 pub async fn register_staker(
     operator: web::Data<Operator>,
     aggregator: web::Data<tokio::sync::RwLock<Aggregator>>,
@@ -55,13 +131,33 @@ pub async fn register_staker(
     }
 }
 
-pub async fn pool_address(operator: web::Data<Operator>) -> impl Responder {
+pub async fn pool_address(
+    operator: web::Data<Operator>,
+    query: web::Query<PoolAddressQuery>,
+) -> impl Responder {
     let operator = operator.as_ref();
-    let (pool_pda, bump) = ore_pool_api::state::pool_pda(operator.keypair.pubkey());
-    HttpResponse::Ok().json(&PoolAddress {
+    let (pool_pda, bump) = (operator.pool, operator.pool_bump);
+    let mut pool_address = PoolAddress {
         address: pool_pda,
         bump,
-    })
+        exists: None,
+        total_members: None,
+    };
+    // optionally verify the pool is initialized on-chain
+    if query.verify.unwrap_or(false) {
+        match operator.get_pool_cached().await {
+            Ok(pool) => {
+                pool_address.exists = Some(pool.is_some());
+                pool_address.total_members = pool.map(|p| p.total_members);
+            }
+            Err(err) => {
+                log::error!("{:?}", err);
+                let http_response: HttpResponse = err.into();
+                return http_response;
+            }
+        }
+    }
+    HttpResponse::Ok().json(&pool_address)
 }
 
 pub async fn update_balance(
@@ -72,7 +168,8 @@ pub async fn update_balance(
         Ok(balance_update) => HttpResponse::Ok().json(balance_update),
         Err(err) => {
             log::error!("{:?}", err);
-            HttpResponse::InternalServerError().body(err.to_string())
+            err.code()
+                .response(StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
         }
     }
 }
@@ -82,86 +179,959 @@ pub async fn member(
     path: web::Path<GetMemberPayload>,
 ) -> impl Responder {
     match operator
-        .get_member_db(path.into_inner().authority.as_str())
+        .get_member_db_read(path.into_inner().authority.as_str())
         .await
     {
         Ok(member) => HttpResponse::Ok().json(&member),
         Err(err) => {
             log::error!("{:?}", err);
-            HttpResponse::NotFound().finish()
+            ErrorCode::MemberNotFound.response(StatusCode::NOT_FOUND, err.to_string())
         }
     }
 }
 
+/// Pages through the member's per-round earnings, newest first.
+pub async fn member_history(
+    operator: web::Data<Operator>,
+    path: web::Path<GetMemberPayload>,
+    query: web::Query<BalanceHistoryQuery>,
+) -> impl Responder {
+    let limit = query
+        .limit
+        .unwrap_or(HISTORY_DEFAULT_LIMIT)
+        .min(HISTORY_MAX_LIMIT);
+    match read_member_history(operator.as_ref(), path.into_inner(), query.cursor, limit).await {
+        Ok(history) => HttpResponse::Ok().json(&history),
+        Err(err) => {
+            log::error!("{:?}", err);
+            let http_response: HttpResponse = err.into();
+            http_response
+        }
+    }
+}
+
+/// Sums the member's earnings per calendar day in the requested timezone, oldest first.
+/// Days without earnings are left out. Responds with 400 if the range is invalid
+/// or longer than the max, or if the timezone is unknown.
+pub async fn member_daily(
+    operator: web::Data<Operator>,
+    path: web::Path<GetMemberPayload>,
+    query: web::Query<DailyEarningsQuery>,
+) -> impl Responder {
+    match read_member_daily(operator.as_ref(), path.into_inner(), query.into_inner()).await {
+        Ok(daily) => HttpResponse::Ok().json(&daily),
+        Err(err) => {
+            log::error!("{:?}", err);
+            let http_response: HttpResponse = err.into();
+            http_response
+        }
+    }
+}
+
+/// Reports whether the member's contribution to the current challenge was accepted.
+/// Reads the in-memory contributions only, nothing on-chain or in the db.
+pub async fn member_pending(
+    aggregator: web::Data<tokio::sync::RwLock<Aggregator>>,
+    path: web::Path<GetMemberPayload>,
+) -> impl Responder {
+    let member = match Pubkey::from_str(path.into_inner().authority.as_str()) {
+        Ok(member) => member,
+        Err(err) => {
+            log::error!("{:?}", err);
+            return ErrorCode::InvalidRequest.response(StatusCode::BAD_REQUEST, err.to_string());
+        }
+    };
+    let pending = {
+        let aggregator = aggregator.read().await;
+        match aggregator.get_contribution(&member) {
+            Some(contribution) => PendingContribution {
+                contributed: true,
+                score: Some(contribution.score),
+                winner: Some(aggregator.is_winner(&contribution.solution)),
+            },
+            None => PendingContribution {
+                contributed: false,
+                score: None,
+                winner: None,
+            },
+        }
+    };
+    HttpResponse::Ok().json(&pending)
+}
+
+/// Reports the nonce range the member is assigned for the current challenge,
+/// the same range its contributions are validated against.
+/// The range only changes when the number of members does, on reset.
+/// Responds with 404 if the authority isn't registered.
+pub async fn member_nonce_range(
+    operator: web::Data<Operator>,
+    aggregator: web::Data<tokio::sync::RwLock<Aggregator>>,
+    path: web::Path<GetMemberPayload>,
+) -> impl Responder {
+    // read from the primary like the validation,
+    // so that a member can fetch its range right after registering
+    let member = match operator
+        .get_member_db(path.into_inner().authority.as_str())
+        .await
+    {
+        Ok(member) => member,
+        Err(err) => {
+            log::error!("{:?}", err);
+            return ErrorCode::MemberNotFound.response(StatusCode::NOT_FOUND, err.to_string());
+        }
+    };
+    if !member.is_active {
+        let http_response: HttpResponse = Error::MemberInactive.into();
+        return http_response;
+    }
+    let (last_hash_at, num_members) = {
+        let aggregator = aggregator.read().await;
+        (aggregator.challenge.lash_hash_at, aggregator.num_members)
+    };
+    let (start, end) = assigned_nonce_range(member.id as u64, num_members);
+    HttpResponse::Ok().json(NonceRange {
+        last_hash_at,
+        num_members,
+        start,
+        end,
+    })
+}
+
 // TODO: consider the need for auth on this get/read?
-pub async fn challenge(aggregator: web::Data<tokio::sync::RwLock<Aggregator>>) -> impl Responder {
+pub async fn challenge(
+    operator: web::Data<Operator>,
+    aggregator: web::Data<tokio::sync::RwLock<Aggregator>>,
+) -> impl Responder {
     // acquire read on aggregator for challenge
-    let (challenge, last_num_members) = {
+    let (challenge, last_num_members, total_score, paused) = {
         let aggregator = aggregator.read().await;
-        (aggregator.challenge, aggregator.num_members)
+        (
+            aggregator.challenge,
+            aggregator.num_members,
+            aggregator.total_score,
+            aggregator.breaker.is_open(),
+        )
     };
     // build member challenge
+    // commissions are read per request so they track the operator's config
+    let config = operator.config();
     let member_challenge = MemberChallenge {
         challenge,
         buffer: BUFFER_CLIENT,
         num_total_members: last_num_members,
+        operator_commission: config.effective_commission(total_score),
+        staker_commission: config.staker_commission,
+        paused,
     };
     HttpResponse::Ok().json(&member_challenge)
 }
 
+/// Caps the open websocket connections across the challenge and balance streams.
+/// Each connection holds a permit until it closes.
+pub struct WsConnections(Arc<tokio::sync::Semaphore>);
+
+impl WsConnections {
+    pub fn new(max: usize) -> Self {
+        Self(Arc::new(tokio::sync::Semaphore::new(max)))
+    }
+
+    /// A permit for one more connection, or a 503 response if at the cap.
+    fn acquire(&self) -> Result<tokio::sync::OwnedSemaphorePermit, HttpResponse> {
+        self.0.clone().try_acquire_owned().map_err(|_| {
+            ErrorCode::ServerBusy.response(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "too many websocket connections",
+            )
+        })
+    }
+}
+
+/// Pushes each new challenge to the client over a websocket,
+/// starting with the current challenge.
+pub async fn challenge_ws(
+    aggregator: web::Data<tokio::sync::RwLock<Aggregator>>,
+    connections: web::Data<WsConnections>,
+    req: HttpRequest,
+    body: web::Payload,
+) -> Result<HttpResponse, actix_web::Error> {
+    let permit = match connections.acquire() {
+        Ok(permit) => permit,
+        Err(response) => return Ok(response),
+    };
+    let (response, mut session, mut msg_stream) = actix_ws::handle(&req, body)?;
+    // subscribe under the same lock as the read
+    // so no challenge is missed in between
+    let (challenge, mut challenge_rx) = {
+        let aggregator = aggregator.read().await;
+        (aggregator.challenge, aggregator.challenge_tx.subscribe())
+    };
+    actix_web::rt::spawn(async move {
+        let _permit = permit;
+        if send_challenge(&mut session, &challenge).await.is_err() {
+            return;
+        }
+        loop {
+            tokio::select! {
+                next = challenge_rx.recv() => match next {
+                    Ok(challenge) => {
+                        if send_challenge(&mut session, &challenge).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                        log::error!("challenge subscriber lagged by {}", skipped);
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                },
+                msg = msg_stream.next() => match msg {
+                    Some(Ok(actix_ws::Message::Ping(bytes))) => {
+                        if session.pong(&bytes).await.is_err() {
+                            break;
+                        }
+                    }
+                    Some(Ok(actix_ws::Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                },
+            }
+        }
+        // the subscription is released when the receiver drops
+        let _ = session.close(None).await;
+    });
+    Ok(response)
+}
+
+/// Pushes the member's balance updates to the client over a websocket,
+/// as each round is attributed. See [`ore_pool_types::BalanceNotification`].
+pub async fn member_balance_ws(
+    operator: web::Data<Operator>,
+    notifier: web::Data<Notifier>,
+    connections: web::Data<WsConnections>,
+    path: web::Path<GetMemberPayload>,
+    req: HttpRequest,
+    body: web::Payload,
+) -> Result<HttpResponse, actix_web::Error> {
+    let member_authority = match Pubkey::from_str(path.into_inner().authority.as_str()) {
+        Ok(member_authority) => member_authority,
+        Err(err) => {
+            log::error!("{:?}", err);
+            return Ok(ErrorCode::InvalidRequest.response(StatusCode::BAD_REQUEST, err.to_string()));
+        }
+    };
+    let pool_pda = operator.pool;
+    let (member_pda, _) = ore_pool_api::state::member_pda(member_authority, pool_pda);
+    let permit = match connections.acquire() {
+        Ok(permit) => permit,
+        Err(response) => return Ok(response),
+    };
+    let (response, mut session, mut msg_stream) = actix_ws::handle(&req, body)?;
+    let address = member_pda.to_string();
+    let mut balance_rx = notifier.subscribe(address.clone());
+    actix_web::rt::spawn(async move {
+        let _permit = permit;
+        loop {
+            tokio::select! {
+                next = balance_rx.recv() => match next {
+                    Ok(notification) => {
+                        if send_json(&mut session, &notification).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                        log::error!("balance subscriber lagged by {}", skipped);
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                },
+                msg = msg_stream.next() => match msg {
+                    Some(Ok(actix_ws::Message::Ping(bytes))) => {
+                        if session.pong(&bytes).await.is_err() {
+                            break;
+                        }
+                    }
+                    Some(Ok(actix_ws::Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                },
+            }
+        }
+        // release the subscription, dropping the member once no one else is subscribed
+        drop(balance_rx);
+        notifier.unsubscribe(&address);
+        let _ = session.close(None).await;
+    });
+    Ok(response)
+}
+
+async fn send_challenge(
+    session: &mut actix_ws::Session,
+    challenge: &ore_pool_types::Challenge,
+) -> Result<(), actix_ws::Closed> {
+    send_json(session, challenge).await
+}
+
+async fn send_json(
+    session: &mut actix_ws::Session,
+    message: &impl serde::Serialize,
+) -> Result<(), actix_ws::Closed> {
+    let message = match serde_json::to_string(message) {
+        Ok(message) => message,
+        Err(err) => {
+            log::error!("{:?}", err);
+            return Ok(());
+        }
+    };
+    session.text(message).await
+}
+
+/// Serves the attestation block of a round so that members can recompute
+/// the hash and check their inclusion. Serialized from memory while the round's
+/// contributions are retained, otherwise served from the block published to s3,
+/// decompressed, or redirected to if it was published uncompressed.
+/// Responds with 404 if neither.
+pub async fn attestation(
+    operator: web::Data<Operator>,
+    aggregator: web::Data<tokio::sync::RwLock<Aggregator>>,
+    path: web::Path<i64>,
+) -> impl Responder {
+    let last_hash_at = path.into_inner();
+    let (attestation, client) = {
+        let aggregator = aggregator.read().await;
+        (
+            aggregator.get_attestation(last_hash_at),
+            aggregator.attestation_client.clone(),
+        )
+    };
+    match attestation {
+        Ok(Some(attestation)) => return HttpResponse::Ok().json(&attestation),
+        Ok(None) => {}
+        Err(err) => {
+            log::error!("{:?}", err);
+            let http_response: HttpResponse = err.into();
+            return http_response;
+        }
+    }
+    // compressed blocks are decompressed for the client
+    match (
+        read_attestation(operator.as_ref(), last_hash_at).await,
+        client.as_ref(),
+    ) {
+        (Ok(Some((_, true))), Some(client)) => match client.fetch(last_hash_at).await {
+            Ok(block) => HttpResponse::Ok().content_type("text/plain").body(block),
+            Err(err) => {
+                log::error!("{:?}", err);
+                let http_response: HttpResponse = err.into();
+                http_response
+            }
+        },
+        (Ok(Some((url, _))), _) => HttpResponse::Found()
+            .insert_header((header::LOCATION, url))
+            .finish(),
+        (Ok(None), _) => ErrorCode::NotFound.response(StatusCode::NOT_FOUND, "round not retained"),
+        (Err(err), _) => {
+            log::error!("{:?}", err);
+            let http_response: HttpResponse = err.into();
+            http_response
+        }
+    }
+}
+
+/// Responds with 404 if the round is no longer (or never was) retained.
+pub async fn challenge_history(
+    aggregator: web::Data<tokio::sync::RwLock<Aggregator>>,
+    path: web::Path<i64>,
+) -> impl Responder {
+    let last_hash_at = path.into_inner();
+    let round = {
+        let aggregator = aggregator.read().await;
+        aggregator.get_round(last_hash_at)
+    };
+    match round {
+        Some(round) => HttpResponse::Ok().json(round),
+        None => ErrorCode::NotFound.response(StatusCode::NOT_FOUND, "round not retained"),
+    }
+}
+
+/// Reports live stats for the current challenge.
+/// Responds with 503 if the aggregator is busy (e.g. submitting) for too long.
+pub async fn stats(aggregator: web::Data<tokio::sync::RwLock<Aggregator>>) -> impl Responder {
+    let timeout = tokio::time::Duration::from_millis(STATS_LOCK_TIMEOUT_MS);
+    match tokio::time::timeout(timeout, aggregator.read()).await {
+        Ok(aggregator) => {
+            let stats = aggregator.stats();
+            drop(aggregator);
+            HttpResponse::Ok().json(&stats)
+        }
+        Err(_) => {
+            log::error!("timed out acquiring aggregator read lock for stats");
+            ErrorCode::ServerBusy.response(StatusCode::SERVICE_UNAVAILABLE, "aggregator busy")
+        }
+    }
+}
+
+/// Reports the total staked and the number of stakers per boost mint,
+/// from the cached stake, so it's cheap enough to poll.
+/// Responds with 503 if the aggregator is busy (e.g. submitting) for too long.
+pub async fn stake(
+    operator: web::Data<Operator>,
+    aggregator: web::Data<tokio::sync::RwLock<Aggregator>>,
+) -> impl Responder {
+    let config = operator.config();
+    let timeout = tokio::time::Duration::from_millis(STATS_LOCK_TIMEOUT_MS);
+    match tokio::time::timeout(timeout, aggregator.read()).await {
+        Ok(aggregator) => {
+            let totals = aggregator.stake_totals(config.as_ref());
+            drop(aggregator);
+            HttpResponse::Ok().json(&totals)
+        }
+        Err(_) => {
+            log::error!("timed out acquiring aggregator read lock for stake");
+            ErrorCode::ServerBusy.response(StatusCode::SERVICE_UNAVAILABLE, "aggregator busy")
+        }
+    }
+}
+
+/// Estimates the per-round reward of a hypothetical contribution
+/// of the given difficulty, or of the difficulty expected from the given hashrate,
+/// from the average of recently distributed rounds.
+pub async fn estimate(
+    operator: web::Data<Operator>,
+    aggregator: web::Data<tokio::sync::RwLock<Aggregator>>,
+    query: web::Query<EstimateQuery>,
+) -> impl Responder {
+    let difficulty = match (query.difficulty, query.hashrate) {
+        (Some(difficulty), _) => difficulty,
+        (None, Some(hashrate)) => expected_difficulty(hashrate),
+        (None, None) => {
+            return ErrorCode::InvalidRequest.response(
+                StatusCode::BAD_REQUEST,
+                "either difficulty or hashrate is required",
+            )
+        }
+    };
+    let timeout = tokio::time::Duration::from_millis(STATS_LOCK_TIMEOUT_MS);
+    let estimate = match tokio::time::timeout(timeout, aggregator.read()).await {
+        Ok(aggregator) => aggregator.estimate_reward(operator.as_ref(), difficulty),
+        Err(_) => {
+            log::error!("timed out acquiring aggregator read lock for estimate");
+            return ErrorCode::ServerBusy
+                .response(StatusCode::SERVICE_UNAVAILABLE, "aggregator busy");
+        }
+    };
+    match estimate {
+        Some(estimate) => HttpResponse::Ok().json(&estimate),
+        None => ErrorCode::NotFound.response(
+            StatusCode::NOT_FOUND,
+            "no rounds have been distributed yet to estimate from",
+        ),
+    }
+}
+
+/// Returns the top contributors for the current challenge, sorted by score.
+pub async fn leaderboard(
+    aggregator: web::Data<tokio::sync::RwLock<Aggregator>>,
+    query: web::Query<LeaderboardQuery>,
+) -> impl Responder {
+    let limit = query.limit.unwrap_or(LEADERBOARD_DEFAULT_LIMIT);
+    // acquire read on aggregator for contributions
+    let (mut contributions, total_score) = {
+        let aggregator = aggregator.read().await;
+        let last_hash_at = aggregator.challenge.lash_hash_at as u64;
+        let contributions: Vec<Contribution> = aggregator
+            .contributions
+            .get(&last_hash_at)
+            .map(|c| c.iter().copied().collect())
+            .unwrap_or_default();
+        (contributions, aggregator.total_score)
+    };
+    // sort by score descending
+    contributions.sort_by(|a, b| b.score.cmp(&a.score));
+    contributions.truncate(limit);
+    let entries: Vec<LeaderboardEntry> = contributions
+        .into_iter()
+        .map(|c| {
+            let percentage = match total_score {
+                0 => 0.0,
+                _ => (c.score as f64) / (total_score as f64) * 100.0,
+            };
+            LeaderboardEntry {
+                member: c.member,
+                score: c.score,
+                difficulty: c.solution.to_hash().difficulty(),
+                percentage,
+            }
+        })
+        .collect();
+    HttpResponse::Ok().json(&entries)
+}
+
 /// Accepts solutions from pool members. If their solutions are valid, it
 /// aggregates the contributions into a list for publishing and submission.
+/// Responds with a receipt signed by the operator, once the contribution is in the round,
+/// or with an empty body if receipts are off.
+#[allow(clippy::too_many_arguments)]
 pub async fn contribute(
     operator: web::Data<Operator>,
     aggregator: web::Data<tokio::sync::RwLock<Aggregator>>,
-    tx: web::Data<tokio::sync::mpsc::UnboundedSender<Contribution>>,
+    tx: web::Data<tokio::sync::mpsc::Sender<Forwarded>>,
+    rate_limiter: web::Data<RateLimiter>,
+    jail: web::Data<Jail>,
+    access: web::Data<Access>,
+    validator: web::Data<Validator>,
+    request_id: RequestId,
     payload: web::Json<ContributePayload>,
 ) -> impl Responder {
     // acquire read on aggregator for challenge
     let aggregator = aggregator.read().await;
     let challenge = aggregator.challenge;
     let num_members = aggregator.num_members;
+    let paused = aggregator.breaker.is_open();
     drop(aggregator);
-    // decode solution difficulty
-    let solution = &payload.solution;
-    let difficulty = solution.to_hash().difficulty();
-    // authenticate the sender signature
-    if !payload
-        .signature
-        .verify(&payload.authority.to_bytes(), &solution.to_bytes())
-    {
-        return HttpResponse::Unauthorized().finish();
-    }
-    // error if solution below min difficulty
-    if difficulty < (challenge.min_difficulty as u32) {
-        log::error!("solution below min difficulity: {:?}", payload.authority);
-        return HttpResponse::BadRequest().finish();
-    }
-    // error if digest is invalid
-    if !drillx::is_valid_digest(&challenge.challenge, &solution.n, &solution.d) {
-        log::error!("invalid solution");
-        return HttpResponse::BadRequest().finish();
-    }
-    // validate nonce
-    let member_authority = &payload.authority;
-    let nonce = solution.n;
-    let nonce = u64::from_le_bytes(nonce);
-    if let Err(err) = validate_nonce(operator.as_ref(), member_authority, nonce, num_members).await
-    {
-        log::error!("{:?}", err);
-        return HttpResponse::Unauthorized().finish();
-    }
-    // calculate score
-    let score = 2u64.pow(difficulty);
-    // update the aggegator
-    if let Err(err) = tx.send(Contribution {
-        member: payload.authority,
+    let gate = ContributeGate {
+        operator: operator.as_ref(),
+        tx: tx.as_ref(),
+        rate_limiter: rate_limiter.as_ref(),
+        jail: jail.as_ref(),
+        access: access.as_ref(),
+        validator: validator.as_ref(),
+        request_id,
+        challenge,
+        num_members,
+        paused,
+    };
+    match gate.accept(&payload).await {
+        Ok(Some(receipt)) => HttpResponse::Ok().json(receipt),
+        Ok(None) => HttpResponse::Ok().finish(),
+        Err(rejected) => rejected.response(),
+    }
+}
+
+/// Accepts a batch of solutions from a relay, validating each independently.
+/// If the batch holds several valid solutions from the same member only the best is forwarded,
+/// the rest are rejected as superseded.
+/// Responds with a result per solution, in the order submitted.
+#[allow(clippy::too_many_arguments)]
+pub async fn contribute_batch(
+    operator: web::Data<Operator>,
+    aggregator: web::Data<tokio::sync::RwLock<Aggregator>>,
+    tx: web::Data<tokio::sync::mpsc::Sender<Forwarded>>,
+    rate_limiter: web::Data<RateLimiter>,
+    jail: web::Data<Jail>,
+    access: web::Data<Access>,
+    validator: web::Data<Validator>,
+    request_id: RequestId,
+    payload: web::Json<Vec<ContributePayload>>,
+) -> impl Responder {
+    let payloads = payload.into_inner();
+    if payloads.len() > CONTRIBUTE_BATCH_MAX {
+        return ErrorCode::InvalidRequest.response(
+            StatusCode::BAD_REQUEST,
+            format!("batch exceeds {} solutions", CONTRIBUTE_BATCH_MAX),
+        );
+    }
+    // acquire read on aggregator for challenge
+    let aggregator = aggregator.read().await;
+    let challenge = aggregator.challenge;
+    let num_members = aggregator.num_members;
+    let paused = aggregator.breaker.is_open();
+    drop(aggregator);
+    let gate = ContributeGate {
+        operator: operator.as_ref(),
+        tx: tx.as_ref(),
+        rate_limiter: rate_limiter.as_ref(),
+        jail: jail.as_ref(),
+        access: access.as_ref(),
+        validator: validator.as_ref(),
+        request_id,
+        challenge,
+        num_members,
+        paused,
+    };
+    // validate every solution first,
+    // so that an invalid solution can't supersede a valid one from the same member
+    let mut validated = Vec::with_capacity(payloads.len());
+    for payload in payloads.iter() {
+        validated.push(gate.validate(payload).await);
+    }
+    let best = best_per_member(
+        payloads
+            .iter()
+            .zip(validated.iter())
+            .enumerate()
+            .filter(|(_, (_, score))| score.is_ok())
+            .map(|(index, (payload, _))| (index, payload)),
+    );
+    let mut results = Vec::with_capacity(payloads.len());
+    for (index, (payload, score)) in payloads.iter().zip(validated).enumerate() {
+        let result = match score {
+            Ok(score) if best.get(&payload.authority) == Some(&index) => {
+                gate.forward(payload, score).await
+            }
+            Ok(_) => Err(Rejected::new(
+                StatusCode::BAD_REQUEST,
+                ErrorCode::Superseded,
+                "superseded by a better solution from the same member",
+            )),
+            Err(rejected) => Err(rejected),
+        };
+        results.push(ContributeResult::from(result));
+    }
+    HttpResponse::Ok().json(&results)
+}
+
+/// The result of a single solution in a batch contribute.
+#[derive(Debug, serde::Serialize)]
+struct ContributeResult {
+    accepted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    receipt: Option<ContributionReceipt>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<ErrorBody>,
+}
+
+impl From<Result<Option<ContributionReceipt>, Rejected>> for ContributeResult {
+    fn from(result: Result<Option<ContributionReceipt>, Rejected>) -> Self {
+        match result {
+            Ok(receipt) => Self {
+                accepted: true,
+                receipt,
+                error: None,
+            },
+            Err(rejected) => Self {
+                accepted: false,
+                receipt: None,
+                error: Some(ErrorBody {
+                    code: rejected.code,
+                    message: rejected.message,
+                }),
+            },
+        }
+    }
+}
+
+/// A contribution that failed validation, with how to respond.
+#[derive(Debug)]
+struct Rejected {
+    status: StatusCode,
+    code: ErrorCode,
+    message: String,
+}
+
+impl Rejected {
+    fn new(status: StatusCode, code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+        }
+    }
+
+    fn response(self) -> HttpResponse {
+        self.code.response(self.status, self.message)
+    }
+}
+
+/// Validates contributions against the current challenge
+/// and forwards the valid ones to the aggregator.
+struct ContributeGate<'a> {
+    operator: &'a Operator,
+    tx: &'a tokio::sync::mpsc::Sender<Forwarded>,
+    rate_limiter: &'a RateLimiter,
+    jail: &'a Jail,
+    access: &'a Access,
+    validator: &'a Validator,
+    request_id: RequestId,
+    challenge: Challenge,
+    num_members: u64,
+    paused: bool,
+}
+
+impl ContributeGate<'_> {
+    async fn accept(
+        &self,
+        payload: &ContributePayload,
+    ) -> Result<Option<ContributionReceipt>, Rejected> {
+        let score = self.validate(payload).await?;
+        self.forward(payload, score).await
+    }
+
+    /// Validates the contribution, returning its score.
+    async fn validate(&self, payload: &ContributePayload) -> Result<u64, Rejected> {
+        let challenge = &self.challenge;
+        // reject while submits are failing, the contribution would never land
+        if self.paused {
+            metrics::contribution_rejected(Rejection::Paused);
+            return Err(Rejected::new(
+                StatusCode::SERVICE_UNAVAILABLE,
+                ErrorCode::PoolPaused,
+                "pool paused",
+            ));
+        }
+        // reject jailed members
+        if self.jail.is_jailed(&payload.authority) {
+            log::error!("jailed: {:?}", payload.authority);
+            metrics::contribution_rejected(Rejection::Jailed);
+            return Err(Rejected::new(
+                StatusCode::FORBIDDEN,
+                ErrorCode::Jailed,
+                "member is jailed",
+            ));
+        }
+        // reject denied authorities, and authorities not allowed in an allowlist-only pool
+        if let Err(err) = self.access.check(&payload.authority) {
+            log::error!("access denied: {:?}", payload.authority);
+            metrics::contribution_rejected(Rejection::AccessDenied);
+            return Err(Rejected::new(
+                StatusCode::FORBIDDEN,
+                err.code(),
+                err.to_string(),
+            ));
+        }
+        // verify the signature off the async runtime,
+        // so that only the authority that signed is charged against its rate limit
+        let pool_address = self.operator.pool;
+        match self
+            .validator
+            .verify_signature(payload, pool_address, *challenge)
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => {
+                metrics::contribution_rejected(Rejection::BadSignature);
+                return Err(Rejected::new(
+                    StatusCode::UNAUTHORIZED,
+                    ErrorCode::BadSignature,
+                    "invalid signature",
+                ));
+            }
+            Err(err) => {
+                log::error!("{:?}", err);
+                return Err(Rejected::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    err.code(),
+                    err.to_string(),
+                ));
+            }
+        }
+        // throttle before spending cpu on the solution
+        if !self.rate_limiter.check(&payload.authority) {
+            log::error!("rate limited: {:?}", payload.authority);
+            metrics::contribution_rejected(Rejection::RateLimited);
+            return Err(Rejected::new(
+                StatusCode::TOO_MANY_REQUESTS,
+                ErrorCode::RateLimited,
+                "rate limited",
+            ));
+        }
+        // verify the solution off the async runtime
+        let verdict = match self.validator.validate(payload, *challenge).await {
+            Ok(verdict) => verdict,
+            Err(err) => {
+                log::error!("{:?}", err);
+                return Err(Rejected::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    err.code(),
+                    err.to_string(),
+                ));
+            }
+        };
+        let score = match verdict {
+            Verdict::Valid { score } => score,
+            Verdict::BelowDifficulty(difficulty) => {
+                log::error!("solution below min difficulity: {:?}", payload.authority);
+                metrics::contribution_rejected(Rejection::BelowDifficulty);
+                return Err(Rejected::new(
+                    StatusCode::BAD_REQUEST,
+                    ErrorCode::BelowDifficulty,
+                    format!(
+                        "difficulty {} below min difficulty {}",
+                        difficulty, challenge.min_difficulty
+                    ),
+                ));
+            }
+            Verdict::BelowMinScore(score) => {
+                log::error!(
+                    "solution score below operator min score: {:?} {} < {}",
+                    payload.authority,
+                    score,
+                    challenge.min_score
+                );
+                metrics::contribution_rejected(Rejection::BelowMinScore);
+                return Err(Rejected::new(
+                    StatusCode::BAD_REQUEST,
+                    ErrorCode::BelowMinScore,
+                    format!("score {} below min score {}", score, challenge.min_score),
+                ));
+            }
+            Verdict::InvalidDigest => {
+                log::error!("invalid solution: {:?}", payload.authority);
+                metrics::contribution_rejected(Rejection::InvalidDigest);
+                // only members are struck, so that throwaway keypairs can't grow the jail
+                if self
+                    .operator
+                    .get_member_db(payload.authority.to_string().as_str())
+                    .await
+                    .is_ok()
+                {
+                    self.jail.strike(&payload.authority);
+                }
+                return Err(Rejected::new(
+                    StatusCode::BAD_REQUEST,
+                    ErrorCode::InvalidDigest,
+                    "invalid digest",
+                ));
+            }
+        };
+        // validate nonce
+        let member_authority = &payload.authority;
+        let nonce = payload.solution.n;
+        let nonce = u64::from_le_bytes(nonce);
+        if let Err(err) = validate_member(
+            self.operator,
+            member_authority,
+            nonce,
+            self.num_members,
+            challenge.lash_hash_at,
+        )
+        .await
+        {
+            log::error!("{:?}", err);
+            return Err(match err {
+                Error::InvalidNonce => {
+                    log::error!("nonce outside of assigned range: {:?}", member_authority);
+                    metrics::contribution_rejected(Rejection::InvalidNonce);
+                    Rejected::new(
+                        StatusCode::BAD_REQUEST,
+                        ErrorCode::InvalidNonce,
+                        "nonce outside of assigned range",
+                    )
+                }
+                Error::RegisteredMidRound => {
+                    metrics::contribution_rejected(Rejection::RegisteredMidRound);
+                    Rejected::new(StatusCode::FORBIDDEN, err.code(), err.to_string())
+                }
+                // unknown or deregistered members
+                _ => Rejected::new(StatusCode::UNAUTHORIZED, err.code(), err.to_string()),
+            });
+        }
+        Ok(score)
+    }
+
+    /// Forwards a validated contribution to the aggregator,
+    /// answering with a receipt once it's in the round.
+    async fn forward(
+        &self,
+        payload: &ContributePayload,
+        score: u64,
+    ) -> Result<Option<ContributionReceipt>, Rejected> {
+        let challenge = &self.challenge;
+        // update the aggegator,
+        // rejecting rather than buffering if the aggregator has fallen behind
+        let (reply, inserted) = tokio::sync::oneshot::channel();
+        let forwarded = Forwarded {
+            contribution: Contribution {
+                member: payload.authority,
+                score,
+                solution: payload.solution,
+                request_id: self.request_id,
+                received_at: 0,
+            },
+            reply,
+        };
+        if let Err(err) = self.tx.try_send(forwarded) {
+            log::error!("{:?}", err);
+            metrics::contribution_rejected(Rejection::Backpressure);
+            return Err(Rejected::new(
+                StatusCode::SERVICE_UNAVAILABLE,
+                ErrorCode::ServerBusy,
+                "server busy",
+            ));
+        }
+        // only answer with a receipt once the contribution is in the round
+        match inserted.await {
+            Ok(Inserted::Accepted) => {}
+            Ok(Inserted::Superseded) => {
+                return Err(Rejected::new(
+                    StatusCode::BAD_REQUEST,
+                    ErrorCode::Superseded,
+                    "superseded by a better solution from the same member",
+                ));
+            }
+            Ok(Inserted::DuplicateSolution) => {
+                return Err(Rejected::new(
+                    StatusCode::CONFLICT,
+                    ErrorCode::DuplicateSolution,
+                    "solution already contributed",
+                ));
+            }
+            Err(_) => {
+                return Err(Rejected::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    ErrorCode::Internal,
+                    "contribution was not recorded",
+                ));
+            }
+        }
+        metrics::contribution_accepted();
+        if !self.operator.receipts {
+            return Ok(None);
+        }
+        match sign_receipt(
+            self.operator.signer.as_ref(),
+            payload.authority,
+            challenge.lash_hash_at,
+            challenge.epoch,
+            score,
+        )
+        .await
+        {
+            Ok(receipt) => Ok(Some(receipt)),
+            Err(err) => {
+                log::error!("{:?}", err);
+                Err(Rejected::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    err.code(),
+                    err.to_string(),
+                ))
+            }
+        }
+    }
+}
+
+/// Signs a receipt for a contribution accepted to the round.
+async fn sign_receipt(
+    signer: &dyn OperatorSigner,
+    authority: Pubkey,
+    last_hash_at: i64,
+    epoch: u64,
+    score: u64,
+) -> Result<ContributionReceipt, Error> {
+    let message = ContributionReceipt::message(&authority, last_hash_at, epoch, score);
+    let signature = signer.sign_message(message.as_slice()).await?;
+    Ok(ContributionReceipt {
+        authority,
+        last_hash_at,
+        epoch,
         score,
-        solution: payload.solution,
-    }) {
-        log::error!("{:?}", err);
+        signature,
+    })
+}
+
+/// The index of each member's most difficult solution of those given,
+/// the first if tied.
+fn best_per_member<'a>(
+    payloads: impl Iterator<Item = (usize, &'a ContributePayload)>,
+) -> HashMap<Pubkey, usize> {
+    let mut best: HashMap<Pubkey, (usize, u32)> = HashMap::new();
+    for (index, payload) in payloads {
+        let difficulty = payload.solution.to_hash().difficulty();
+        match best.get(&payload.authority) {
+            Some((_, best_difficulty)) if *best_difficulty >= difficulty => {}
+            _ => {
+                best.insert(payload.authority, (index, difficulty));
+            }
+        }
     }
-    HttpResponse::Ok().finish()
+    best.into_iter()
+        .map(|(authority, (index, _))| (authority, index))
+        .collect()
 }
 ////////////////////////////////////////////////////////////////////////////////////
 ////////////////////////////////////////////////////////////////////////////////////
@@ -170,7 +1140,7 @@ async fn update_balance_onchain(
     operator: &Operator,
     payload: UpdateBalancePayload,
 ) -> Result<BalanceUpdate, Error> {
-    let keypair = &operator.keypair;
+    let signer = operator.signer.as_ref();
     let member_authority = payload.authority;
     let hash = payload.hash;
     // fetch member balance
@@ -182,7 +1152,7 @@ async fn update_balance_onchain(
     let fee_payer = tx.message.account_keys.first().ok_or(Error::Internal(
         "missing fee payer in update balance payload".to_string(),
     ))?;
-    if fee_payer.eq(&keypair.pubkey()) {
+    if fee_payer.eq(&signer.pubkey()) {
         return Err(Error::Internal(
             "fee payer must be client for update balance".to_string(),
         ));
@@ -192,28 +1162,52 @@ async fn update_balance_onchain(
     // sign transaction and submit
     let mut tx = tx;
     let rpc_client = &operator.rpc_client;
-    tx.partial_sign(&[keypair], hash);
+    signer::partial_sign_transaction(signer, &mut tx, hash).await?;
     let sig = tx::submit::submit_and_confirm_transaction(rpc_client, &tx).await?;
     log::info!("on demand attribution sig: {:?}", sig);
     // set member as synced in db
     let db_client = &operator.db_client;
     let db_client = db_client.get().await?;
-    let (pool_address, _) = ore_pool_api::state::pool_pda(keypair.pubkey());
+    let pool_address = operator.pool;
     let (member_address, _) = ore_pool_api::state::member_pda(member_authority, pool_address);
     database::write_synced_members(&db_client, &[member_address.to_string()]).await?;
+    let claim_cooldown_remaining = claim_cooldown_remaining(operator, &member_authority).await;
     Ok(BalanceUpdate {
         balance: member.total_balance as u64,
         signature: sig,
+        claim_cooldown_remaining,
     })
 }
 
+/// The seconds left until the member may claim, per the on-chain cooldown.
+/// The balance is already updated by the time this is read,
+/// so a failed read is logged and reported as no cooldown rather than failing the update.
+async fn claim_cooldown_remaining(operator: &Operator, member_authority: &Pubkey) -> u64 {
+    let remaining = async {
+        let pool = operator.get_pool_cached().await?;
+        let claim_cooldown = pool.map_or(0, |pool| pool.claim_cooldown);
+        if claim_cooldown == 0 {
+            return Ok(0);
+        }
+        let member = operator.get_member_onchain(member_authority).await?;
+        let clock = operator.get_clock().await?;
+        Ok::<_, Error>(member.claim_cooldown_remaining(claim_cooldown, clock.unix_timestamp))
+    };
+    match remaining.await {
+        Ok(remaining) => remaining as u64,
+        Err(err) => {
+            log::error!("claim cooldown: {:?}", err);
+            0
+        }
+    }
+}
+
 async fn register_new_staker(
     operator: &Operator,
     aggregator: &tokio::sync::RwLock<Aggregator>,
     webhook_client: &webhook::Client,
     payload: RegisterStakerPayload,
 ) -> Result<Staker, Error> {
-    let keypair = &operator.keypair;
     let member_authority = payload.authority;
     let mint = payload.mint;
     // check if on-chain account already exists
@@ -240,7 +1234,7 @@ async fn register_new_staker(
                 Err(_err) => {
                     // write staker to db
                     let conn = operator.db_client.get().await?;
-                    let (pool_pda, _) = ore_pool_api::state::pool_pda(keypair.pubkey());
+                    let pool_pda = operator.pool;
                     let db_staker =
                         database::write_new_staker(&conn, &member_authority, &pool_pda, &mint)
                             .await?;
@@ -269,9 +1263,10 @@ async fn register_new_member(
     operator: &Operator,
     payload: RegisterPayload,
 ) -> Result<ore_pool_types::Member, Error> {
-    let keypair = &operator.keypair;
     let member_authority = payload.authority;
-    let (pool_pda, _) = ore_pool_api::state::pool_pda(keypair.pubkey());
+    let pool_pda = operator.pool;
+    // reject malformed authorities before touching the rpc or db
+    validate_register_payload(&payload, &pool_pda)?;
     // check if on-chain account already exists
     let member = operator.get_member_onchain(&member_authority).await;
     let db_client = operator.db_client.get().await?;
@@ -297,6 +1292,11 @@ async fn register_new_member(
             // member doesn't exist yet on-chain
             // land tx to create new member account
             log::error!("{:?}", err);
+            // tell the client up front if the join would be rejected
+            let pool = operator.get_pool().await?;
+            if pool.max_members > 0 && pool.active_members >= pool.max_members {
+                return Err(Error::PoolFull);
+            }
             // return error to http client
             // bc they should create the member account before hitting this path
             Err(Error::MemberDoesNotExist)
@@ -304,28 +1304,491 @@ async fn register_new_member(
     }
 }
 
+/// The authority must be on the ed25519 curve (i.e. not a pda),
+/// and if a signature is included it must prove control of the authority.
+fn validate_register_payload(payload: &RegisterPayload, pool: &Pubkey) -> Result<(), Error> {
+    if !payload.authority.is_on_curve() {
+        return Err(Error::InvalidAuthority(
+            "authority is not on the ed25519 curve".to_string(),
+        ));
+    }
+    if let Some(signature) = payload.signature {
+        let message = RegisterPayload::message(pool);
+        if !signature.verify(&payload.authority.to_bytes(), message.as_slice()) {
+            return Err(Error::InvalidAuthority(
+                "signature does not match authority".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+async fn read_member_history(
+    operator: &Operator,
+    payload: GetMemberPayload,
+    cursor: Option<i64>,
+    limit: usize,
+) -> Result<BalanceHistory, Error> {
+    let member_authority = Pubkey::from_str(payload.authority.as_str())?;
+    let pool_pda = operator.pool;
+    let (member_pda, _) = ore_pool_api::state::member_pda(member_authority, pool_pda);
+    let db_client = operator.db_read_client.get().await?;
+    let events = database::read_member_balance_history(
+        &db_client,
+        &member_pda.to_string(),
+        cursor,
+        limit as i64,
+    )
+    .await?;
+    // a short page is the last page
+    let next_cursor = match events.last() {
+        Some(last) if events.len() == limit => Some(last.last_hash_at),
+        _ => None,
+    };
+    Ok(BalanceHistory {
+        events,
+        next_cursor,
+    })
+}
+
+async fn read_member_daily(
+    operator: &Operator,
+    payload: GetMemberPayload,
+    query: DailyEarningsQuery,
+) -> Result<Vec<DailyEarnings>, Error> {
+    let member_authority = Pubkey::from_str(payload.authority.as_str())?;
+    day_span(query.from.as_str(), query.to.as_str())?;
+    let tz = query.tz.unwrap_or_else(|| "UTC".to_string());
+    let pool_pda = operator.pool;
+    let (member_pda, _) = ore_pool_api::state::member_pda(member_authority, pool_pda);
+    let db_client = operator.db_read_client.get().await?;
+    database::read_member_daily(
+        &db_client,
+        &member_pda.to_string(),
+        query.from.as_str(),
+        query.to.as_str(),
+        tz.as_str(),
+    )
+    .await
+}
+
+/// The number of days from the first to the last day, inclusive,
+/// capped so that a request can't scan a member's whole history.
+fn day_span(from: &str, to: &str) -> Result<i64, Error> {
+    let invalid = |day: &str| Error::InvalidRequest(format!("invalid date: {}", day));
+    let from_day = parse_day(from).ok_or_else(|| invalid(from))?;
+    let to_day = parse_day(to).ok_or_else(|| invalid(to))?;
+    let span = to_day - from_day + 1;
+    if span < 1 {
+        return Err(Error::InvalidRequest("to is before from".to_string()));
+    }
+    if span > DAILY_MAX_DAYS {
+        return Err(Error::InvalidRequest(format!(
+            "range exceeds {} days",
+            DAILY_MAX_DAYS
+        )));
+    }
+    Ok(span)
+}
+
+/// Parses a YYYY-MM-DD date into days since the unix epoch.
+fn parse_day(day: &str) -> Option<i64> {
+    let bytes = day.as_bytes();
+    if bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+        return None;
+    }
+    let year: i64 = day[0..4].parse().ok()?;
+    let month: i64 = day[5..7].parse().ok()?;
+    let date: i64 = day[8..10].parse().ok()?;
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return None,
+    };
+    if date < 1 || date > days_in_month {
+        return None;
+    }
+    // days from civil, with the year starting in march
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + date - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    Some(era * 146_097 + day_of_era - 719_468)
+}
+
+async fn read_attestation(
+    operator: &Operator,
+    last_hash_at: i64,
+) -> Result<Option<(String, bool)>, Error> {
+    let db_client = operator.db_read_client.get().await?;
+    database::read_attestation(&db_client, last_hash_at).await
+}
+
+async fn deregister_member(
+    operator: &Operator,
+    payload: DeregisterPayload,
+) -> Result<ore_pool_types::Member, Error> {
+    let member_authority = payload.authority;
+    // the member authenticates by landing the deregister instruction on-chain
+    let member = operator
+        .get_member_onchain(&member_authority)
+        .await
+        .map_err(|_| Error::MemberDoesNotExist)?;
+    if member.deregistered == 0 {
+        return Err(Error::MemberNotDeregistered);
+    }
+    // mark inactive in db
+    let pool_pda = operator.pool;
+    let (member_pda, _) = ore_pool_api::state::member_pda(member_authority, pool_pda);
+    let db_client = operator.db_client.get().await?;
+    database::write_deregistered_member(&db_client, &member_pda.to_string()).await?;
+    database::read_member(&db_client, &member_pda.to_string()).await
+}
+
+async fn set_auto_compound(
+    operator: &Operator,
+    payload: AutoCompoundPayload,
+) -> Result<ore_pool_types::Member, Error> {
+    let pool_pda = operator.pool;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    validate_auto_compound_payload(&payload, &pool_pda, now)?;
+    let (member_pda, _) = ore_pool_api::state::member_pda(payload.authority, pool_pda);
+    let db_client = operator.db_client.get().await?;
+    let member = database::read_member(&db_client, &member_pda.to_string())
+        .await
+        .map_err(|_| Error::MemberDoesNotExist)?;
+    if !member.is_active {
+        return Err(Error::MemberInactive);
+    }
+    database::write_auto_compound(&db_client, &member_pda.to_string(), payload.enabled).await?;
+    database::read_member(&db_client, &member_pda.to_string()).await
+}
+
+/// The signature must prove control of the authority,
+/// over a timestamp recent enough that the payload isn't a replay.
+fn validate_auto_compound_payload(
+    payload: &AutoCompoundPayload,
+    pool: &Pubkey,
+    now: i64,
+) -> Result<(), Error> {
+    let message = AutoCompoundPayload::message(pool, payload.enabled, payload.timestamp);
+    if !payload
+        .signature
+        .verify(&payload.authority.to_bytes(), message.as_slice())
+    {
+        return Err(Error::InvalidAuthority(
+            "signature does not match authority".to_string(),
+        ));
+    }
+    if now.abs_diff(payload.timestamp) > AUTO_COMPOUND_PAYLOAD_MAX_AGE_SECS as u64 {
+        return Err(Error::InvalidAuthority("payload has expired".to_string()));
+    }
+    Ok(())
+}
+
 // TODO: consider fitting lookup table from member authority to id, in memory
-async fn validate_nonce(
+/// Validates the member is active, registered for the whole round if required,
+/// and that the nonce is within its assigned range.
+async fn validate_member(
     operator: &Operator,
     member_authority: &Pubkey,
     nonce: u64,
     num_members: u64,
+    round_start: i64,
 ) -> Result<(), Error> {
-    if num_members.eq(&0) {
+    if num_members.eq(&0) && !operator.require_full_round {
         return Ok(());
     }
     let member = operator
         .get_member_db(member_authority.to_string().as_str())
         .await?;
+    if !member.is_active {
+        return Err(Error::MemberInactive);
+    }
+    if operator.require_full_round && !registered_for_round(member.registered_at, round_start) {
+        return Err(Error::RegisteredMidRound);
+    }
+    if num_members.eq(&0) {
+        return Ok(());
+    }
     let nonce_index = member.id as u64;
+    if is_valid_nonce(nonce, nonce_index, num_members) {
+        Ok(())
+    } else {
+        Err(Error::InvalidNonce)
+    }
+}
+
+/// Whether the member was registered by the time the round started.
+/// Members registered before registrations were recorded are.
+fn registered_for_round(registered_at: Option<i64>, round_start: i64) -> bool {
+    registered_at.map_or(true, |registered_at| registered_at <= round_start)
+}
+
+/// The nonce space is divided evenly by the number of members,
+/// each member is assigned the disjoint range [left, right) by their id.
+fn nonce_range(nonce_index: u64, num_members: u64) -> (u64, u64) {
     let u64_unit = u64::MAX.saturating_div(num_members);
     let left_bound = u64_unit.saturating_mul(nonce_index);
     let right_bound = u64_unit.saturating_mul(nonce_index + 1);
-    let ge_left = nonce >= left_bound;
-    let le_right = nonce <= right_bound;
-    if ge_left && le_right {
-        Ok(())
-    } else {
-        Err(Error::Internal("invalid nonce from client".to_string()))
+    (left_bound, right_bound)
+}
+
+/// The range served to the member,
+/// the whole nonce space if there are no members yet since nonces aren't validated then.
+fn assigned_nonce_range(nonce_index: u64, num_members: u64) -> (u64, u64) {
+    match num_members {
+        0 => (0, u64::MAX),
+        _ => nonce_range(nonce_index, num_members),
+    }
+}
+
+fn is_valid_nonce(nonce: u64, nonce_index: u64, num_members: u64) -> bool {
+    let (left_bound, right_bound) = nonce_range(nonce_index, num_members);
+    nonce >= left_bound && nonce < right_bound
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::{signature::Keypair, signer::Signer};
+
+    use super::*;
+    use crate::signer::LocalSigner;
+
+    fn unsigned_payload(authority: Pubkey, difficulty: u32) -> ContributePayload {
+        let solution = (0u64..)
+            .map(|n| drillx::Solution::new([0; 16], n.to_le_bytes()))
+            .find(|s| s.to_hash().difficulty() == difficulty)
+            .unwrap();
+        ContributePayload {
+            authority,
+            solution,
+            signature: solana_sdk::signature::Signature::default(),
+        }
+    }
+
+    #[test]
+    fn batch_keeps_best_per_member() {
+        let member = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let payloads = vec![
+            unsigned_payload(member, 2),
+            unsigned_payload(other, 1),
+            unsigned_payload(member, 4),
+            unsigned_payload(member, 4),
+            unsigned_payload(member, 3),
+        ];
+        let best = best_per_member(payloads.iter().enumerate());
+        assert_eq!(best.len(), 2);
+        // the first of the most difficult
+        assert_eq!(best[&member], 2);
+        assert_eq!(best[&other], 1);
+        // among the valid solutions only
+        let valid = payloads
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| ![2, 3].contains(index));
+        assert_eq!(best_per_member(valid)[&member], 4);
+    }
+
+    fn signed_payload(pool: &Pubkey, last_hash_at: i64) -> ContributePayload {
+        let keypair = solana_sdk::signature::Keypair::new();
+        let solution = drillx::Solution::new([1; 16], [2; 8]);
+        let message = ContributePayload::message(pool, last_hash_at, &solution);
+        ContributePayload {
+            authority: keypair.pubkey(),
+            solution,
+            signature: keypair.sign_message(message.as_slice()),
+        }
+    }
+
+    #[tokio::test]
+    async fn receipt_verifies_against_operator() {
+        let operator = LocalSigner::new(Keypair::new());
+        let receipt = sign_receipt(&operator, Pubkey::new_unique(), 100, 7, 42)
+            .await
+            .unwrap();
+        assert!(receipt.verify(&operator.pubkey()));
+        assert!(!receipt.verify(&Pubkey::new_unique()));
+    }
+
+    #[tokio::test]
+    async fn tampered_receipt_is_invalid() {
+        let operator = LocalSigner::new(Keypair::new());
+        let receipt = sign_receipt(&operator, Pubkey::new_unique(), 100, 7, 42)
+            .await
+            .unwrap();
+        let inflated = ContributionReceipt {
+            score: 43,
+            ..receipt
+        };
+        assert!(!inflated.verify(&operator.pubkey()));
+        let replayed = ContributionReceipt {
+            last_hash_at: 101,
+            ..receipt
+        };
+        assert!(!replayed.verify(&operator.pubkey()));
+        let other_epoch = ContributionReceipt {
+            epoch: 8,
+            ..receipt
+        };
+        assert!(!other_epoch.verify(&operator.pubkey()));
+        let stolen = ContributionReceipt {
+            authority: Pubkey::new_unique(),
+            ..receipt
+        };
+        assert!(!stolen.verify(&operator.pubkey()));
+    }
+
+    #[test]
+    fn signature_in_domain_is_valid() {
+        let pool = Pubkey::new_unique();
+        let payload = signed_payload(&pool, 100);
+        assert!(payload.verify(&pool, 100));
+    }
+
+    #[test]
+    fn signature_out_of_domain_is_invalid() {
+        let pool = Pubkey::new_unique();
+        let payload = signed_payload(&pool, 100);
+        // replayed against another pool
+        assert!(!payload.verify(&Pubkey::new_unique(), 100));
+        // replayed against another challenge
+        assert!(!payload.verify(&pool, 101));
+        // signed over the bare solution
+        let keypair = solana_sdk::signature::Keypair::new();
+        let bare = ContributePayload {
+            authority: keypair.pubkey(),
+            solution: payload.solution,
+            signature: keypair.sign_message(&payload.solution.to_bytes()),
+        };
+        assert!(!bare.verify(&pool, 100));
+    }
+
+    #[test]
+    fn register_off_curve_authority_is_invalid() {
+        let pool = Pubkey::new_unique();
+        let (pda, _) = Pubkey::find_program_address(&[b"member"], &ore_pool_api::ID);
+        let payload = RegisterPayload {
+            authority: pda,
+            signature: None,
+        };
+        assert!(validate_register_payload(&payload, &pool).is_err());
+    }
+
+    #[test]
+    fn register_signed_authority_is_valid() {
+        let pool = Pubkey::new_unique();
+        let keypair = solana_sdk::signature::Keypair::new();
+        let message = RegisterPayload::message(&pool);
+        let payload = RegisterPayload {
+            authority: keypair.pubkey(),
+            signature: Some(keypair.sign_message(message.as_slice())),
+        };
+        assert!(validate_register_payload(&payload, &pool).is_ok());
+        // signed for another pool
+        let payload = RegisterPayload {
+            authority: keypair.pubkey(),
+            signature: Some(keypair.sign_message(&RegisterPayload::message(&Pubkey::new_unique()))),
+        };
+        assert!(validate_register_payload(&payload, &pool).is_err());
+    }
+
+    fn auto_compound_payload(
+        keypair: &solana_sdk::signature::Keypair,
+        pool: &Pubkey,
+        timestamp: i64,
+    ) -> AutoCompoundPayload {
+        let message = AutoCompoundPayload::message(pool, true, timestamp);
+        AutoCompoundPayload {
+            authority: keypair.pubkey(),
+            enabled: true,
+            timestamp,
+            signature: keypair.sign_message(message.as_slice()),
+        }
+    }
+
+    #[test]
+    fn auto_compound_payload_must_be_signed_and_recent() {
+        let keypair = solana_sdk::signature::Keypair::new();
+        let pool = Pubkey::new_unique();
+        let payload = auto_compound_payload(&keypair, &pool, 1_000);
+        assert!(validate_auto_compound_payload(&payload, &pool, 1_000).is_ok());
+        // replayed long after signing
+        assert!(validate_auto_compound_payload(&payload, &pool, 1_000 + 301).is_err());
+        // signed for another pool
+        assert!(validate_auto_compound_payload(&payload, &Pubkey::new_unique(), 1_000).is_err());
+        // preference flipped after signing
+        let flipped = AutoCompoundPayload {
+            enabled: false,
+            ..payload
+        };
+        assert!(validate_auto_compound_payload(&flipped, &pool, 1_000).is_err());
+    }
+
+    #[test]
+    fn just_registered_member_is_rejected_from_round_in_progress() {
+        let round_start = 1_000;
+        // registered mid-round, then submits a pre-computed solution
+        assert!(!registered_for_round(Some(round_start + 5), round_start));
+        // registered before the round started
+        assert!(registered_for_round(Some(round_start - 60), round_start));
+        assert!(registered_for_round(Some(round_start), round_start));
+        // registered before registrations were recorded
+        assert!(registered_for_round(None, round_start));
+    }
+
+    #[test]
+    fn nonce_inside_range_is_valid() {
+        let (left_bound, right_bound) = nonce_range(2, 4);
+        assert!(is_valid_nonce(left_bound, 2, 4));
+        assert!(is_valid_nonce(right_bound - 1, 2, 4));
+    }
+
+    #[test]
+    fn nonce_outside_range_is_invalid() {
+        let (left_bound, right_bound) = nonce_range(2, 4);
+        assert!(!is_valid_nonce(left_bound - 1, 2, 4));
+        assert!(!is_valid_nonce(right_bound, 2, 4));
+    }
+
+    #[test]
+    fn assigned_nonce_ranges_partition_by_member() {
+        let ranges: Vec<(u64, u64)> = (0..4).map(|i| assigned_nonce_range(i, 4)).collect();
+        assert_eq!(ranges[0].0, 0);
+        for (i, window) in ranges.windows(2).enumerate() {
+            assert_eq!(window[0].1, window[1].0);
+            assert!(is_valid_nonce(window[0].0, i as u64, 4));
+            assert!(!is_valid_nonce(window[0].1, i as u64, 4));
+        }
+        assert_eq!(assigned_nonce_range(2, 4), assigned_nonce_range(2, 4));
+        assert_eq!(assigned_nonce_range(2, 0), (0, u64::MAX));
+    }
+
+    #[test]
+    fn parses_days_since_epoch() {
+        assert_eq!(parse_day("1970-01-01"), Some(0));
+        assert_eq!(parse_day("2024-03-01"), Some(19_783));
+        assert_eq!(parse_day("2024-02-29"), Some(19_782));
+        assert_eq!(parse_day("2023-02-29"), None);
+        assert_eq!(parse_day("2024-13-01"), None);
+        assert_eq!(parse_day("2024-1-1"), None);
+        assert_eq!(parse_day("2024/01/01"), None);
+    }
+
+    #[test]
+    fn daily_range_is_capped() {
+        assert_eq!(day_span("2024-01-01", "2024-01-01").unwrap(), 1);
+        assert_eq!(day_span("2024-01-01", "2024-12-31").unwrap(), 366);
+        assert!(day_span("2024-01-01", "2025-01-01").is_err());
+        assert!(day_span("2024-01-02", "2024-01-01").is_err());
+        assert!(day_span("2024-01-01", "tomorrow").is_err());
     }
 }
@@ -0,0 +1,133 @@
+/// The max difficulty tracked per round,
+/// higher difficulties are counted in the last bucket.
+const MAX_TRACKED_DIFFICULTY: u32 = 64;
+
+/// Counts the contributions of the current round by difficulty,
+/// one bucket per difficulty so that memory stays bounded however many members contribute.
+#[derive(Debug, Default)]
+pub struct DifficultyHistogram {
+    /// The number of contributions per difficulty.
+    counts: Vec<u64>,
+
+    /// The number of contributions counted.
+    total: u64,
+}
+
+/// A summary of the difficulties contributed in a round.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DifficultySummary {
+    pub min: u32,
+    pub median: u32,
+    pub p90: u32,
+    pub max: u32,
+}
+
+impl DifficultyHistogram {
+    /// Counts a contribution, returning its bucket and the bucket's new count.
+    pub fn record(&mut self, difficulty: u32) -> (u32, u64) {
+        let bucket = difficulty.min(MAX_TRACKED_DIFFICULTY);
+        let index = bucket as usize;
+        if self.counts.len() <= index {
+            self.counts.resize(index + 1, 0);
+        }
+        self.counts[index] = self.counts[index].saturating_add(1);
+        self.total = self.total.saturating_add(1);
+        (bucket, self.counts[index])
+    }
+
+    /// Uncounts a replaced contribution, returning its bucket and the bucket's new count.
+    pub fn remove(&mut self, difficulty: u32) -> (u32, u64) {
+        let bucket = difficulty.min(MAX_TRACKED_DIFFICULTY);
+        match self.counts.get_mut(bucket as usize) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                self.total -= 1;
+                (bucket, *count)
+            }
+            _ => (bucket, 0),
+        }
+    }
+
+    /// The lowest difficulty that at least the percentage of contributions are at or below.
+    pub fn percentile(&self, pct: u64) -> Option<u32> {
+        if self.total == 0 {
+            return None;
+        }
+        // nearest rank
+        let rank = (self.total as u128 * pct.min(100) as u128)
+            .div_ceil(100)
+            .max(1) as u64;
+        let mut counted = 0u64;
+        for (bucket, count) in self.counts.iter().enumerate() {
+            counted = counted.saturating_add(*count);
+            if counted >= rank {
+                return Some(bucket as u32);
+            }
+        }
+        None
+    }
+
+    /// Summarizes the difficulties counted, None if there are none.
+    pub fn summary(&self) -> Option<DifficultySummary> {
+        Some(DifficultySummary {
+            min: self.percentile(0)?,
+            median: self.percentile(50)?,
+            p90: self.percentile(90)?,
+            max: self.percentile(100)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn histogram(difficulties: &[u32]) -> DifficultyHistogram {
+        let mut histogram = DifficultyHistogram::default();
+        for difficulty in difficulties {
+            histogram.record(*difficulty);
+        }
+        histogram
+    }
+
+    #[test]
+    fn empty_histogram_has_no_summary() {
+        assert_eq!(DifficultyHistogram::default().summary(), None);
+    }
+
+    #[test]
+    fn summary_by_nearest_rank() {
+        let histogram = histogram(&[8, 8, 9, 9, 9, 10, 11, 12, 14, 20]);
+        assert_eq!(
+            histogram.summary(),
+            Some(DifficultySummary {
+                min: 8,
+                median: 9,
+                p90: 14,
+                max: 20,
+            })
+        );
+    }
+
+    #[test]
+    fn replaced_contribution_is_uncounted() {
+        let mut histogram = histogram(&[8, 10]);
+        assert_eq!(histogram.remove(8), (8, 0));
+        assert_eq!(histogram.record(12), (12, 1));
+        let summary = histogram.summary().unwrap();
+        assert_eq!((summary.min, summary.max), (10, 12));
+        // nothing to uncount
+        assert_eq!(histogram.remove(30), (30, 0));
+        assert_eq!(histogram.summary().unwrap().min, 10);
+    }
+
+    #[test]
+    fn high_difficulties_share_the_last_bucket() {
+        let mut histogram = histogram(&[70]);
+        assert_eq!(
+            histogram.record(MAX_TRACKED_DIFFICULTY + 1),
+            (MAX_TRACKED_DIFFICULTY, 2)
+        );
+        assert_eq!(histogram.summary().unwrap().max, MAX_TRACKED_DIFFICULTY);
+    }
+}
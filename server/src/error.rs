@@ -1,4 +1,8 @@
-use actix_web::{http::header::ToStrError, HttpResponse};
+use actix_web::{
+    http::{header::ToStrError, StatusCode},
+    HttpRequest, HttpResponse, ResponseError,
+};
+use serde::Serialize;
 
 use crate::webhook;
 
@@ -11,7 +15,7 @@ pub enum Error {
     #[error("try from slice")]
     TryFromSlice(#[from] std::array::TryFromSliceError),
     #[error("rewards channel send")]
-    RewardsChannelSend(#[from] tokio::sync::mpsc::error::SendError<webhook::Rewards>),
+    RewardsChannelSend(Box<tokio::sync::mpsc::error::SendError<webhook::Rewards>>),
     #[error("tokio postgres")]
     TokioPostgres(#[from] tokio_postgres::Error),
     #[error("deadpool postgress")]
@@ -24,8 +28,8 @@ pub enum Error {
     SerdeJson(#[from] serde_json::Error),
     #[error("std io")]
     StdIO(#[from] std::io::Error),
-    #[error("Environment variable not set: {0}")]
-    StdEnv(String, #[source] std::env::VarError),
+    #[error("invalid config: {}", .0.join(", "))]
+    Config(Vec<String>),
     #[error("std parse int")]
     StdParseInt(#[from] std::num::ParseIntError),
     #[error("solana client")]
@@ -34,10 +38,46 @@ pub enum Error {
     SolanaProgram(#[from] solana_sdk::program_error::ProgramError),
     #[error("solana pubkey")]
     SolanaPubkey(#[from] solana_sdk::pubkey::ParsePubkeyError),
+    #[error("s3 put object")]
+    S3PutObject(
+        #[from] aws_sdk_s3::error::SdkError<aws_sdk_s3::operation::put_object::PutObjectError>,
+    ),
+    #[error("s3 get object")]
+    S3GetObject(
+        #[from] aws_sdk_s3::error::SdkError<aws_sdk_s3::operation::get_object::GetObjectError>,
+    ),
+    #[error("s3 byte stream")]
+    S3ByteStream(#[from] aws_sdk_s3::primitives::ByteStreamError),
+    #[error("exhausted retries: {0}")]
+    RetriesExhausted(String),
+    #[error("non-retryable: {0}")]
+    NonRetryable(String),
+    #[error("rpc node is lagging, slot not advancing past {0}")]
+    StaleRpc(u64),
+    #[error("submit for round {0} not settled at the rewards commitment")]
+    SubmitUnsettled(i64),
     #[error("member doesn't exist yet")]
     MemberDoesNotExist,
     #[error("staker doesn't exist yet")]
     StakerDoesNotExist,
+    #[error("member has not deregistered on-chain")]
+    MemberNotDeregistered,
+    #[error("member has deregistered")]
+    MemberInactive,
+    #[error("member registered after the round started")]
+    RegisteredMidRound,
+    #[error("authority is not allowed in this pool")]
+    AccessDenied,
+    #[error("pool is full")]
+    PoolFull,
+    #[error("unauthorized")]
+    Unauthorized,
+    #[error("invalid authority: {0}")]
+    InvalidAuthority(String),
+    #[error("invalid nonce from client")]
+    InvalidNonce,
+    #[error("invalid request: {0}")]
+    InvalidRequest(String),
     #[error("share account received")]
     ShareAccountReceived,
     #[error("proof account received")]
@@ -46,14 +86,115 @@ pub enum Error {
     Internal(String),
 }
 
+impl From<tokio::sync::mpsc::error::SendError<webhook::Rewards>> for Error {
+    fn from(err: tokio::sync::mpsc::error::SendError<webhook::Rewards>) -> Self {
+        Self::RewardsChannelSend(Box::new(err))
+    }
+}
+
+/// Stable, machine-readable codes returned in the body of every error response.
+/// Clients can branch on these, e.g. to retry `server_busy` but not `bad_signature`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    RateLimited,
+    Jailed,
+    BadSignature,
+    BelowDifficulty,
+    BelowMinScore,
+    InvalidDigest,
+    InvalidNonce,
+    Superseded,
+    DuplicateSolution,
+    MemberInactive,
+    RegisteredMidRound,
+    AccessDenied,
+    PoolPaused,
+    MemberNotFound,
+    StakerNotFound,
+    MemberNotDeregistered,
+    PoolFull,
+    InvalidAuthority,
+    InvalidRequest,
+    NotFound,
+    Unauthorized,
+    ServerBusy,
+    Internal,
+}
+
+/// The json body of every error response.
+#[derive(Debug, Serialize)]
+pub struct ErrorBody {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl ErrorCode {
+    /// Builds an error response with this code in the body.
+    pub fn response(self, status: StatusCode, message: impl Into<String>) -> HttpResponse {
+        HttpResponse::build(status).json(ErrorBody {
+            code: self,
+            message: message.into(),
+        })
+    }
+}
+
+impl Error {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Error::MemberDoesNotExist => ErrorCode::MemberNotFound,
+            Error::StakerDoesNotExist => ErrorCode::StakerNotFound,
+            Error::MemberNotDeregistered => ErrorCode::MemberNotDeregistered,
+            Error::MemberInactive => ErrorCode::MemberInactive,
+            Error::RegisteredMidRound => ErrorCode::RegisteredMidRound,
+            Error::AccessDenied => ErrorCode::AccessDenied,
+            Error::PoolFull => ErrorCode::PoolFull,
+            Error::Unauthorized => ErrorCode::Unauthorized,
+            Error::InvalidAuthority(_) => ErrorCode::InvalidAuthority,
+            Error::InvalidNonce => ErrorCode::InvalidNonce,
+            Error::InvalidRequest(_) => ErrorCode::InvalidRequest,
+            _ => ErrorCode::Internal,
+        }
+    }
+}
+
 impl From<Error> for HttpResponse {
     fn from(value: Error) -> Self {
-        match value {
-            Error::MemberDoesNotExist | Error::StakerDoesNotExist => {
-                HttpResponse::NotFound().finish()
-            }
-            Error::ShareAccountReceived => HttpResponse::Ok().finish(),
-            _ => HttpResponse::InternalServerError().finish(),
-        }
+        let status = match value {
+            Error::MemberDoesNotExist | Error::StakerDoesNotExist => StatusCode::NOT_FOUND,
+            Error::MemberNotDeregistered
+            | Error::InvalidAuthority(_)
+            | Error::InvalidNonce
+            | Error::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            Error::MemberInactive | Error::Unauthorized => StatusCode::UNAUTHORIZED,
+            Error::RegisteredMidRound | Error::AccessDenied => StatusCode::FORBIDDEN,
+            Error::PoolFull => StatusCode::CONFLICT,
+            Error::ShareAccountReceived => return HttpResponse::Ok().finish(),
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        value.code().response(status, value.to_string())
+    }
+}
+
+/// Error handler for the json, path, and query extractors,
+/// so that malformed requests get the same error body as everything else.
+pub fn invalid_request<E: ResponseError + 'static>(err: E, _req: &HttpRequest) -> actix_web::Error {
+    let response = ErrorCode::InvalidRequest.response(err.status_code(), err.to_string());
+    actix_web::error::InternalError::from_response(err, response).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_body_serializes_snake_case_code() {
+        let body = ErrorBody {
+            code: ErrorCode::BelowMinScore,
+            message: "score 1 below min score 2".to_string(),
+        };
+        let json = serde_json::to_value(body).unwrap();
+        assert_eq!(json["code"], "below_min_score");
+        assert_eq!(json["message"], "score 1 below min score 2");
     }
 }
@@ -0,0 +1,184 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use dashmap::DashMap;
+use solana_sdk::pubkey::Pubkey;
+
+/// The number of invalid submissions within the window before a member is jailed.
+/// More than one, so that a single stale submission racing a new challenge is forgiven.
+pub const DEFAULT_JAIL_THRESHOLD: usize = 5;
+
+/// The sliding window (in seconds) over which invalid submissions are counted.
+pub const DEFAULT_JAIL_WINDOW_SECS: u64 = 60;
+
+/// How long (in seconds) a jailed member is rejected for.
+pub const DEFAULT_JAIL_COOLDOWN_SECS: u64 = 600;
+
+/// How often (in seconds) records with no recent activity are pruned.
+const PRUNE_SECS: u64 = 60;
+
+/// Tracks invalid submissions per member authority
+/// and jails members that exceed the threshold, shared across http workers.
+pub struct Jail {
+    records: DashMap<Pubkey, Record>,
+    threshold: usize,
+    window: Duration,
+    cooldown: Duration,
+}
+
+#[derive(Default)]
+struct Record {
+    /// the times of invalid submissions within the window
+    strikes: VecDeque<Instant>,
+    /// set while the member is jailed
+    jailed_until: Option<Instant>,
+}
+
+impl Jail {
+    pub fn new(threshold: usize, window: Duration, cooldown: Duration) -> Self {
+        log::info!(
+            "jail threshold: {} window: {:?} cooldown: {:?}",
+            threshold,
+            window,
+            cooldown
+        );
+        Self {
+            records: DashMap::new(),
+            threshold,
+            window,
+            cooldown,
+        }
+    }
+
+    /// Whether or not the member is currently jailed.
+    pub fn is_jailed(&self, authority: &Pubkey) -> bool {
+        self.is_jailed_at(authority, Instant::now())
+    }
+
+    fn is_jailed_at(&self, authority: &Pubkey, now: Instant) -> bool {
+        self.records
+            .get(authority)
+            .and_then(|record| record.jailed_until)
+            .is_some_and(|until| now < until)
+    }
+
+    /// Records an invalid submission, jailing the member if it exceeds the threshold.
+    pub fn strike(&self, authority: &Pubkey) {
+        self.strike_at(authority, Instant::now())
+    }
+
+    fn strike_at(&self, authority: &Pubkey, now: Instant) {
+        let mut record = self.records.entry(*authority).or_default();
+        // expire the previous sentence
+        if record.jailed_until.is_some_and(|until| now >= until) {
+            record.jailed_until = None;
+        }
+        // slide the window
+        while record
+            .strikes
+            .front()
+            .is_some_and(|strike| now.saturating_duration_since(*strike) > self.window)
+        {
+            record.strikes.pop_front();
+        }
+        record.strikes.push_back(now);
+        if record.strikes.len() >= self.threshold {
+            log::error!("jailing member: {:?}", authority);
+            record.strikes.clear();
+            record.jailed_until = Some(now + self.cooldown);
+        }
+    }
+
+    /// The currently jailed members and the seconds remaining on their sentence.
+    /// Also prunes records with no recent activity.
+    pub fn jailed(&self) -> Vec<(Pubkey, u64)> {
+        let now = Instant::now();
+        self.prune(now);
+        self.records
+            .iter()
+            .filter_map(|entry| {
+                entry
+                    .jailed_until
+                    .filter(|until| now < *until)
+                    .map(|until| (*entry.key(), until.duration_since(now).as_secs()))
+            })
+            .collect()
+    }
+
+    /// Removes records that are neither jailed nor have a strike within the window.
+    fn prune(&self, now: Instant) {
+        let window = self.window;
+        self.records.retain(|_, record| {
+            record.jailed_until.is_some_and(|until| now < until)
+                || record
+                    .strikes
+                    .back()
+                    .is_some_and(|strike| now.saturating_duration_since(*strike) <= window)
+        });
+    }
+
+    /// Prunes idle records on an interval, off the request path.
+    pub async fn run(&self) {
+        let mut interval = tokio::time::interval(Duration::from_secs(PRUNE_SECS));
+        loop {
+            interval.tick().await;
+            self.prune(Instant::now());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_jail() -> Jail {
+        Jail::new(3, Duration::from_secs(60), Duration::from_secs(600))
+    }
+
+    #[test]
+    fn single_stale_submission_is_not_jailed() {
+        let jail = test_jail();
+        let authority = Pubkey::new_unique();
+        let start = Instant::now();
+        // one stale submission per challenge
+        for i in 0..10 {
+            let now = start + Duration::from_secs(61 * i);
+            jail.strike_at(&authority, now);
+            assert!(!jail.is_jailed_at(&authority, now));
+        }
+    }
+
+    #[test]
+    fn repeated_invalid_submissions_are_jailed_until_cooldown() {
+        let jail = test_jail();
+        let authority = Pubkey::new_unique();
+        let start = Instant::now();
+        for i in 0..3 {
+            jail.strike_at(&authority, start + Duration::from_secs(i));
+        }
+        assert!(jail.is_jailed_at(&authority, start + Duration::from_secs(3)));
+        assert!(!jail.is_jailed_at(&authority, start + Duration::from_secs(603)));
+    }
+
+    #[test]
+    fn idle_records_are_pruned() {
+        let jail = test_jail();
+        let (struck, jailed) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let start = Instant::now();
+        jail.strike_at(&struck, start);
+        for i in 0..3 {
+            jail.strike_at(&jailed, start + Duration::from_secs(i));
+        }
+        // kept while the strike is within the window
+        jail.prune(start + Duration::from_secs(60));
+        assert_eq!(jail.records.len(), 2);
+        // and while jailed
+        jail.prune(start + Duration::from_secs(61));
+        assert_eq!(jail.records.len(), 1);
+        assert!(jail.is_jailed_at(&jailed, start + Duration::from_secs(61)));
+        jail.prune(start + Duration::from_secs(603));
+        assert!(jail.records.is_empty());
+    }
+}
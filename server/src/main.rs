@@ -1,49 +1,136 @@
+mod access;
+mod admin;
 mod aggregator;
+mod arrival;
+mod attestation;
+mod breaker;
+mod compound;
+mod config;
+mod contributions_hash;
 mod contributor;
 mod database;
+mod difficulty;
 mod error;
+mod jail;
+mod metrics;
+mod notify;
 mod operator;
+mod rate_limit;
+mod request_id;
+mod rpc;
+mod scoring;
+mod signer;
+mod stake;
+mod tls;
 mod tx;
 mod utils;
+mod validation;
 mod webhook;
 
 use core::panic;
-use std::{collections::HashMap, sync::Arc};
+use std::sync::Arc;
 
-use actix_web::{get, middleware, web, App, HttpResponse, HttpServer, Responder};
-use aggregator::{Aggregator, Contribution, Stakers};
+use actix_web::{
+    dev::Service, get, http::header::HeaderName, middleware, web, App, HttpResponse, HttpServer,
+    Responder,
+};
+use aggregator::{Aggregator, Contribution, Forwarded};
+use futures::FutureExt;
 use operator::Operator;
-use utils::{create_cors, try_env_var};
+use ore_pool_types::{Health, HealthQuery, Version};
+use solana_sdk::pubkey::Pubkey;
+use tracing::Instrument;
+use utils::{create_cors, AllowedOrigins};
+
+/// How long the deep health check waits on each dependency.
+const HEALTH_TIMEOUT_MS: u64 = 2_000;
+
+/// How often (in seconds) submitted rounds are checked for overdue rewards.
+const REWARDS_SWEEP_SECS: u64 = 30;
+
+/// Logs human-readable text by default, or with LOG_FORMAT=json,
+/// one json object per line with the timestamp, level, target, message, event fields,
+/// and the fields of the enclosing spans, for log aggregators.
+/// Filtered by RUST_LOG as usual either way.
+fn init_logging(log_format: config::LogFormat) {
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env());
+    match log_format {
+        config::LogFormat::Json => builder.json().flatten_event(true).init(),
+        config::LogFormat::Text => builder.init(),
+    }
+}
 
-// TODO: publish attestation to s3
-// write attestation url to db with last-hash-at as foreign key
 #[actix_web::main]
 async fn main() -> Result<(), error::Error> {
-    env_logger::init();
+    // load and validate the config up front,
+    // reporting every missing or invalid var at once
+    let config = config::Config::from_env()?;
+    // initialize logging exactly once, before any worker is started
+    init_logging(config.log_format);
     // rewards channel
     let (rewards_tx, mut rewards_rx) = tokio::sync::mpsc::channel::<webhook::Rewards>(1);
     let rewards_tx = web::Data::new(rewards_tx);
     // contributions channel
-    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Contribution>();
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<Forwarded>(config.contributions_capacity);
     let tx = web::Data::new(tx);
     // operator and aggregator mutex
-    let operator = web::Data::new(Operator::new()?);
-    let aggregator = tokio::sync::RwLock::new(Aggregator::new(&operator).await?);
+    let operator = web::Data::new(Operator::new(&config)?);
+    let aggregator = tokio::sync::RwLock::new(Aggregator::new(&operator, &config).await?);
     let aggregator = web::Data::new(aggregator);
-    let webhook_handler = web::Data::new(webhook::Handle::new()?);
-    let webhook_client = web::Data::new(webhook::Client::new_stake()?);
+    let webhook_handler = web::Data::new(webhook::Handle::new(&config.helius));
+    let webhook_client = web::Data::new(webhook::Client::new_stake(&config.helius));
+    let rate_limiter = web::Data::new(rate_limit::RateLimiter::new(config.contribute_rate_per_sec));
+    let jail = web::Data::new(jail::Jail::new(
+        config.jail_threshold,
+        config.jail_window,
+        config.jail_cooldown,
+    ));
+    let access = web::Data::new(access::Access::new(
+        config.allowlist_only,
+        config.access_refresh,
+    ));
+    access.refresh(&operator.db_client).await?;
+    let validator = web::Data::new(validation::Validator::new(
+        config.validation_workers,
+        config.score_curve,
+    ));
+    let admin_auth = web::Data::new(admin::Auth::new(config.admin_token.clone()));
+    let stake_refresh = Arc::new(stake::Refresh::new(&config));
+    let notifier = web::Data::new(notify::Notifier::default());
+    let ws_connections = web::Data::new(contributor::WsConnections::new(config.max_ws_connections));
     // env vars
-    let attribution_epoch = attribution_epoch()?;
-    let stake_commit_epoch = stake_commit_epoch()?;
+    let attribution_epoch = config.attribution_epoch;
+    let stake_commit_epoch = config.stake_commit_epoch;
+    let allowed_origins = config.allowed_origins.clone();
+    match &allowed_origins {
+        AllowedOrigins::Any => log::info!("dev cors enabled, allowing any origin"),
+        AllowedOrigins::List(origins) if origins.is_empty() => {
+            log::info!("allowed origins not configured, denying cross-origin requests")
+        }
+        AllowedOrigins::List(origins) => log::info!("allowed origins: {:?}", origins),
+    }
+    log::info!("score curve: {:?}", config.score_curve);
+    let auto_compound = compound::AutoCompound::new(config.auto_compound_threshold);
+    log::info!("auto compound: {}", auto_compound.is_some());
+
+    // shutdown signals
+    let contributions_shutdown = Arc::new(tokio::sync::Notify::new());
+    let rewards_shutdown = Arc::new(tokio::sync::Notify::new());
 
     // aggregate contributions
-    tokio::task::spawn({
+    let contributions_handle = tokio::task::spawn({
         let operator = operator.clone();
         let aggregator = aggregator.clone();
+        let shutdown = contributions_shutdown.clone();
         async move {
-            if let Err(err) =
-                aggregator::process_contributions(aggregator.as_ref(), operator.as_ref(), &mut rx)
-                    .await
+            if let Err(err) = aggregator::process_contributions(
+                aggregator.as_ref(),
+                operator.as_ref(),
+                &mut rx,
+                shutdown.as_ref(),
+            )
+            .await
             {
                 log::error!("{:?}", err);
             }
@@ -51,12 +138,34 @@ async fn main() -> Result<(), error::Error> {
     });
 
     // distribute rewards
-    tokio::task::spawn({
+    let rewards_handle = tokio::task::spawn({
         let operator = operator.clone();
         let aggregator = aggregator.clone();
+        let shutdown = rewards_shutdown.clone();
         async move {
+            let mut sweep =
+                tokio::time::interval(tokio::time::Duration::from_secs(REWARDS_SWEEP_SECS));
             loop {
-                match rewards_rx.recv().await {
+                let rewards = tokio::select! {
+                    rewards = rewards_rx.recv() => rewards,
+                    _ = sweep.tick() => {
+                        // expire rounds whose rewards webhook never arrived
+                        let mut aggregator = aggregator.write().await;
+                        if let Err(err) = aggregator
+                            .expire_pending_rewards(operator.as_ref())
+                            .await
+                        {
+                            log::error!("{:?}", err);
+                        }
+                        continue;
+                    }
+                    _ = shutdown.notified() => {
+                        // drain rewards already received before exiting
+                        rewards_rx.close();
+                        rewards_rx.recv().await
+                    }
+                };
+                match rewards {
                     Some(rewards) => {
                         let mut aggregator = aggregator.write().await;
                         if let Err(err) = aggregator
@@ -67,128 +176,470 @@ async fn main() -> Result<(), error::Error> {
                         }
                     }
                     None => {
-                        panic!("rewards channel closed")
+                        log::info!("rewards channel closed");
+                        break;
                     }
                 };
             }
         }
     });
 
-    // kick off attribution loop
+    // kick off attribution loop,
+    // for all members and the stakers of each boost with its own epoch.
+    // the members attribution also auto-compounds, if enabled,
+    // once their attributed balances have landed
+    let boost_epochs = operator
+        .boost_accounts
+        .iter()
+        .filter_map(|ba| ba.attribution_epoch.map(|epoch| (ba.mint, epoch)))
+        .collect::<Vec<_>>();
+    for (mint, epoch) in boost_epochs.iter() {
+        log::info!("boost attribution epoch: {} {}", mint, epoch);
+    }
+    spawn_attribution_loop(
+        operator.clone(),
+        attribution_epoch,
+        boost_epochs,
+        auto_compound,
+    );
+
+    // kick off commit-stake loop
     tokio::task::spawn({
         let operator = operator.clone();
+        let stake_refresh = stake_refresh.clone();
         async move {
             loop {
-                // submit attributions
-                let operator = operator.clone().into_inner();
-                if let Err(err) = operator.attribute_members().await {
-                    panic!("{:?}", err)
+                // commit stake,
+                // which moves the staker balances
+                match operator.commit_stake().await {
+                    Ok(()) => stake_refresh.trigger(),
+                    Err(err) => log::error!("{:?}", err),
                 }
                 // sleep until next epoch
-                tokio::time::sleep(tokio::time::Duration::from_secs(60 * attribution_epoch)).await;
+                tokio::time::sleep(tokio::time::Duration::from_secs(60 * stake_commit_epoch)).await;
             }
         }
     });
 
-    // kick off commit-stake loop
+    // kick off staker balances refresh loop
     tokio::task::spawn({
         let operator = operator.clone();
         let aggregator = aggregator.clone();
+        let stake_refresh = stake_refresh.clone();
         async move {
-            loop {
-                let operator = operator.clone().into_inner();
-                let aggregator = aggregator.clone().into_inner();
-                // commit stake
-                if let Err(err) = commit_stake(operator, aggregator).await {
-                    log::error!("{:?}", err);
+            stake_refresh
+                .run(operator.as_ref(), aggregator.as_ref())
+                .await;
+        }
+    });
+
+    // prune idle rate limit buckets
+    tokio::task::spawn({
+        let rate_limiter = rate_limiter.clone();
+        async move {
+            rate_limiter.run().await;
+        }
+    });
+
+    // prune idle jail records
+    tokio::task::spawn({
+        let jail = jail.clone();
+        async move {
+            jail.run().await;
+        }
+    });
+
+    // refresh the access lists from the db
+    tokio::task::spawn({
+        let operator = operator.clone();
+        let access = access.clone();
+        async move {
+            access.run(&operator.db_client).await;
+        }
+    });
+
+    // forward balance updates from the db to member websockets
+    tokio::task::spawn({
+        let notifier = notifier.clone();
+        let db_url = config.db_url.clone();
+        async move {
+            notifier.run(db_url.as_str()).await;
+        }
+    });
+
+    // reload the operator config on SIGHUP,
+    // picked up by the aggregator from the next round
+    let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())?;
+    tokio::task::spawn({
+        let operator = operator.clone();
+        async move {
+            while hangup.recv().await.is_some() {
+                log::info!("received SIGHUP, reloading config");
+                if let Err(err) = operator.reload() {
+                    log::error!("failed to reload config, keeping current: {:?}", err);
                 }
-                // sleep until next epoch
-                tokio::time::sleep(tokio::time::Duration::from_secs(60 * stake_commit_epoch)).await;
             }
         }
     });
 
     // launch server
-    HttpServer::new(move || {
-        log::info!("starting server");
+    let bind_addr = config.bind_addr.clone();
+    let tls_config = config.tls.as_ref().map(tls::server_config).transpose()?;
+    log::info!("starting server");
+    // the factory runs once per worker,
+    // so it only builds the app
+    let server = HttpServer::new(move || {
+        log::info!("starting worker");
         App::new()
             .wrap(middleware::Logger::default())
-            .wrap(create_cors())
+            // assign each request an id and run it in a span carrying the id,
+            // so its logs can be correlated through to submit and attribution
+            .wrap_fn(|req, srv| {
+                let request_id = request_id::assign(&req);
+                let span = tracing::info_span!("request", request_id = %request_id);
+                srv.call(req).instrument(span).map(move |res| {
+                    res.map(|mut res| {
+                        res.headers_mut().insert(
+                            HeaderName::from_static(request_id::HEADER),
+                            request_id.header_value(),
+                        );
+                        res
+                    })
+                })
+            })
+            .wrap(create_cors(&allowed_origins))
             .app_data(tx.clone())
             .app_data(operator.clone())
             .app_data(aggregator.clone())
             .app_data(webhook_handler.clone())
             .app_data(webhook_client.clone())
             .app_data(rewards_tx.clone())
-            .service(web::resource("/member/{authority}").route(web::get().to(contributor::member)))
-            .service(web::resource("/pool-address").route(web::get().to(contributor::pool_address)))
-            .service(web::resource("/register").route(web::post().to(contributor::register)))
-            .service(
-                web::resource("/register-staker")
-                    .route(web::post().to(contributor::register_staker)),
-            )
-            .service(web::resource("/contribute").route(web::post().to(contributor::contribute)))
-            .service(web::resource("/challenge").route(web::get().to(contributor::challenge)))
-            .service(
-                web::resource("/update-balance").route(web::post().to(contributor::update_balance)),
-            )
-            .service(
-                web::resource("/webhook/share-account")
-                    .route(web::post().to(webhook::Handle::share_account)),
-            )
-            .service(
-                web::resource("/webhook/rewards").route(web::post().to(webhook::Handle::rewards)),
-            )
-            .service(health)
-    })
-    .bind(format!("0.0.0.0:{}", get_port()))?
-    .run()
-    .await
-    .map_err(From::from)
+            .app_data(rate_limiter.clone())
+            .app_data(jail.clone())
+            .app_data(access.clone())
+            .app_data(notifier.clone())
+            .app_data(ws_connections.clone())
+            .app_data(validator.clone())
+            .app_data(admin_auth.clone())
+            .app_data(web::JsonConfig::default().error_handler(error::invalid_request))
+            .app_data(web::PathConfig::default().error_handler(error::invalid_request))
+            .app_data(web::QueryConfig::default().error_handler(error::invalid_request))
+            .configure(routes)
+    });
+    let server = match tls_config {
+        Some(tls_config) => {
+            log::info!("listening with tls on {}", bind_addr);
+            server.bind_rustls_021(bind_addr.as_str(), tls_config)?
+        }
+        None => {
+            log::info!("listening on {}", bind_addr);
+            server.bind(bind_addr.as_str())?
+        }
+    };
+    server.run().await?;
+
+    // the server has stopped on a shutdown signal,
+    // let any in-progress submit finish before flushing rewards
+    log::info!("shutting down");
+    contributions_shutdown.notify_one();
+    if let Err(err) = contributions_handle.await {
+        log::error!("{:?}", err);
+    }
+    // write any rewards already received to the db
+    rewards_shutdown.notify_one();
+    if let Err(err) = rewards_handle.await {
+        log::error!("{:?}", err);
+    }
+    log::info!("flushed pending rewards");
+    Ok(())
+}
+
+/// Attributes on an independent timer,
+/// all members if no boost mint is given, otherwise only the boost's stakers.
+/// Attributes from a single task, so that the attribution of all members
+/// and of the stakers of a boost never run at the same time.
+fn spawn_attribution_loop(
+    operator: web::Data<Operator>,
+    epoch: u64,
+    boost_epochs: Vec<(Pubkey, u64)>,
+    auto_compound: Option<compound::AutoCompound>,
+) {
+    tokio::task::spawn(async move {
+        let mut schedule =
+            AttributionSchedule::new(epoch, boost_epochs, tokio::time::Instant::now());
+        loop {
+            for mint in schedule.due(tokio::time::Instant::now()) {
+                // submit attributions
+                let operator = operator.clone().into_inner();
+                let res = match mint {
+                    None => operator.clone().attribute_members().await,
+                    Some(mint) => operator.clone().attribute_stakers(mint).await,
+                };
+                if let Err(err) = res {
+                    panic!("{:?}", err)
+                }
+                // restake the rewards of the members that opted in
+                if mint.is_none() {
+                    if let Some(auto_compound) = auto_compound.as_ref() {
+                        if let Err(err) = auto_compound.run(operator.as_ref()).await {
+                            log::error!("{:?}", err);
+                        }
+                    }
+                }
+                schedule.attributed(mint, tokio::time::Instant::now());
+            }
+            // sleep until the next attribution is due
+            tokio::time::sleep_until(schedule.next_due()).await;
+        }
+    });
 }
 
-fn get_port() -> u16 {
-    try_env_var("PORT")
-        .ok()
-        .and_then(|p| p.parse().ok())
-        .unwrap_or(8080)
+/// When the attribution of all members, and of the stakers of each boost
+/// with its own epoch, is next due. Epochs are in minutes.
+struct AttributionSchedule {
+    epoch: tokio::time::Duration,
+    members_due: tokio::time::Instant,
+    boosts: Vec<(Pubkey, tokio::time::Duration, tokio::time::Instant)>,
 }
 
-async fn commit_stake(
-    operator: Arc<Operator>,
-    aggregator: Arc<tokio::sync::RwLock<Aggregator>>,
-) -> Result<(), error::Error> {
-    // commit stake
-    operator.commit_stake().await?;
-    // lock aggregator
-    let aggregator = &mut aggregator.write().await;
-    // update staker balances
-    let mut stake: Stakers = HashMap::new();
-    let boost_accounts = operator.boost_accounts.iter();
-    for ba in boost_accounts {
-        let stakers = operator.get_stakers_onchain(&ba.mint).await?;
-        stake.insert(ba.mint, stakers);
+impl AttributionSchedule {
+    /// All members are due right away, which covers the stakers of every boost.
+    fn new(epoch: u64, boost_epochs: Vec<(Pubkey, u64)>, now: tokio::time::Instant) -> Self {
+        let minutes = |epoch: u64| tokio::time::Duration::from_secs(60 * epoch);
+        Self {
+            epoch: minutes(epoch),
+            members_due: now,
+            boosts: boost_epochs
+                .into_iter()
+                .map(|(mint, epoch)| (mint, minutes(epoch), now + minutes(epoch)))
+                .collect(),
+        }
+    }
+
+    /// The attributions due, None for all members.
+    /// When all members are due, the boosts aren't attributed separately.
+    fn due(&self, now: tokio::time::Instant) -> Vec<Option<Pubkey>> {
+        if now >= self.members_due {
+            return vec![None];
+        }
+        self.boosts
+            .iter()
+            .filter(|(_, _, due)| now >= *due)
+            .map(|(mint, _, _)| Some(*mint))
+            .collect()
+    }
+
+    /// Schedules the next attribution, an epoch after this one finished.
+    /// Attributing all members restarts the epoch of every boost.
+    fn attributed(&mut self, mint: Option<Pubkey>, now: tokio::time::Instant) {
+        for (boost, epoch, due) in self.boosts.iter_mut() {
+            if mint.is_none() || mint == Some(*boost) {
+                *due = now + *epoch;
+            }
+        }
+        if mint.is_none() {
+            self.members_due = now + self.epoch;
+        }
+    }
+
+    fn next_due(&self) -> tokio::time::Instant {
+        self.boosts
+            .iter()
+            .map(|(_, _, due)| *due)
+            .fold(self.members_due, std::cmp::min)
     }
-    // set stakers
-    aggregator.stake = stake;
-    Ok(())
 }
 
-// denominated in minutes
-fn stake_commit_epoch() -> Result<u64, error::Error> {
-    let string = try_env_var("STAKE_EPOCH")?;
-    let epoch: u64 = string.parse()?;
-    Ok(epoch)
+/// Registers the routes.
+/// A literal path must be registered before a path param resource it would otherwise match,
+/// since a resource that matches the path but not the method answers 405
+/// rather than falling through.
+fn routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/member/auto-compound").route(web::post().to(contributor::auto_compound)),
+    )
+    .service(web::resource("/member/{authority}").route(web::get().to(contributor::member)))
+    .service(
+        web::resource("/member/{authority}/history")
+            .route(web::get().to(contributor::member_history)),
+    )
+    .service(
+        web::resource("/member/{authority}/daily").route(web::get().to(contributor::member_daily)),
+    )
+    .service(
+        web::resource("/member/{authority}/pending")
+            .route(web::get().to(contributor::member_pending)),
+    )
+    .service(
+        web::resource("/member/{authority}/nonce-range")
+            .route(web::get().to(contributor::member_nonce_range)),
+    )
+    .service(
+        web::resource("/member/{authority}/balance/ws")
+            .route(web::get().to(contributor::member_balance_ws)),
+    )
+    .service(web::resource("/pool-address").route(web::get().to(contributor::pool_address)))
+    .service(web::resource("/register").route(web::post().to(contributor::register)))
+    .service(web::resource("/deregister").route(web::post().to(contributor::deregister)))
+    .service(web::resource("/register-staker").route(web::post().to(contributor::register_staker)))
+    .service(web::resource("/contribute").route(web::post().to(contributor::contribute)))
+    .service(
+        web::resource("/contribute/batch").route(web::post().to(contributor::contribute_batch)),
+    )
+    .service(web::resource("/challenge").route(web::get().to(contributor::challenge)))
+    .service(web::resource("/challenge/ws").route(web::get().to(contributor::challenge_ws)))
+    .service(
+        web::resource("/challenge/{last_hash_at}")
+            .route(web::get().to(contributor::challenge_history)),
+    )
+    .service(
+        web::resource("/attestation/{last_hash_at}").route(web::get().to(contributor::attestation)),
+    )
+    .service(web::resource("/stats").route(web::get().to(contributor::stats)))
+    .service(web::resource("/leaderboard").route(web::get().to(contributor::leaderboard)))
+    .service(web::resource("/estimate").route(web::get().to(contributor::estimate)))
+    .service(web::resource("/stake").route(web::get().to(contributor::stake)))
+    .service(web::resource("/metrics").route(web::get().to(metrics::handle)))
+    .service(web::resource("/update-balance").route(web::post().to(contributor::update_balance)))
+    .service(
+        web::resource("/webhook/share-account")
+            .route(web::post().to(webhook::Handle::share_account)),
+    )
+    .service(web::resource("/webhook/rewards").route(web::post().to(webhook::Handle::rewards)))
+    .service(web::resource("/admin/jail").route(web::get().to(admin::jailed)))
+    .service(web::resource("/admin/forensics").route(web::get().to(admin::forensics)))
+    .service(web::resource("/admin/timeline/{last_hash_at}").route(web::get().to(admin::timeline)))
+    .service(web::resource("/admin/members").route(web::get().to(admin::members)))
+    .service(web::resource("/admin/force-submit").route(web::post().to(admin::force_submit)))
+    .service(web::resource("/admin/min-difficulty").route(web::post().to(admin::min_difficulty)))
+    .service(web::resource("/admin/resume").route(web::post().to(admin::resume)))
+    .service(
+        web::resource("/admin/access")
+            .route(web::get().to(admin::access))
+            .route(web::put().to(admin::set_access)),
+    )
+    .service(
+        web::resource("/admin/access/{authority}").route(web::delete().to(admin::remove_access)),
+    )
+    .service(health)
+    .service(version);
 }
 
-// denominated in minutes
-fn attribution_epoch() -> Result<u64, error::Error> {
-    let string = try_env_var("ATTR_EPOCH")?;
-    let epoch: u64 = string.parse()?;
-    Ok(epoch)
+/// Reports the server build, the program id, and the cluster of the connected rpc,
+/// for telling deployments apart. Never locks the aggregator.
+#[get("/version")]
+async fn version(operator: web::Data<Operator>) -> impl Responder {
+    let timeout = tokio::time::Duration::from_millis(HEALTH_TIMEOUT_MS);
+    let genesis_hash = match tokio::time::timeout(timeout, operator.get_genesis_hash()).await {
+        Ok(Ok(genesis_hash)) => Some(genesis_hash),
+        Ok(Err(err)) => {
+            log::error!("version genesis hash: {:?}", err);
+            None
+        }
+        Err(_) => {
+            log::error!("version genesis hash: timed out");
+            None
+        }
+    };
+    let version = Version {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_commit: option_env!("GIT_COMMIT").map(str::to_string),
+        program_id: ore_pool_api::id(),
+        genesis_hash,
+        cluster: utils::cluster(genesis_hash.as_ref()).to_string(),
+    };
+    HttpResponse::Ok().json(&version)
 }
 
+/// Liveness check, or with `?deep=true` a readiness check
+/// that pings the rpc and db and responds 503 if either is down.
 #[get("/health")]
-async fn health() -> impl Responder {
-    HttpResponse::Ok().body("ok")
+async fn health(operator: web::Data<Operator>, query: web::Query<HealthQuery>) -> impl Responder {
+    if !query.deep.unwrap_or(false) {
+        return HttpResponse::Ok().body("ok");
+    }
+    let timeout = tokio::time::Duration::from_millis(HEALTH_TIMEOUT_MS);
+    let rpc = async {
+        match tokio::time::timeout(timeout, operator.rpc_client.get_slot()).await {
+            Ok(Ok(_)) => true,
+            Ok(Err(err)) => {
+                log::error!("health check rpc: {:?}", err);
+                false
+            }
+            Err(_) => {
+                log::error!("health check rpc: timed out");
+                false
+            }
+        }
+    };
+    let db = async {
+        let ping = async {
+            let conn = operator.db_client.get().await?;
+            conn.simple_query("SELECT 1").await?;
+            Ok::<(), error::Error>(())
+        };
+        match tokio::time::timeout(timeout, ping).await {
+            Ok(Ok(())) => true,
+            Ok(Err(err)) => {
+                log::error!("health check db: {:?}", err);
+                false
+            }
+            Err(_) => {
+                log::error!("health check db: timed out");
+                false
+            }
+        }
+    };
+    let (rpc, db) = tokio::join!(rpc, db);
+    let health = Health { rpc, db };
+    if rpc && db {
+        HttpResponse::Ok().json(&health)
+    } else {
+        HttpResponse::ServiceUnavailable().json(&health)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        http::StatusCode,
+        test::{call_service, init_service, TestRequest},
+        App,
+    };
+
+    use super::*;
+
+    #[actix_web::test]
+    async fn auto_compound_is_not_shadowed_by_member_lookup() {
+        let app = init_service(App::new().configure(routes)).await;
+        let req = TestRequest::post()
+            .uri("/member/auto-compound")
+            .to_request();
+        let res = call_service(&app, req).await;
+        // routed to the handler, which fails on the app data missing here
+        assert_ne!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_ne!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn attributions_take_turns() {
+        let boost = Pubkey::new_unique();
+        let start = tokio::time::Instant::now();
+        let minutes = |m: u64| start + tokio::time::Duration::from_secs(60 * m);
+        let mut schedule = AttributionSchedule::new(60, vec![(boost, 10)], start);
+        // all members first, covering the boost
+        assert_eq!(schedule.due(start), vec![None]);
+        schedule.attributed(None, start);
+        assert!(schedule.due(start).is_empty());
+        assert_eq!(schedule.next_due(), minutes(10));
+        // then the boost on its own epoch
+        assert_eq!(schedule.due(minutes(10)), vec![Some(boost)]);
+        schedule.attributed(Some(boost), minutes(10));
+        assert_eq!(schedule.next_due(), minutes(20));
+        // the boost isn't attributed again alongside all members
+        schedule.attributed(Some(boost), minutes(55));
+        assert_eq!(schedule.due(minutes(65)), vec![None]);
+        schedule.attributed(None, minutes(65));
+        assert_eq!(schedule.next_due(), minutes(75));
+    }
 }
@@ -0,0 +1,238 @@
+use std::{sync::OnceLock, time::Duration};
+
+use actix_web::{http::StatusCode, HttpResponse, Responder};
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+
+use crate::error::ErrorCode;
+
+/// The reasons a contribution may be rejected.
+#[derive(Clone, Copy, Debug)]
+pub enum Rejection {
+    RateLimited,
+    Jailed,
+    BadSignature,
+    BelowDifficulty,
+    BelowMinScore,
+    InvalidDigest,
+    InvalidNonce,
+    RegisteredMidRound,
+    AccessDenied,
+    Paused,
+    Backpressure,
+    DuplicateSolution,
+}
+
+impl Rejection {
+    fn label(&self) -> &'static str {
+        match self {
+            Rejection::RateLimited => "rate_limited",
+            Rejection::Jailed => "jailed",
+            Rejection::BadSignature => "bad_signature",
+            Rejection::BelowDifficulty => "below_difficulty",
+            Rejection::BelowMinScore => "below_min_score",
+            Rejection::InvalidDigest => "invalid_digest",
+            Rejection::InvalidNonce => "invalid_nonce",
+            Rejection::RegisteredMidRound => "registered_mid_round",
+            Rejection::AccessDenied => "access_denied",
+            Rejection::Paused => "paused",
+            Rejection::Backpressure => "backpressure",
+            Rejection::DuplicateSolution => "duplicate_solution",
+        }
+    }
+}
+
+/// Prometheus registry and the metrics scraped from /metrics.
+/// Gauges are set as the aggregator changes
+/// so that scraping never locks the aggregator.
+struct Metrics {
+    registry: Registry,
+    contributions_accepted: IntCounter,
+    contributions_rejected: IntCounterVec,
+    submits_landed: IntCounter,
+    submits_failed: IntCounter,
+    attestations_unconfirmed: IntCounter,
+    submits_unsettled: IntCounter,
+    total_score: IntGauge,
+    num_members: IntGauge,
+    validation_seconds: Histogram,
+    arrival_seconds: Histogram,
+    adaptive_cutoff_seconds: IntGauge,
+    paused: IntGauge,
+    round_difficulty: IntGaugeVec,
+}
+
+impl Metrics {
+    fn new() -> Result<Self, prometheus::Error> {
+        let registry = Registry::new();
+        let contributions_accepted = IntCounter::new(
+            "contributions_accepted_total",
+            "Contributions accepted from members",
+        )?;
+        let contributions_rejected = IntCounterVec::new(
+            Opts::new(
+                "contributions_rejected_total",
+                "Contributions rejected from members by reason",
+            ),
+            &["reason"],
+        )?;
+        let submits_landed = IntCounter::new("submits_landed_total", "Submit transactions landed")?;
+        let submits_failed = IntCounter::new("submits_failed_total", "Submit transactions failed")?;
+        let attestations_unconfirmed = IntCounter::new(
+            "attestations_unconfirmed_total",
+            "Landed submits whose attestation wasn't read back from the pool account",
+        )?;
+        let submits_unsettled = IntCounter::new(
+            "submits_unsettled_total",
+            "Landed submits not settled at the rewards commitment, e.g. reorged out",
+        )?;
+        let total_score = IntGauge::new("total_score", "Total score of the current challenge")?;
+        let num_members = IntGauge::new("num_members", "Number of members at the last submission")?;
+        let validation_seconds = Histogram::with_opts(
+            HistogramOpts::new(
+                "validation_seconds",
+                "Time to verify a contribution's signature and solution",
+            )
+            .buckets(vec![0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1]),
+        )?;
+        registry.register(Box::new(contributions_accepted.clone()))?;
+        registry.register(Box::new(contributions_rejected.clone()))?;
+        let arrival_seconds = Histogram::with_opts(
+            HistogramOpts::new(
+                "contribution_arrival_seconds",
+                "Seconds into the round that score-raising contributions arrived",
+            )
+            .buckets(vec![5.0, 10.0, 20.0, 30.0, 40.0, 50.0, 55.0, 60.0]),
+        )?;
+        let adaptive_cutoff_seconds = IntGauge::new(
+            "adaptive_cutoff_seconds",
+            "Seconds into the round the adaptive cutoff closes at, 0 if not adapting",
+        )?;
+        let paused = IntGauge::new(
+            "paused",
+            "1 while contributions are paused after repeated submit failures",
+        )?;
+        let round_difficulty = IntGaugeVec::new(
+            Opts::new(
+                "round_contributions_by_difficulty",
+                "Contributions to the current round by difficulty, the highest bucket includes higher difficulties",
+            ),
+            &["difficulty"],
+        )?;
+        registry.register(Box::new(submits_landed.clone()))?;
+        registry.register(Box::new(submits_failed.clone()))?;
+        registry.register(Box::new(attestations_unconfirmed.clone()))?;
+        registry.register(Box::new(submits_unsettled.clone()))?;
+        registry.register(Box::new(total_score.clone()))?;
+        registry.register(Box::new(num_members.clone()))?;
+        registry.register(Box::new(validation_seconds.clone()))?;
+        registry.register(Box::new(arrival_seconds.clone()))?;
+        registry.register(Box::new(adaptive_cutoff_seconds.clone()))?;
+        registry.register(Box::new(paused.clone()))?;
+        registry.register(Box::new(round_difficulty.clone()))?;
+        Ok(Self {
+            registry,
+            contributions_accepted,
+            contributions_rejected,
+            submits_landed,
+            submits_failed,
+            attestations_unconfirmed,
+            submits_unsettled,
+            total_score,
+            num_members,
+            validation_seconds,
+            arrival_seconds,
+            adaptive_cutoff_seconds,
+            paused,
+            round_difficulty,
+        })
+    }
+}
+
+fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(|| Metrics::new().expect("failed to register metrics"))
+}
+
+pub fn contribution_accepted() {
+    metrics().contributions_accepted.inc();
+}
+
+pub fn contribution_rejected(reason: Rejection) {
+    metrics()
+        .contributions_rejected
+        .with_label_values(&[reason.label()])
+        .inc();
+}
+
+pub fn submit_landed() {
+    metrics().submits_landed.inc();
+}
+
+pub fn submit_failed() {
+    metrics().submits_failed.inc();
+}
+
+pub fn attestation_unconfirmed() {
+    metrics().attestations_unconfirmed.inc();
+}
+
+pub fn submit_unsettled() {
+    metrics().submits_unsettled.inc();
+}
+
+pub fn set_total_score(total_score: u64) {
+    metrics().total_score.set(total_score as i64);
+}
+
+pub fn set_num_members(num_members: u64) {
+    metrics().num_members.set(num_members as i64);
+}
+
+pub fn observe_validation(elapsed: Duration) {
+    metrics().validation_seconds.observe(elapsed.as_secs_f64());
+}
+
+pub fn observe_arrival(offset_secs: u64) {
+    metrics().arrival_seconds.observe(offset_secs as f64);
+}
+
+pub fn set_adaptive_cutoff(secs: Option<u64>) {
+    metrics()
+        .adaptive_cutoff_seconds
+        .set(secs.unwrap_or(0) as i64);
+}
+
+pub fn set_paused(paused: bool) {
+    metrics().paused.set(paused as i64);
+}
+
+pub fn set_round_difficulty(difficulty: u32, count: u64) {
+    metrics()
+        .round_difficulty
+        .with_label_values(&[difficulty.to_string().as_str()])
+        .set(count as i64);
+}
+
+/// Clears the difficulty histogram for a new round.
+pub fn reset_round_difficulty() {
+    metrics().round_difficulty.reset();
+}
+
+/// Serves the metrics in the prometheus text format.
+pub async fn handle() -> impl Responder {
+    let encoder = TextEncoder::new();
+    let metric_families = metrics().registry.gather();
+    let mut buffer = vec![];
+    match encoder.encode(&metric_families, &mut buffer) {
+        Ok(()) => HttpResponse::Ok()
+            .content_type(encoder.format_type())
+            .body(buffer),
+        Err(err) => {
+            log::error!("{:?}", err);
+            ErrorCode::Internal.response(StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+        }
+    }
+}
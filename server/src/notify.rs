@@ -0,0 +1,189 @@
+use std::time::Duration;
+
+use dashmap::DashMap;
+use futures::StreamExt;
+use ore_pool_types::BalanceNotification;
+use serde::Deserialize;
+use tokio_postgres::{AsyncMessage, NoTls};
+
+use crate::error::Error;
+
+/// The postgres channel balance updates are notified on,
+/// see [`crate::database::write_all_balances`].
+pub const CHANNEL: &str = "balance_updates";
+
+/// The number of notifications buffered per member before a slow subscriber lags.
+const SUBSCRIBER_CAPACITY: usize = 16;
+
+/// How long (in seconds) to wait before reconnecting the listen connection, doubled per failure.
+const RECONNECT_BASE_SECS: u64 = 1;
+
+/// The max wait (in seconds) between reconnects of the listen connection.
+const RECONNECT_MAX_SECS: u64 = 30;
+
+/// The payload of a balance update notification, as written by the db.
+#[derive(Debug, Deserialize)]
+struct Payload {
+    /// The member account address.
+    address: String,
+    last_hash_at: i64,
+    amount: i64,
+    total_balance: i64,
+}
+
+/// Forwards the balance updates notified by the db to the subscribers of each member,
+/// shared across http workers.
+#[derive(Default)]
+pub struct Notifier {
+    /// The subscribers of each member, keyed by member account address.
+    subscribers: DashMap<String, tokio::sync::broadcast::Sender<BalanceNotification>>,
+}
+
+impl Notifier {
+    /// Subscribes to the balance updates of the member account.
+    pub fn subscribe(
+        &self,
+        address: String,
+    ) -> tokio::sync::broadcast::Receiver<BalanceNotification> {
+        self.subscribers
+            .entry(address)
+            .or_insert_with(|| tokio::sync::broadcast::channel(SUBSCRIBER_CAPACITY).0)
+            .subscribe()
+    }
+
+    /// Drops the member once all of its subscribers have gone,
+    /// called by a subscriber after dropping its receiver.
+    pub fn unsubscribe(&self, address: &str) {
+        self.subscribers
+            .remove_if(address, |_, tx| tx.receiver_count() == 0);
+    }
+
+    /// Forwards a notification to the subscribers of its member, if any,
+    /// dropping the member once all of its subscribers have gone.
+    fn route(&self, payload: &str) {
+        let payload: Payload = match serde_json::from_str(payload) {
+            Ok(payload) => payload,
+            Err(err) => {
+                log::error!("invalid balance notification: {:?} {}", err, payload);
+                return;
+            }
+        };
+        let notification = BalanceNotification {
+            last_hash_at: payload.last_hash_at,
+            amount: payload.amount as u64,
+            total_balance: payload.total_balance as u64,
+        };
+        let delivered = match self.subscribers.get(&payload.address) {
+            Some(tx) => tx.send(notification).is_ok(),
+            None => return,
+        };
+        if !delivered {
+            self.unsubscribe(&payload.address);
+        }
+    }
+
+    /// Listens for balance updates forever on a dedicated connection,
+    /// reconnecting with backoff whenever it drops.
+    /// Updates written while disconnected are not replayed.
+    pub async fn run(&self, db_url: &str) {
+        let mut failures = 0u32;
+        loop {
+            match self.listen(db_url).await {
+                Ok(()) => {
+                    log::error!("balance listen connection closed");
+                    failures = 0;
+                }
+                Err(err) => {
+                    log::error!("balance listen connection: {:?}", err);
+                    failures = failures.saturating_add(1);
+                }
+            }
+            let backoff = RECONNECT_BASE_SECS
+                .saturating_mul(2u64.saturating_pow(failures))
+                .min(RECONNECT_MAX_SECS);
+            tokio::time::sleep(Duration::from_secs(backoff)).await;
+        }
+    }
+
+    /// Listens until the connection drops.
+    async fn listen(&self, db_url: &str) -> Result<(), Error> {
+        let (client, mut connection) = tokio_postgres::connect(db_url, NoTls).await?;
+        // the connection must be polled for the client to make progress,
+        // so forward its messages from a separate task
+        let (tx, mut rx) = futures_channel::mpsc::unbounded();
+        let mut messages = futures::stream::poll_fn(move |cx| connection.poll_message(cx));
+        tokio::task::spawn(async move {
+            while let Some(message) = messages.next().await {
+                if tx.unbounded_send(message).is_err() {
+                    break;
+                }
+            }
+        });
+        client.batch_execute(&format!("LISTEN {}", CHANNEL)).await?;
+        log::info!("listening for balance updates");
+        while let Some(message) = rx.next().await {
+            match message? {
+                AsyncMessage::Notification(notification) if notification.channel() == CHANNEL => {
+                    self.route(notification.payload());
+                }
+                AsyncMessage::Notice(notice) => log::info!("{}", notice),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(address: &str, amount: u64) -> String {
+        format!(
+            r#"{{"address":"{}","last_hash_at":10,"amount":{},"total_balance":100}}"#,
+            address, amount
+        )
+    }
+
+    #[test]
+    fn routes_to_subscribed_member_only() {
+        let notifier = Notifier::default();
+        let mut rx = notifier.subscribe("a".to_string());
+        notifier.route(payload("b", 1).as_str());
+        notifier.route(payload("a", 2).as_str());
+        let notification = rx.try_recv().unwrap();
+        assert_eq!(notification.amount, 2);
+        assert_eq!(notification.last_hash_at, 10);
+        assert_eq!(notification.total_balance, 100);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn drops_member_without_subscribers() {
+        let notifier = Notifier::default();
+        drop(notifier.subscribe("a".to_string()));
+        notifier.route(payload("a", 1).as_str());
+        assert!(notifier.subscribers.is_empty());
+    }
+
+    #[test]
+    fn unsubscribe_drops_member_once_its_last_subscriber_goes() {
+        let notifier = Notifier::default();
+        let first = notifier.subscribe("a".to_string());
+        let second = notifier.subscribe("a".to_string());
+        drop(first);
+        notifier.unsubscribe("a");
+        assert_eq!(notifier.subscribers.len(), 1);
+        drop(second);
+        notifier.unsubscribe("a");
+        assert!(notifier.subscribers.is_empty());
+    }
+
+    #[test]
+    fn ignores_malformed_payload() {
+        let notifier = Notifier::default();
+        let mut rx = notifier.subscribe("a".to_string());
+        notifier.route("not json");
+        assert!(rx.try_recv().is_err());
+    }
+}
@@ -2,45 +2,169 @@ use std::{collections::HashMap, pin::Pin, str::FromStr, sync::Arc, vec};
 
 use futures::{Future, StreamExt, TryFutureExt, TryStreamExt};
 use ore_api::state::{Config, Proof};
+use ore_boost_api::state::Boost;
 use ore_pool_api::state::{Member, Pool, Share};
 use ore_pool_types::Staker;
-use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::{
-    account::Account,
-    clock::Clock,
-    commitment_config::CommitmentConfig,
-    pubkey::Pubkey,
-    signature::Keypair,
-    signer::{EncodableKey, Signer},
-    sysvar,
+    account::Account, clock::Clock, commitment_config::CommitmentConfig, hash::Hash,
+    pubkey::Pubkey, sysvar,
 };
 use steel::AccountDeserialize;
 
-use crate::{database, error::Error, tx, utils::try_env_var};
+use crate::{
+    config::{self, ReloadableConfig},
+    database,
+    error::Error,
+    rpc::Rpc,
+    signer::{self, OperatorSigner},
+    tx,
+};
 
+/// The default seconds before the on-chain deadline to stop accepting contributions.
+/// Clients are given two more, see `BUFFER_CLIENT`,
+/// and late contributions may be accepted into the margin, see `LATE_GRACE_SECS`.
 pub const BUFFER_OPERATOR: u64 = 5;
 const MIN_DIFFICULTY: Option<u64> = None;
+/// The percentile of recent priority fees targeted by dynamic pricing.
+const PRIORITY_FEE_PERCENTILE: usize = 75;
+/// How long a verified pool account is cached for /pool-address?verify=true.
+const POOL_CACHE_TTL_SECS: u64 = 30;
+const COMMIT_RETRY: tx::submit::Retry = tx::submit::Retry {
+    max_retries: 5,
+    base_delay_ms: 1_000,
+    max_jitter_ms: 500,
+};
 
 pub struct Operator {
-    /// The pool authority keypair.
-    pub keypair: Keypair,
+    /// Signs for the pool authority, locally or remotely.
+    pub signer: Box<dyn OperatorSigner>,
+
+    /// Whether or not accepted contributions are answered with a signed receipt.
+    pub receipts: bool,
+
+    /// The pool account, derived from the authority it was launched with,
+    /// which is no longer the signer once the pool has been transferred.
+    pub pool: Pubkey,
 
-    /// Solana RPC client.
-    pub rpc_client: RpcClient,
+    /// The bump of the pool account.
+    pub pool_bump: u8,
+
+    /// Solana RPC clients, failing over across the configured endpoints.
+    pub rpc_client: Rpc,
 
     /// Postgres connection pool.
     pub db_client: deadpool_postgres::Pool,
 
+    /// Postgres connection pool for read-heavy endpoints.
+    /// May lag behind writes if it points at a replica, the same pool otherwise.
+    pub db_read_client: deadpool_postgres::Pool,
+
     /// The boost accounts for mining multipliers.
     pub boost_accounts: Vec<BoostAccount>,
 
-    /// The operator commission in % percentage.
-    /// Applied to the miner and staker rewards.
-    pub operator_commission: u64,
+    /// The max number of boosts whose stakers are fetched at once.
+    pub stake_fetch_concurrency: usize,
+
+    /// The config that can be reloaded without a restart, see [`Operator::reload`].
+    config: std::sync::RwLock<Arc<ReloadableConfig>>,
+
+    /// The optional env file the reloadable config is read from, overriding the environment.
+    config_file: Option<String>,
+
+    /// The max share of the total score in % percentage
+    /// that any single member may be attributed per challenge.
+    pub max_member_score_pct: Option<u64>,
+
+    /// Who receives a boost's staker reward when the boost has no eligible stake.
+    pub unstaked_recipient: UnstakedRecipient,
+
+    /// The min seconds since a member first contributed before they are attributed,
+    /// disabled if zero.
+    pub min_member_age_secs: u64,
+
+    /// The min balance increment written for a member per round,
+    /// smaller attributions are held back per the dust recipient.
+    pub min_attribution: u64,
+
+    /// What becomes of the attributions below the min attribution.
+    pub dust_recipient: DustRecipient,
+
+    /// How the solution submitted on-chain is selected from the contributions.
+    pub winner_strategy: WinnerStrategy,
+
+    /// The min rewards a bus must hold to be submitted to.
+    pub min_bus_rewards: u64,
 
-    /// The staker commission in % percentage.
-    /// The rest is given to miners to incentize participation.
-    pub staker_commission: u64,
+    /// The last pool account verified on-chain and when it was fetched.
+    /// Only positive results are cached.
+    pool_cache: std::sync::Mutex<Option<(std::time::Instant, Pool)>>,
+
+    /// The genesis hash of the rpc, fetched once on first use.
+    genesis_hash: std::sync::OnceLock<Hash>,
+
+    /// Rolling estimate of submit latency, for the cutoff margin.
+    submit_latency: std::sync::Mutex<tx::latency::LatencyEstimate>,
+
+    /// The requested seconds past the cutoff that late contributions are still accepted for.
+    late_grace_secs: u64,
+
+    /// Whether or not to exclude the operator's own stake from the staker commission,
+    /// since the operator already takes the operator commission.
+    pub exclude_operator_stake: bool,
+
+    /// Whether or not to reject contributions from members registered after the round started,
+    /// so that members only earn from rounds they were registered for the whole of.
+    pub require_full_round: bool,
+
+    /// Whether or not to skip sending submit transactions,
+    /// logging the winner, attestation and reward distribution instead.
+    pub dry_run: bool,
+
+    /// Whether or not to distribute a round from the on-chain pool reward
+    /// when its rewards webhook never arrives.
+    pub rewards_fallback: bool,
+
+    /// The commitment a landed submit must reach before its round is reset and attributed.
+    pub rewards_commitment: CommitmentConfig,
+}
+
+/// Who receives a boost's staker reward when no one is eligible for it,
+/// e.g. everyone has unstaked or the only staker is the excluded operator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnstakedRecipient {
+    /// The operator's member account.
+    Operator,
+    /// The round's miners, proportional to their scores.
+    Miners,
+}
+
+/// What becomes of a member's attribution below the min attribution,
+/// which would cost more in claim fees than it's worth.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DustRecipient {
+    /// Carried over to the member's next round, until the total reaches the min attribution.
+    CarryOver,
+    /// Forfeited to the operator's member account.
+    Operator,
+}
+
+/// How the solution submitted on-chain is selected from the contributions.
+///
+/// Every member is attributed by score regardless of the strategy,
+/// and the on-chain reward only depends on the difficulty of the submitted solution,
+/// so anything but the max difficulty trades some pool reward for something else.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WinnerStrategy {
+    /// The highest difficulty solution, for the max reward.
+    MaxDifficulty,
+    /// A solution at or above the min difficulty, picked at random weighted by score,
+    /// to spread the on-chain credit of the submit across members.
+    /// Falls back to the max difficulty if none qualify.
+    Weighted { min_difficulty: u32 },
+    /// The first solution at or above the min difficulty to arrive,
+    /// so that the winner is settled as early as possible in the round.
+    /// Falls back to the max difficulty if none qualify.
+    Earliest { min_difficulty: u32 },
 }
 
 pub struct BoostAccount {
@@ -52,63 +176,205 @@ pub struct BoostAccount {
 
     /// The pool's stake account derived from the boost.
     pub stake: Pubkey,
+
+    /// How often (in minutes) the stakers of this boost are attributed,
+    /// in addition to the attribution of all members.
+    pub attribution_epoch: Option<u64>,
 }
 
 impl BoostAccount {
-    fn new(mint: Pubkey, operator_pubkey: Pubkey) -> Self {
+    fn new(mint: Pubkey, attribution_epoch: Option<u64>, pool: Pubkey) -> Self {
         let (boost, _) = ore_boost_api::state::boost_pda(mint);
-        let (pool, _) = ore_pool_api::state::pool_pda(operator_pubkey);
         let (stake, _) = ore_boost_api::state::stake_pda(pool, boost);
-        Self { mint, boost, stake }
+        Self {
+            mint,
+            boost,
+            stake,
+            attribution_epoch,
+        }
     }
 
-    fn new_from_vec(mint_vec: Vec<Pubkey>, operator_pubkey: Pubkey) -> Vec<Self> {
-        mint_vec
+    fn new_from_vec(boost_vec: Vec<(Pubkey, Option<u64>)>, pool: Pubkey) -> Vec<Self> {
+        boost_vec
             .into_iter()
-            .map(|mint| Self::new(mint, operator_pubkey))
+            .map(|(mint, attribution_epoch)| Self::new(mint, attribution_epoch, pool))
             .collect()
     }
 }
 
 impl Operator {
-    pub fn new() -> Result<Operator, Error> {
-        let keypair = Self::keypair()?;
-        let rpc_client = Self::rpc_client()?;
-        let db_client = database::create_pool();
-        let boosts = Self::load_boosts()?;
-        log::info!("boosts: {:?}", boosts);
-        let boost_accounts = BoostAccount::new_from_vec(boosts, keypair.pubkey());
-        let operator_commission = Self::operator_commission()?;
-        log::info!("operator commision: {}", operator_commission);
-        let staker_commission = Self::staker_commission()?;
-        log::info!("staker commission: {}", staker_commission);
+    pub fn new(config: &config::Config) -> Result<Operator, Error> {
+        let signer = signer::new(&config.signer)?;
+        log::info!("pool authority: {}", signer.pubkey());
+        let receipts = config.signer.receipts();
+        log::info!("receipts: {}", receipts);
+        let launch_authority = config.pool_launch_authority.unwrap_or(signer.pubkey());
+        let (pool, pool_bump) = ore_pool_api::state::pool_pda(launch_authority);
+        log::info!("pool: {} launched by: {}", pool, launch_authority);
+        let rpc_client = Rpc::new(config.rpc_urls.as_slice(), config.rpc_policy);
+        log::info!(
+            "rpc endpoints: {} policy: {:?}",
+            config.rpc_urls.len(),
+            config.rpc_policy
+        );
+        let db_client = database::create_pool(config.db_url.clone());
+        let db_read_client = database::create_read_pool(&db_client, config.db_read_url.clone());
+        log::info!("boosts: {:?}", config.boosts);
+        log::info!(
+            "stake fetch concurrency: {}",
+            config.stake_fetch_concurrency
+        );
+        let boost_accounts = BoostAccount::new_from_vec(config.boosts.clone(), pool);
+        log::info!("config: {:?}", config.reloadable);
+        log::info!("max member score pct: {:?}", config.max_member_score_pct);
+        log::info!("unstaked boost recipient: {:?}", config.unstaked_recipient);
+        log::info!("min member age: {}", config.min_member_age_secs);
+        log::info!(
+            "min attribution: {} dust recipient: {:?}",
+            config.min_attribution,
+            config.dust_recipient
+        );
+        log::info!("winner strategy: {:?}", config.winner_strategy);
+        log::info!("min bus rewards: {}", config.min_bus_rewards);
+        log::info!("min cutoff margin: {}", config.min_cutoff_margin);
+        log::info!("late grace: {}", config.late_grace_secs);
+        log::info!("exclude operator stake: {}", config.exclude_operator_stake);
+        log::info!("require full round: {}", config.require_full_round);
+        if config.dry_run {
+            log::info!("dry run, submit transactions will not be sent");
+        }
+        log::info!("rewards fallback: {}", config.rewards_fallback);
+        log::info!(
+            "rewards commitment: {:?}",
+            config.rewards_commitment.commitment
+        );
         Ok(Operator {
-            keypair,
+            signer,
+            receipts,
+            pool,
+            pool_bump,
             rpc_client,
             db_client,
+            db_read_client,
             boost_accounts,
-            operator_commission,
-            staker_commission,
+            stake_fetch_concurrency: config.stake_fetch_concurrency,
+            config: std::sync::RwLock::new(Arc::new(config.reloadable.clone())),
+            config_file: config.config_file.clone(),
+            max_member_score_pct: config.max_member_score_pct,
+            unstaked_recipient: config.unstaked_recipient,
+            min_member_age_secs: config.min_member_age_secs,
+            min_attribution: config.min_attribution,
+            dust_recipient: config.dust_recipient,
+            winner_strategy: config.winner_strategy,
+            min_bus_rewards: config.min_bus_rewards,
+            pool_cache: std::sync::Mutex::new(None),
+            genesis_hash: std::sync::OnceLock::new(),
+            submit_latency: std::sync::Mutex::new(tx::latency::LatencyEstimate::new(
+                config.min_cutoff_margin,
+            )),
+            late_grace_secs: config.late_grace_secs,
+            exclude_operator_stake: config.exclude_operator_stake,
+            require_full_round: config.require_full_round,
+            dry_run: config.dry_run,
+            rewards_fallback: config.rewards_fallback,
+            rewards_commitment: config.rewards_commitment,
         })
     }
 
+    /// A snapshot of the reloadable config.
+    /// Hold onto it for the duration of a round rather than reading fields one by one,
+    /// so that a concurrent reload can't mix values of the old and new config.
+    pub fn config(&self) -> Arc<ReloadableConfig> {
+        match self.config.read() {
+            Ok(config) => config.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    /// Re-reads the reloadable config, from the config file if set,
+    /// and swaps it in for the next round.
+    /// The current config is kept if the new one is invalid.
+    pub fn reload(&self) -> Result<(), Error> {
+        let new = Arc::new(ReloadableConfig::reload(self.config_file.as_deref())?);
+        let mut config = match self.config.write() {
+            Ok(config) => config,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        log::info!("reloaded config: {:?} -> {:?}", config, new);
+        *config = new;
+        Ok(())
+    }
+
+    /// Fetches the pool account if it exists on-chain,
+    /// serving from a short-lived cache once it has been found.
+    pub async fn get_pool_cached(&self) -> Result<Option<Pool>, Error> {
+        let ttl = std::time::Duration::from_secs(POOL_CACHE_TTL_SECS);
+        if let Ok(cache) = self.pool_cache.lock() {
+            if let Some((fetched_at, pool)) = *cache {
+                if fetched_at.elapsed() < ttl {
+                    return Ok(Some(pool));
+                }
+            }
+        }
+        let pool_pda = self.pool;
+        let account = self
+            .rpc_client
+            .get_account_with_commitment(&pool_pda, CommitmentConfig::confirmed())
+            .await?
+            .value;
+        let pool = match account {
+            Some(account) => *Pool::try_from_bytes(account.data.as_slice())?,
+            None => return Ok(None),
+        };
+        if let Ok(mut cache) = self.pool_cache.lock() {
+            *cache = Some((std::time::Instant::now(), pool));
+        }
+        Ok(Some(pool))
+    }
+
+    /// The genesis hash of the rpc, which never changes, so only fetched until it succeeds.
+    pub async fn get_genesis_hash(&self) -> Result<Hash, Error> {
+        if let Some(genesis_hash) = self.genesis_hash.get() {
+            return Ok(*genesis_hash);
+        }
+        let genesis_hash = self.rpc_client.get_genesis_hash().await?;
+        Ok(*self.genesis_hash.get_or_init(|| genesis_hash))
+    }
+
     pub async fn get_pool(&self) -> Result<Pool, Error> {
-        let authority = self.keypair.pubkey();
         let rpc_client = &self.rpc_client;
-        let (pool_pda, _) = ore_pool_api::state::pool_pda(authority);
+        let pool_pda = self.pool;
         let data = rpc_client.get_account_data(&pool_pda).await?;
         let pool = Pool::try_from_bytes(data.as_slice())?;
         Ok(*pool)
     }
 
+    /// Fetches the pool and its proof, None if either doesn't exist yet,
+    /// i.e. the pool hasn't been initialized.
+    pub async fn find_pool_and_proof(&self) -> Result<Option<(Pool, Proof)>, Error> {
+        let pool_pda = self.pool;
+        let (proof_pda, _) = ore_pool_api::state::pool_proof_pda(pool_pda);
+        let accounts = self
+            .rpc_client
+            .get_multiple_accounts(&[pool_pda, proof_pda])
+            .await?;
+        match accounts.as_slice() {
+            [Some(pool), Some(proof)] => {
+                let pool = Pool::try_from_bytes(pool.data.as_slice())?;
+                let proof = Proof::try_from_bytes(proof.data.as_slice())?;
+                Ok(Some((*pool, *proof)))
+            }
+            _ => Ok(None),
+        }
+    }
+
     pub async fn get_staker_onchain(
         &self,
         member_authority: &Pubkey,
         mint: &Pubkey,
     ) -> Result<(ore_pool_api::state::Share, Pubkey), Error> {
-        let keypair = &self.keypair;
         let rpc_client = &self.rpc_client;
-        let (pool_pda, _) = ore_pool_api::state::pool_pda(keypair.pubkey());
+        let pool_pda = self.pool;
         let (share_pda, _) = ore_pool_api::state::share_pda(*member_authority, pool_pda, *mint);
         let data = rpc_client.get_account_data(&share_pda).await?;
         let share = ore_pool_api::state::Share::try_from_bytes(data.as_slice())?;
@@ -120,10 +386,9 @@ impl Operator {
         member_authority: &Pubkey,
         mint: &Pubkey,
     ) -> Result<Staker, Error> {
-        let keypair = &self.keypair;
         let db_client = &self.db_client;
         let db_client = db_client.get().await?;
-        let (pool_pda, _) = ore_pool_api::state::pool_pda(keypair.pubkey());
+        let pool_pda = self.pool;
         let (share_pda, _) = ore_pool_api::state::share_pda(*member_authority, pool_pda, *mint);
         database::read_staker(&db_client, &share_pda.to_string()).await
     }
@@ -159,24 +424,51 @@ impl Operator {
             queries.push(Box::pin(query));
         }
         let results: Vec<Vec<Option<Account>>> = futures::future::try_join_all(queries).await?;
-        let results: HashMap<Pubkey, u64> = results
-            .into_iter()
-            .flat_map(|v| v.into_iter())
-            .filter_map(|option| {
-                option.and_then(|account| {
-                    let data = account.data;
-                    let share = Share::try_from_bytes(data.as_slice()).ok();
-                    share.map(|s| (s.authority, s.balance))
+        // a share holds the merged balance of every stake into it,
+        // sum per authority so nothing is dropped if an authority has several
+        let mut stakers: HashMap<Pubkey, u64> = HashMap::new();
+        for (authority, balance) in
+            results
+                .into_iter()
+                .flat_map(|v| v.into_iter())
+                .filter_map(|option| {
+                    option.and_then(|account| {
+                        let data = account.data;
+                        let share = Share::try_from_bytes(data.as_slice()).ok();
+                        share.map(|s| (s.authority, s.balance))
+                    })
+                })
+        {
+            let total = stakers.entry(authority).or_insert(0);
+            *total = total.saturating_add(balance);
+        }
+        Ok(stakers)
+    }
+
+    /// The total deposits of each boost account, keyed by mint.
+    pub async fn get_boost_totals(&self) -> Result<HashMap<Pubkey, u64>, Error> {
+        let boosts: Vec<Pubkey> = self.boost_accounts.iter().map(|ba| ba.boost).collect();
+        if boosts.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let accounts = self.rpc_client.get_multiple_accounts(&boosts).await?;
+        let totals = self
+            .boost_accounts
+            .iter()
+            .zip(accounts)
+            .filter_map(|(ba, account)| {
+                account.and_then(|account| {
+                    let boost = Boost::try_from_bytes(account.data.as_slice()).ok();
+                    boost.map(|b| (ba.mint, b.total_stake))
                 })
             })
             .collect();
-        Ok(results)
+        Ok(totals)
     }
 
     pub async fn get_member_onchain(&self, member_authority: &Pubkey) -> Result<Member, Error> {
-        let authority = self.keypair.pubkey();
         let rpc_client = &self.rpc_client;
-        let (pool_pda, _) = ore_pool_api::state::pool_pda(authority);
+        let pool_pda = self.pool;
         let (member_pda, _) = ore_pool_api::state::member_pda(*member_authority, pool_pda);
         let data = rpc_client.get_account_data(&member_pda).await?;
         let member = Member::try_from_bytes(data.as_slice())?;
@@ -187,32 +479,128 @@ impl Operator {
         &self,
         member_authority: &str,
     ) -> Result<ore_pool_types::Member, Error> {
-        let db_client = self.db_client.get().await?;
+        self.read_member_db(&self.db_client, member_authority).await
+    }
+
+    /// Reads the member from the read pool, for endpoints that tolerate replica lag.
+    pub async fn get_member_db_read(
+        &self,
+        member_authority: &str,
+    ) -> Result<ore_pool_types::Member, Error> {
+        self.read_member_db(&self.db_read_client, member_authority)
+            .await
+    }
+
+    async fn read_member_db(
+        &self,
+        pool: &deadpool_postgres::Pool,
+        member_authority: &str,
+    ) -> Result<ore_pool_types::Member, Error> {
+        let db_client = pool.get().await?;
         let member_authority = Pubkey::from_str(member_authority)?;
-        let pool_authority = self.keypair.pubkey();
-        let (pool_pda, _) = ore_pool_api::state::pool_pda(pool_authority);
+        let pool_pda = self.pool;
         let (member_pda, _) = ore_pool_api::state::member_pda(member_authority, pool_pda);
         database::read_member(&db_client, &member_pda.to_string()).await
     }
 
     pub async fn get_proof(&self) -> Result<Proof, Error> {
-        let authority = self.keypair.pubkey();
         let rpc_client = &self.rpc_client;
-        let (pool_pda, _) = ore_pool_api::state::pool_pda(authority);
+        let pool_pda = self.pool;
         let (proof_pda, _) = ore_pool_api::state::pool_proof_pda(pool_pda);
         let data = rpc_client.get_account_data(&proof_pda).await?;
         let proof = Proof::try_from_bytes(data.as_slice())?;
         Ok(*proof)
     }
 
+    /// Whether or not the proof has advanced past the round at the rewards commitment,
+    /// i.e. the round's submit can no longer be rolled back by a reorg.
+    pub async fn submit_settled(&self, last_hash_at: i64) -> Result<bool, Error> {
+        let pool_pda = self.pool;
+        let (proof_pda, _) = ore_pool_api::state::pool_proof_pda(pool_pda);
+        let account = self
+            .rpc_client
+            .get_account_with_commitment(&proof_pda, self.rewards_commitment)
+            .await?
+            .value;
+        let Some(account) = account else {
+            return Ok(false);
+        };
+        let proof = Proof::try_from_bytes(account.data.as_slice())?;
+        Ok(proof.last_hash_at > last_hash_at)
+    }
+
+    /// The compute unit limit and price for a submit touching the given accounts.
+    /// If dynamic pricing is enabled, targets a percentile of recent priority fees
+    /// for the accounts, clamped between the configured price and max price.
+    pub async fn submit_compute_budget(&self, accounts: &[Pubkey]) -> (u32, u64) {
+        let config = self.config();
+        let cu_price = match config.submit_cu_price_max {
+            None => config.submit_cu_price,
+            Some(max) => match self
+                .rpc_client
+                .get_recent_prioritization_fees(accounts)
+                .await
+            {
+                Ok(fees) => {
+                    let fees: Vec<u64> = fees.iter().map(|f| f.prioritization_fee).collect();
+                    fee_percentile(fees, PRIORITY_FEE_PERCENTILE)
+                        .clamp(config.submit_cu_price, max.max(config.submit_cu_price))
+                }
+                Err(err) => {
+                    log::error!("{:?}", err);
+                    config.submit_cu_price
+                }
+            },
+        };
+        (config.submit_cu_limit, cu_price)
+    }
+
+    /// Records the observed latency of a landed submit.
+    pub fn record_submit_latency(&self, latency: std::time::Duration) {
+        if let Ok(mut estimate) = self.submit_latency.lock() {
+            estimate.record(latency);
+        }
+    }
+
+    /// The seconds before the on-chain deadline to stop accepting contributions,
+    /// adapted from recently observed submit latency.
+    pub fn cutoff_margin(&self) -> u64 {
+        self.submit_latency
+            .lock()
+            .map(|estimate| estimate.margin_secs())
+            .unwrap_or(BUFFER_OPERATOR)
+    }
+
+    /// The seconds past the cutoff that late contributions are still accepted for,
+    /// bounded so that the submit still lands before the on-chain deadline.
+    pub fn late_grace(&self) -> u64 {
+        self.submit_latency
+            .lock()
+            .map(|estimate| estimate.grace_secs(self.late_grace_secs))
+            .unwrap_or(0)
+    }
+
     pub async fn get_cutoff(&self, proof: &Proof) -> Result<u64, Error> {
+        self.get_cutoff_at(proof.last_hash_at).await
+    }
+
+    pub async fn get_cutoff_at(&self, last_hash_at: i64) -> Result<u64, Error> {
+        let (cutoff_time, _) = self.get_cutoff_window_at(last_hash_at).await?;
+        Ok(cutoff_time)
+    }
+
+    /// The cutoff of the round, with whether the round's on-chain deadline is still ahead,
+    /// as opposed to a round the pool has been idle in since its deadline passed.
+    pub async fn get_cutoff_window_at(&self, last_hash_at: i64) -> Result<(u64, bool), Error> {
         let clock = self.get_clock().await?;
-        Ok(proof
-            .last_hash_at
-            .saturating_add(60)
-            .saturating_sub(BUFFER_OPERATOR as i64)
+        let margin = self.cutoff_margin();
+        log::info!("cutoff margin: {}", margin);
+        let deadline = last_hash_at.saturating_add(60);
+        let cutoff_time = deadline
+            .saturating_sub(margin as i64)
             .saturating_sub(clock.unix_timestamp)
-            .max(0) as u64)
+            .max(0) as u64;
+        Ok((cutoff_time, clock.unix_timestamp < deadline))
     }
 
     pub async fn min_difficulty(&self) -> Result<u64, Error> {
@@ -230,18 +618,26 @@ impl Operator {
     pub async fn attribute_members(self: Arc<Self>) -> Result<(), Error> {
         let db_client = self.db_client.get().await?;
         let db_client = Arc::new(db_client);
-        database::stream_members_attribution(db_client, self).await?;
+        database::stream_members_attribution(db_client, self, None).await?;
+        Ok(())
+    }
+
+    /// Attributes only the members staking to the boost.
+    pub async fn attribute_stakers(self: Arc<Self>, mint: Pubkey) -> Result<(), Error> {
+        let db_client = self.db_client.get().await?;
+        let db_client = Arc::new(db_client);
+        database::stream_members_attribution(db_client, self, Some(mint)).await?;
         Ok(())
     }
 
     pub async fn commit_stake(&self) -> Result<(), Error> {
-        let authority = &self.keypair;
+        let authority = self.signer.as_ref();
         let rpc_client = &self.rpc_client;
         let boost_mints = self.get_boosts();
         if boost_mints.len().gt(&0) {
             let mut ixs = vec![];
             for mint in boost_mints.iter() {
-                let ix = ore_pool_api::sdk::commit(authority.pubkey(), *mint);
+                let ix = ore_pool_api::sdk::commit(authority.pubkey(), self.pool, *mint);
                 ixs.push(ix);
             }
             let sig = tx::submit::submit_and_confirm_instructions(
@@ -250,6 +646,7 @@ impl Operator {
                 ixs.as_slice(),
                 1_000_000,
                 10_000,
+                COMMIT_RETRY,
             )
             .await?;
             log::info!("commit stake sig: {:?}", sig);
@@ -281,80 +678,21 @@ impl Operator {
         Ok(*config)
     }
 
-    async fn get_clock(&self) -> Result<Clock, Error> {
+    pub async fn get_clock(&self) -> Result<Clock, Error> {
         let rpc_client = &self.rpc_client;
         let data = rpc_client.get_account_data(&sysvar::clock::id()).await?;
         bincode::deserialize(&data).map_err(From::from)
     }
+}
 
-    fn keypair() -> Result<Keypair, Error> {
-        let keypair_path = Operator::keypair_path()?;
-        let keypair = Keypair::read_from_file(keypair_path)
-            .map_err(|err| Error::Internal(err.to_string()))?;
-        Ok(keypair)
-    }
-
-    fn keypair_path() -> Result<String, Error> {
-        try_env_var("KEYPAIR_PATH").map_err(From::from)
-    }
-
-    fn rpc_client() -> Result<RpcClient, Error> {
-        let rpc_url = Operator::rpc_url()?;
-        Ok(RpcClient::new_with_commitment(
-            rpc_url,
-            CommitmentConfig::confirmed(),
-        ))
-    }
-
-    fn rpc_url() -> Result<String, Error> {
-        try_env_var("RPC_URL").map_err(From::from)
-    }
-
-    fn load_boosts() -> Result<Vec<Pubkey>, Error> {
-        let boost_1 = Self::boost_one()?;
-        let boost_2 = Self::boost_two()?;
-        let boost_3 = Self::boost_three()?;
-        let boosts: Vec<Pubkey> = vec![boost_1, boost_2, boost_3]
-            .into_iter()
-            .flatten()
-            .collect();
-        Ok(boosts)
-    }
-
-    fn load_boost(var: String) -> Result<Option<Pubkey>, Error> {
-        match std::env::var(var) {
-            Ok(boost) => {
-                let boost = Pubkey::from_str(boost.as_str())?;
-                Ok(Some(boost))
-            }
-            // optional
-            Err(_) => Ok(None),
-        }
-    }
-
-    fn boost_one() -> Result<Option<Pubkey>, Error> {
-        Self::load_boost("BOOST_ONE".to_string())
-    }
-
-    fn boost_two() -> Result<Option<Pubkey>, Error> {
-        Self::load_boost("BOOST_TWO".to_string())
-    }
-
-    fn boost_three() -> Result<Option<Pubkey>, Error> {
-        Self::load_boost("BOOST_THREE".to_string())
-    }
-
-    fn operator_commission() -> Result<u64, Error> {
-        let str = try_env_var("OPERATOR_COMMISSION")?;
-        let commission: u64 = str.parse()?;
-        Ok(commission)
-    }
-
-    fn staker_commission() -> Result<u64, Error> {
-        let str = try_env_var("STAKER_COMMISSION")?;
-        let commission: u64 = str.parse()?;
-        Ok(commission)
+/// The fee at the given percentile, zero if there are no fees.
+fn fee_percentile(mut fees: Vec<u64>, percentile: usize) -> u64 {
+    if fees.is_empty() {
+        return 0;
     }
+    fees.sort_unstable();
+    let index = (fees.len() - 1) * percentile.min(100) / 100;
+    fees[index]
 }
 
 type GetManyStakers = Result<Vec<Option<Account>>, Error>;
@@ -364,6 +702,17 @@ mod tests {
     use base64::{prelude::BASE64_STANDARD, Engine};
     use ore_api::event::MineEvent;
 
+    use super::fee_percentile;
+
+    #[test]
+    fn fee_percentile_selects_from_sorted() {
+        let fees = vec![500, 100, 0, 400, 200];
+        assert_eq!(fee_percentile(fees.clone(), 0), 0);
+        assert_eq!(fee_percentile(fees.clone(), 75), 400);
+        assert_eq!(fee_percentile(fees, 100), 500);
+        assert_eq!(fee_percentile(vec![], 75), 0);
+    }
+
     #[test]
     fn one() {
         let bytes = vec![
@@ -0,0 +1,124 @@
+use std::time::Instant;
+
+use dashmap::DashMap;
+use solana_sdk::pubkey::Pubkey;
+
+/// The default number of contributions per second a member may sustain.
+pub const DEFAULT_CONTRIBUTE_RATE_PER_SEC: f64 = 1.0;
+
+/// The max number of requests a member may burst before being throttled.
+const BUCKET_CAPACITY: f64 = 5.0;
+
+/// How often (in seconds) idle buckets are pruned.
+const PRUNE_SECS: u64 = 60;
+
+/// Token bucket rate limiter keyed by member authority,
+/// shared across http workers.
+/// Only checked once the contribution's signature has been verified,
+/// so that no one can drain another member's bucket.
+pub struct RateLimiter {
+    buckets: DashMap<Pubkey, Bucket>,
+    /// the number of tokens refilled per second
+    rate_per_sec: f64,
+}
+
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(rate_per_sec: f64) -> Self {
+        log::info!("contribute rate per sec: {}", rate_per_sec);
+        Self {
+            buckets: DashMap::new(),
+            rate_per_sec,
+        }
+    }
+
+    /// Takes a token from the member's bucket.
+    /// Returns false if the member should be throttled.
+    pub fn check(&self, authority: &Pubkey) -> bool {
+        self.check_at(authority, Instant::now())
+    }
+
+    fn check_at(&self, authority: &Pubkey, now: Instant) -> bool {
+        let mut bucket = self.buckets.entry(*authority).or_insert(Bucket {
+            tokens: BUCKET_CAPACITY,
+            last_refill: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.last_refill);
+        let refill = elapsed.as_secs_f64() * self.rate_per_sec;
+        bucket.tokens = (bucket.tokens + refill).min(BUCKET_CAPACITY);
+        bucket.last_refill = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Removes buckets that have idled long enough to refill completely.
+    fn prune(&self, now: Instant) {
+        let rate_per_sec = self.rate_per_sec;
+        self.buckets.retain(|_, bucket| {
+            let elapsed = now.saturating_duration_since(bucket.last_refill);
+            bucket.tokens + elapsed.as_secs_f64() * rate_per_sec < BUCKET_CAPACITY
+        });
+    }
+
+    /// Prunes idle buckets on an interval, off the request path.
+    pub async fn run(&self) {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(PRUNE_SECS));
+        loop {
+            interval.tick().await;
+            self.prune(Instant::now());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn once_per_challenge_is_never_throttled() {
+        let limiter = RateLimiter::new(1.0);
+        let authority = Pubkey::new_unique();
+        let start = Instant::now();
+        for i in 0..100 {
+            let now = start + Duration::from_secs(60 * i);
+            assert!(limiter.check_at(&authority, now));
+        }
+    }
+
+    #[test]
+    fn spam_is_throttled() {
+        let limiter = RateLimiter::new(1.0);
+        let authority = Pubkey::new_unique();
+        let start = Instant::now();
+        let accepted = (0..100)
+            .map(|i| start + Duration::from_millis(10 * i))
+            .filter(|now| limiter.check_at(&authority, *now))
+            .count();
+        // only the initial burst, a full token doesn't refill within the second
+        assert_eq!(accepted, 5);
+    }
+
+    #[test]
+    fn only_idle_buckets_are_pruned() {
+        let limiter = RateLimiter::new(1.0);
+        let idle = Pubkey::new_unique();
+        let busy = Pubkey::new_unique();
+        let start = Instant::now();
+        limiter.check_at(&idle, start);
+        let now = start + Duration::from_secs(10);
+        limiter.check_at(&busy, now);
+        limiter.prune(now);
+        assert!(!limiter.buckets.contains_key(&idle));
+        assert!(limiter.buckets.contains_key(&busy));
+    }
+}
@@ -0,0 +1,103 @@
+use std::{fmt, future::Ready};
+
+use actix_web::{
+    dev::{Payload, ServiceRequest},
+    http::header::HeaderValue,
+    FromRequest, HttpMessage, HttpRequest,
+};
+
+/// The header a request id is accepted from and echoed back in.
+pub const HEADER: &str = "x-request-id";
+
+/// Correlates the logs of a request,
+/// from the handler through to the submit and attribution of its contribution.
+/// Displayed as 32 hex chars.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RequestId(u128);
+
+impl RequestId {
+    pub fn new() -> Self {
+        Self(rand::random())
+    }
+
+    /// Accepts the client's id if it is up to 32 hex chars, e.g. a uuid,
+    /// otherwise assigns a new one.
+    pub fn from_service_request(req: &ServiceRequest) -> Self {
+        req.headers()
+            .get(HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(Self::parse)
+            .unwrap_or_default()
+    }
+
+    fn parse(str: &str) -> Option<Self> {
+        let hex: String = str.chars().filter(|c| *c != '-').collect();
+        if hex.is_empty() || hex.len() > 32 {
+            return None;
+        }
+        u128::from_str_radix(hex.as_str(), 16).ok().map(Self)
+    }
+
+    pub fn header_value(&self) -> HeaderValue {
+        // hex is always a valid header value
+        HeaderValue::from_str(self.to_string().as_str()).unwrap_or(HeaderValue::from_static(""))
+    }
+}
+
+impl Default for RequestId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:032x}", self.0)
+    }
+}
+
+/// Extracts the id assigned by the request id middleware.
+impl FromRequest for RequestId {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let request_id = req
+            .extensions()
+            .get::<RequestId>()
+            .copied()
+            .unwrap_or_default();
+        std::future::ready(Ok(request_id))
+    }
+}
+
+/// Stores the id in the request extensions for handlers to extract.
+pub fn assign(req: &ServiceRequest) -> RequestId {
+    let request_id = RequestId::from_service_request(req);
+    req.extensions_mut().insert(request_id);
+    request_id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uuid_is_accepted() {
+        let request_id = RequestId::parse("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap();
+        assert_eq!(request_id.to_string(), "67e5504410b1426f9247bb680e5fe0c8");
+    }
+
+    #[test]
+    fn short_hex_is_zero_padded() {
+        let request_id = RequestId::parse("ff").unwrap();
+        assert_eq!(request_id.to_string(), format!("{:0>32}", "ff"));
+    }
+
+    #[test]
+    fn invalid_ids_are_rejected() {
+        assert!(RequestId::parse("").is_none());
+        assert!(RequestId::parse("not-hex").is_none());
+        assert!(RequestId::parse(&"f".repeat(33)).is_none());
+    }
+}
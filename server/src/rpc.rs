@@ -0,0 +1,381 @@
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind, Result as ClientResult},
+    nonblocking::rpc_client::RpcClient,
+    rpc_response::{Response, RpcPrioritizationFee},
+};
+use solana_sdk::{
+    account::Account, commitment_config::CommitmentConfig, hash::Hash, pubkey::Pubkey,
+    signature::Signature, transaction::Transaction,
+};
+
+/// How long (in seconds) an endpoint is skipped after a connection error, doubled per failure.
+const UNHEALTHY_BASE_SECS: u64 = 5;
+
+/// The max time (in seconds) an endpoint is skipped for.
+const UNHEALTHY_MAX_SECS: u64 = 60;
+
+/// Which endpoint a call is sent to first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RpcPolicy {
+    /// The first healthy endpoint in the configured order,
+    /// so that the fallbacks are only used while the primary is down.
+    Primary,
+    /// The next healthy endpoint in turn, spreading calls across every endpoint.
+    RoundRobin,
+}
+
+/// Solana RPC clients for one or more endpoints.
+/// A call that fails to reach an endpoint fails over to the next,
+/// and the endpoint is skipped for a while so that a dead endpoint isn't hammered.
+/// Errors returned by a reachable endpoint are returned as is.
+pub struct Rpc {
+    endpoints: Vec<Endpoint>,
+    policy: RpcPolicy,
+    /// The endpoint the next round-robin call starts at.
+    next: AtomicUsize,
+}
+
+struct Endpoint {
+    client: Arc<RpcClient>,
+    /// The scheme and host of the url, which is logged rather than the url
+    /// since the path or query often holds an api key.
+    host: String,
+    health: Mutex<Health>,
+}
+
+#[derive(Default)]
+struct Health {
+    /// The number of consecutive connection errors.
+    failures: u32,
+    /// When the endpoint is tried again before the healthy endpoints.
+    unhealthy_until: Option<Instant>,
+}
+
+impl Rpc {
+    pub fn new(urls: &[String], policy: RpcPolicy) -> Self {
+        let endpoints = urls
+            .iter()
+            .map(|url| Endpoint {
+                client: Arc::new(RpcClient::new_with_commitment(
+                    url.clone(),
+                    CommitmentConfig::confirmed(),
+                )),
+                host: host(url).to_string(),
+                health: Mutex::new(Health::default()),
+            })
+            .collect();
+        Self {
+            endpoints,
+            policy,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// The order endpoints are tried in, healthy first per the policy, then unhealthy.
+    fn order(&self, now: Instant) -> Vec<usize> {
+        let len = self.endpoints.len();
+        if len == 0 {
+            return vec![];
+        }
+        let start = match self.policy {
+            RpcPolicy::Primary => 0,
+            RpcPolicy::RoundRobin => self.next.fetch_add(1, Ordering::Relaxed) % len,
+        };
+        let (mut healthy, unhealthy): (Vec<usize>, Vec<usize>) = (0..len)
+            .map(|i| (start + i) % len)
+            .partition(|i| self.endpoints[*i].is_healthy(now));
+        healthy.extend(unhealthy);
+        healthy
+    }
+
+    /// Calls each endpoint in turn until one is reached.
+    pub async fn call<T, F, Fut>(&self, f: F) -> ClientResult<T>
+    where
+        F: Fn(Arc<RpcClient>) -> Fut,
+        Fut: Future<Output = ClientResult<T>>,
+    {
+        let order = self.order(Instant::now());
+        let mut last_err = None;
+        for (attempt, index) in order.iter().enumerate() {
+            let endpoint = &self.endpoints[*index];
+            match f(endpoint.client.clone()).await {
+                Err(err) if is_connection_error(&err) => {
+                    endpoint.failed(Instant::now());
+                    match order.get(attempt + 1) {
+                        Some(next) => log::warn!(
+                            "rpc failover: {} -> {}: {:?}",
+                            endpoint.host,
+                            self.endpoints[*next].host,
+                            err
+                        ),
+                        None => log::error!("rpc unreachable: {}: {:?}", endpoint.host, err),
+                    }
+                    last_err = Some(err);
+                }
+                res => {
+                    endpoint.reached();
+                    return res;
+                }
+            }
+        }
+        Err(last_err.unwrap_or_else(|| {
+            ClientError::from(ClientErrorKind::Custom("no rpc endpoints".to_string()))
+        }))
+    }
+
+    /// The endpoint to pin a sequence of calls to, the first in the current order,
+    /// so that reads compared across calls come from the same node.
+    pub fn pin(&self) -> usize {
+        self.order(Instant::now()).first().copied().unwrap_or(0)
+    }
+
+    /// Calls the pinned endpoint only, without failing over.
+    pub async fn call_pinned<T, F, Fut>(&self, index: usize, f: F) -> ClientResult<T>
+    where
+        F: Fn(Arc<RpcClient>) -> Fut,
+        Fut: Future<Output = ClientResult<T>>,
+    {
+        let Some(endpoint) = self.endpoints.get(index) else {
+            return Err(ClientErrorKind::Custom("no rpc endpoints".to_string()).into());
+        };
+        match f(endpoint.client.clone()).await {
+            Err(err) if is_connection_error(&err) => {
+                endpoint.failed(Instant::now());
+                log::error!("rpc unreachable: {}: {:?}", endpoint.host, err);
+                Err(err)
+            }
+            res => {
+                endpoint.reached();
+                res
+            }
+        }
+    }
+
+    pub async fn get_account_data(&self, pubkey: &Pubkey) -> ClientResult<Vec<u8>> {
+        self.call(|client| async move { client.get_account_data(pubkey).await })
+            .await
+    }
+
+    pub async fn get_account_with_commitment(
+        &self,
+        pubkey: &Pubkey,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<Response<Option<Account>>> {
+        self.call(
+            |client| async move { client.get_account_with_commitment(pubkey, commitment).await },
+        )
+        .await
+    }
+
+    pub async fn get_multiple_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> ClientResult<Vec<Option<Account>>> {
+        self.call(|client| async move { client.get_multiple_accounts(pubkeys).await })
+            .await
+    }
+
+    pub async fn get_genesis_hash(&self) -> ClientResult<Hash> {
+        self.call(|client| async move { client.get_genesis_hash().await })
+            .await
+    }
+
+    pub async fn get_latest_blockhash(&self) -> ClientResult<Hash> {
+        self.call(|client| async move { client.get_latest_blockhash().await })
+            .await
+    }
+
+    pub async fn get_slot(&self) -> ClientResult<u64> {
+        self.call(|client| async move { client.get_slot().await })
+            .await
+    }
+
+    /// Gets the slot from the pinned endpoint, see [`Rpc::pin`].
+    pub async fn get_slot_pinned(&self, index: usize) -> ClientResult<u64> {
+        self.call_pinned(index, |client| async move { client.get_slot().await })
+            .await
+    }
+
+    pub async fn get_recent_prioritization_fees(
+        &self,
+        addresses: &[Pubkey],
+    ) -> ClientResult<Vec<RpcPrioritizationFee>> {
+        self.call(|client| async move { client.get_recent_prioritization_fees(addresses).await })
+            .await
+    }
+
+    /// Sends the transaction, which may land through any endpoint
+    /// since the signature is the same whichever endpoint relays it.
+    pub async fn send_transaction(&self, transaction: &Transaction) -> ClientResult<Signature> {
+        self.call(|client| async move { client.send_transaction(transaction).await })
+            .await
+    }
+
+    pub async fn confirm_transaction_with_commitment(
+        &self,
+        signature: &Signature,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<Response<bool>> {
+        self.call(|client| async move {
+            client
+                .confirm_transaction_with_commitment(signature, commitment)
+                .await
+        })
+        .await
+    }
+}
+
+impl Endpoint {
+    fn is_healthy(&self, now: Instant) -> bool {
+        match self.health.lock() {
+            Ok(health) => health.unhealthy_until.map_or(true, |until| now >= until),
+            Err(_) => true,
+        }
+    }
+
+    fn failed(&self, now: Instant) {
+        if let Ok(mut health) = self.health.lock() {
+            health.failures = health.failures.saturating_add(1);
+            let secs = UNHEALTHY_BASE_SECS
+                .saturating_mul(2u64.saturating_pow(health.failures - 1))
+                .min(UNHEALTHY_MAX_SECS);
+            health.unhealthy_until = Some(now + Duration::from_secs(secs));
+        }
+    }
+
+    fn reached(&self) {
+        if let Ok(mut health) = self.health.lock() {
+            if health.failures > 0 {
+                log::info!("rpc recovered: {}", self.host);
+            }
+            *health = Health::default();
+        }
+    }
+}
+
+/// Whether the endpoint couldn't be reached or failed to serve the request,
+/// as opposed to an error it returned about the request itself.
+fn is_connection_error(err: &ClientError) -> bool {
+    matches!(
+        err.kind(),
+        ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_)
+    )
+}
+
+/// The scheme and host of the url.
+fn host(url: &str) -> &str {
+    let start = url.find("://").map_or(0, |i| i + 3);
+    let end = url[start..]
+        .find(['/', '?'])
+        .map_or(url.len(), |i| start + i);
+    &url[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rpc(policy: RpcPolicy) -> Rpc {
+        let urls = ["http://a", "http://b", "http://c"].map(String::from);
+        Rpc::new(urls.as_slice(), policy)
+    }
+
+    fn unreachable() -> ClientError {
+        std::io::Error::from(std::io::ErrorKind::ConnectionRefused).into()
+    }
+
+    #[test]
+    fn host_omits_path_and_query() {
+        assert_eq!(
+            host("https://rpc.example.com/token?api-key=secret"),
+            "https://rpc.example.com"
+        );
+        assert_eq!(host("http://localhost:8899"), "http://localhost:8899");
+    }
+
+    #[test]
+    fn round_robin_rotates_and_skips_unhealthy() {
+        let rpc = rpc(RpcPolicy::RoundRobin);
+        let now = Instant::now();
+        assert_eq!(rpc.order(now), vec![0, 1, 2]);
+        assert_eq!(rpc.order(now), vec![1, 2, 0]);
+        rpc.endpoints[2].failed(now);
+        assert_eq!(rpc.order(now), vec![0, 1, 2]);
+        assert_eq!(rpc.order(now), vec![0, 1, 2]);
+        // tried again once the endpoint has cooled down
+        let later = now + Duration::from_secs(UNHEALTHY_BASE_SECS);
+        assert_eq!(rpc.order(later), vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn cooldown_grows_with_consecutive_failures() {
+        let rpc = rpc(RpcPolicy::Primary);
+        let now = Instant::now();
+        let endpoint = &rpc.endpoints[0];
+        endpoint.failed(now);
+        endpoint.failed(now);
+        assert!(!endpoint.is_healthy(now + Duration::from_secs(UNHEALTHY_BASE_SECS)));
+        assert!(endpoint.is_healthy(now + Duration::from_secs(UNHEALTHY_BASE_SECS * 2)));
+        for _ in 0..10 {
+            endpoint.failed(now);
+        }
+        assert!(endpoint.is_healthy(now + Duration::from_secs(UNHEALTHY_MAX_SECS)));
+        endpoint.reached();
+        assert!(endpoint.is_healthy(now));
+    }
+
+    #[tokio::test]
+    async fn fails_over_on_connection_error_only() {
+        let rpc = rpc(RpcPolicy::Primary);
+        // the primary is down
+        let url = rpc
+            .call(|client| async move {
+                match client.url().as_str() {
+                    "http://a" => Err(unreachable()),
+                    url => Ok(url.to_string()),
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!(url, "http://b");
+        assert!(!rpc.endpoints[0].is_healthy(Instant::now()));
+        assert_eq!(rpc.order(Instant::now()), vec![1, 2, 0]);
+        // an error about the request isn't failed over
+        let res: ClientResult<()> = rpc
+            .call(|_| async { Err(ClientErrorKind::Custom("invalid".to_string()).into()) })
+            .await;
+        assert!(res.is_err());
+        assert!(rpc.endpoints[1].is_healthy(Instant::now()));
+        // every endpoint is down
+        let res: ClientResult<()> = rpc.call(|_| async { Err(unreachable()) }).await;
+        assert!(matches!(res.unwrap_err().kind(), ClientErrorKind::Io(_)));
+    }
+
+    #[tokio::test]
+    async fn pinned_calls_stay_on_one_endpoint() {
+        let rpc = rpc(RpcPolicy::RoundRobin);
+        let index = rpc.pin();
+        for _ in 0..3 {
+            let url = rpc
+                .call_pinned(index, |client| async move { Ok(client.url()) })
+                .await
+                .unwrap();
+            assert_eq!(url, "http://a");
+        }
+        // an unreachable pinned endpoint isn't failed over
+        let res: ClientResult<()> = rpc
+            .call_pinned(index, |_| async { Err(unreachable()) })
+            .await;
+        assert!(res.is_err());
+        assert!(!rpc.endpoints[index].is_healthy(Instant::now()));
+    }
+}
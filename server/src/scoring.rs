@@ -0,0 +1,63 @@
+/// The curve mapping solution difficulty to contribution score, set by SCORE_CURVE.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Curve {
+    /// 2^difficulty, rewards concentrate on the hardest solutions.
+    Exponential,
+    /// The difficulty itself, the smoothest distribution.
+    Linear,
+    /// 2^difficulty up to the cap difficulty, flat beyond it.
+    CappedExponential(u32),
+}
+
+impl Curve {
+    /// The score of a solution of the difficulty, saturating at u64::MAX.
+    pub fn score(&self, difficulty: u32) -> u64 {
+        match self {
+            Curve::Exponential => exponential(difficulty),
+            Curve::Linear => difficulty as u64,
+            Curve::CappedExponential(cap) => exponential(difficulty.min(*cap)),
+        }
+    }
+}
+
+fn exponential(difficulty: u32) -> u64 {
+    1u64.checked_shl(difficulty).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exponential_saturates_at_overflow_boundary() {
+        assert_eq!(Curve::Exponential.score(0), 1);
+        assert_eq!(Curve::Exponential.score(63), 1 << 63);
+        assert_eq!(Curve::Exponential.score(64), u64::MAX);
+        assert_eq!(Curve::Exponential.score(65), u64::MAX);
+    }
+
+    #[test]
+    fn linear_is_difficulty() {
+        assert_eq!(Curve::Linear.score(0), 0);
+        assert_eq!(Curve::Linear.score(64), 64);
+        assert_eq!(Curve::Linear.score(65), 65);
+    }
+
+    #[test]
+    fn capped_exponential_is_flat_beyond_cap() {
+        let curve = Curve::CappedExponential(22);
+        assert_eq!(curve.score(10), 1 << 10);
+        assert_eq!(curve.score(22), 1 << 22);
+        assert_eq!(curve.score(63), 1 << 22);
+        assert_eq!(curve.score(64), 1 << 22);
+        assert_eq!(curve.score(65), 1 << 22);
+    }
+
+    #[test]
+    fn capped_exponential_above_overflow_saturates() {
+        let curve = Curve::CappedExponential(70);
+        assert_eq!(curve.score(63), 1 << 63);
+        assert_eq!(curve.score(64), u64::MAX);
+        assert_eq!(curve.score(65), u64::MAX);
+    }
+}
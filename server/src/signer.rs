@@ -0,0 +1,267 @@
+use std::{future::Future, pin::Pin};
+
+use base64::{prelude::BASE64_STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::{EncodableKey, Signer},
+    transaction::Transaction,
+};
+
+use crate::{config::SignerConfig, error::Error};
+
+/// How long (in milliseconds) the remote signer waits on connecting, and on a signature, by default.
+pub const DEFAULT_REMOTE_SIGNER_CONNECT_TIMEOUT_MS: u64 = 1_000;
+pub const DEFAULT_REMOTE_SIGNER_TIMEOUT_MS: u64 = 5_000;
+
+pub type SignFuture<'a> = Pin<Box<dyn Future<Output = Result<Signature, Error>> + Send + 'a>>;
+
+/// Signs for the pool authority.
+/// The local signer holds the keypair in memory,
+/// the remote signer asks a signing service that holds it instead.
+pub trait OperatorSigner: Send + Sync {
+    /// The pool authority.
+    fn pubkey(&self) -> Pubkey;
+
+    /// Signs the message, e.g. the serialized message of a transaction.
+    fn sign_message<'a>(&'a self, message: &'a [u8]) -> SignFuture<'a>;
+}
+
+/// Builds the signer selected by the config.
+pub fn new(config: &SignerConfig) -> Result<Box<dyn OperatorSigner>, Error> {
+    match config {
+        SignerConfig::Keypair { path } => {
+            let keypair = Keypair::read_from_file(path.as_str())
+                .map_err(|err| Error::Internal(err.to_string()))?;
+            Ok(Box::new(LocalSigner::new(keypair)))
+        }
+        SignerConfig::Remote {
+            url,
+            pubkey,
+            auth_token,
+            connect_timeout,
+            timeout,
+            ..
+        } => {
+            // a hung signing service must not hang the submits and requests waiting on it
+            let client = reqwest::Client::builder()
+                .connect_timeout(*connect_timeout)
+                .timeout(*timeout)
+                .build()?;
+            Ok(Box::new(RemoteSigner {
+                client,
+                url: url.clone(),
+                pubkey: *pubkey,
+                auth_token: auth_token.clone(),
+            }))
+        }
+    }
+}
+
+/// Signs with a keypair read from disk.
+pub struct LocalSigner {
+    keypair: Keypair,
+}
+
+impl LocalSigner {
+    pub fn new(keypair: Keypair) -> Self {
+        Self { keypair }
+    }
+}
+
+impl OperatorSigner for LocalSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+
+    fn sign_message<'a>(&'a self, message: &'a [u8]) -> SignFuture<'a> {
+        let signature = self.keypair.sign_message(message);
+        Box::pin(async move { Ok(signature) })
+    }
+}
+
+/// Signs by posting each message to a signing service:
+/// `{"pubkey": "<base58>", "message": "<base64>"}`,
+/// answered with `{"signature": "<base58>"}`.
+/// The signature is verified before it's used,
+/// so a misconfigured service can't sign with the wrong key unnoticed.
+pub struct RemoteSigner {
+    client: reqwest::Client,
+    url: String,
+    pubkey: Pubkey,
+    auth_token: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SignRequest {
+    pubkey: String,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct SignResponse {
+    signature: String,
+}
+
+impl RemoteSigner {
+    async fn sign(&self, message: &[u8]) -> Result<Signature, Error> {
+        let request = SignRequest {
+            pubkey: self.pubkey.to_string(),
+            message: BASE64_STANDARD.encode(message),
+        };
+        let mut builder = self.client.post(self.url.as_str()).json(&request);
+        if let Some(auth_token) = self.auth_token.as_ref() {
+            builder = builder.bearer_auth(auth_token);
+        }
+        let response = builder.send().await?.error_for_status()?;
+        let response: SignResponse = response.json().await?;
+        let signature: Signature = response
+            .signature
+            .parse()
+            .map_err(|err| Error::Internal(format!("remote signer signature: {:?}", err)))?;
+        if !signature.verify(self.pubkey.as_ref(), message) {
+            return Err(Error::Internal(
+                "remote signer signature does not match the pool authority".to_string(),
+            ));
+        }
+        Ok(signature)
+    }
+}
+
+impl OperatorSigner for RemoteSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    fn sign_message<'a>(&'a self, message: &'a [u8]) -> SignFuture<'a> {
+        Box::pin(self.sign(message))
+    }
+}
+
+/// Signs a transaction the pool authority is the only signer of.
+pub async fn sign_transaction(
+    signer: &dyn OperatorSigner,
+    tx: &mut Transaction,
+    hash: Hash,
+) -> Result<(), Error> {
+    tx.message.recent_blockhash = hash;
+    let signature = signer.sign_message(tx.message_data().as_slice()).await?;
+    tx.signatures = vec![signature];
+    Ok(())
+}
+
+/// Adds the pool authority's signature to a transaction signed by others,
+/// like [`Transaction::partial_sign`].
+pub async fn partial_sign_transaction(
+    signer: &dyn OperatorSigner,
+    tx: &mut Transaction,
+    hash: Hash,
+) -> Result<(), Error> {
+    let positions = tx
+        .get_signing_keypair_positions(&[signer.pubkey()])
+        .map_err(|err| Error::Internal(format!("{:?}", err)))?;
+    let position = positions.first().copied().flatten().ok_or(Error::Internal(
+        "pool authority is not a signer of the transaction".to_string(),
+    ))?;
+    // a new blockhash invalidates the existing signatures
+    if tx.message.recent_blockhash != hash {
+        tx.message.recent_blockhash = hash;
+        tx.signatures
+            .iter_mut()
+            .for_each(|signature| *signature = Signature::default());
+    }
+    let signature = signer.sign_message(tx.message_data().as_slice()).await?;
+    tx.signatures[position] = signature;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{web, App, HttpServer};
+    use solana_sdk::{instruction::Instruction, system_instruction};
+
+    use super::*;
+
+    /// A signing service that signs with the keypair.
+    async fn mock_remote_signer(keypair: Keypair) -> String {
+        let keypair = web::Data::new(keypair);
+        let server = HttpServer::new(move || {
+            App::new().app_data(keypair.clone()).route(
+                "/sign",
+                web::post().to(
+                    |keypair: web::Data<Keypair>, request: web::Json<SignRequestBody>| async move {
+                        let message = BASE64_STANDARD.decode(request.message.as_str()).unwrap();
+                        let signature = keypair.sign_message(message.as_slice());
+                        web::Json(serde_json::json!({ "signature": signature.to_string() }))
+                    },
+                ),
+            )
+        })
+        .workers(1)
+        .bind(("127.0.0.1", 0))
+        .unwrap();
+        let addr = server.addrs()[0];
+        actix_web::rt::spawn(server.run());
+        format!("http://{}/sign", addr)
+    }
+
+    #[derive(Deserialize)]
+    struct SignRequestBody {
+        message: String,
+    }
+
+    fn remote_signer(url: String, pubkey: Pubkey) -> RemoteSigner {
+        RemoteSigner {
+            client: reqwest::Client::new(),
+            url,
+            pubkey,
+            auth_token: None,
+        }
+    }
+
+    fn transfer(from: Pubkey) -> Instruction {
+        system_instruction::transfer(&from, &Pubkey::new_unique(), 1)
+    }
+
+    #[actix_web::test]
+    async fn remote_signer_signs_transactions() {
+        let keypair = Keypair::new();
+        let pubkey = keypair.pubkey();
+        let signer = remote_signer(mock_remote_signer(keypair).await, pubkey);
+        let mut tx = Transaction::new_with_payer(&[transfer(pubkey)], Some(&pubkey));
+        sign_transaction(&signer, &mut tx, Hash::new_unique())
+            .await
+            .unwrap();
+        assert!(tx.verify().is_ok());
+    }
+
+    #[actix_web::test]
+    async fn remote_signature_of_another_key_is_rejected() {
+        let url = mock_remote_signer(Keypair::new()).await;
+        let signer = remote_signer(url, Pubkey::new_unique());
+        assert!(signer.sign_message(b"message").await.is_err());
+    }
+
+    #[actix_web::test]
+    async fn partial_sign_adds_the_authority_signature() {
+        let keypair = Keypair::new();
+        let pubkey = keypair.pubkey();
+        let signer = remote_signer(mock_remote_signer(keypair).await, pubkey);
+        // the client pays the fee and signs first
+        let client = Keypair::new();
+        let hash = Hash::new_unique();
+        let mut tx = Transaction::new_with_payer(&[transfer(pubkey)], Some(&client.pubkey()));
+        tx.partial_sign(&[&client], hash);
+        partial_sign_transaction(&signer, &mut tx, hash)
+            .await
+            .unwrap();
+        assert!(tx.verify().is_ok());
+        // a signer that isn't part of the transaction
+        let local = LocalSigner::new(Keypair::new());
+        assert!(partial_sign_transaction(&local, &mut tx, hash)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,228 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use futures::{StreamExt, TryStreamExt};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    aggregator::{Aggregator, Stakers},
+    config::Config,
+    error::Error,
+    operator::Operator,
+};
+
+/// How often (in seconds) the staker balances are re-fetched regardless of boost totals.
+pub const DEFAULT_STAKE_REFRESH_SECS: u64 = 600;
+
+/// How often (in seconds) the boost totals are polled for material changes.
+pub const DEFAULT_STAKE_POLL_SECS: u64 = 60;
+
+/// The change in a boost's total deposits (in % percentage) that forces an early refresh.
+pub const DEFAULT_STAKE_REFRESH_THRESHOLD_PCT: u64 = 5;
+
+/// Keeps the aggregator's cached staker balances fresh.
+/// Staker balances change slowly and fetching them is an rpc call per hundred stakers,
+/// so they are re-fetched on an interval rather than every round,
+/// and early if a boost's total deposits move materially or a refresh is triggered.
+pub struct Refresh {
+    /// wakes the refresh loop early
+    force: tokio::sync::Notify,
+    interval: Duration,
+    poll_interval: Duration,
+    threshold_pct: u64,
+}
+
+impl Refresh {
+    pub fn new(config: &Config) -> Self {
+        let interval = config.stake_refresh_interval;
+        let poll_interval = config.stake_poll_interval;
+        let threshold_pct = config.stake_refresh_threshold_pct;
+        log::info!(
+            "stake refresh interval: {:?} poll: {:?} threshold: {}%",
+            interval,
+            poll_interval,
+            threshold_pct
+        );
+        Self {
+            force: tokio::sync::Notify::new(),
+            interval,
+            poll_interval,
+            threshold_pct,
+        }
+    }
+
+    /// Refreshes the staker balances as soon as possible,
+    /// e.g. after committing stake.
+    pub fn trigger(&self) {
+        self.force.notify_one();
+    }
+
+    /// Runs the refresh loop forever.
+    /// The aggregator is expected to have fetched the balances at startup.
+    pub async fn run(&self, operator: &Operator, aggregator: &tokio::sync::RwLock<Aggregator>) {
+        let mut refreshed_at = Instant::now();
+        let mut totals = match operator.get_boost_totals().await {
+            Ok(totals) => totals,
+            Err(err) => {
+                log::error!("{:?}", err);
+                HashMap::new()
+            }
+        };
+        loop {
+            let forced = tokio::select! {
+                _ = tokio::time::sleep(self.poll_interval) => false,
+                _ = self.force.notified() => true,
+            };
+            let latest = match operator.get_boost_totals().await {
+                Ok(latest) => latest,
+                Err(err) => {
+                    log::error!("{:?}", err);
+                    continue;
+                }
+            };
+            let due = refreshed_at.elapsed() >= self.interval;
+            let moved = has_moved(&totals, &latest, self.threshold_pct);
+            if !(forced || due || moved) {
+                continue;
+            }
+            log::info!(
+                "refreshing staker balances, forced: {} due: {} moved: {}",
+                forced,
+                due,
+                moved
+            );
+            // fetch without holding the lock
+            match fetch(operator).await {
+                Ok(stake) => {
+                    let mut aggregator = aggregator.write().await;
+                    aggregator.stake = stake;
+                    drop(aggregator);
+                    refreshed_at = Instant::now();
+                    totals = latest;
+                }
+                Err(err) => {
+                    log::error!("{:?}", err);
+                }
+            }
+        }
+    }
+}
+
+/// Fetches the staker balances of every boost account,
+/// a bounded number of boosts at once to stay within rpc rate limits.
+pub async fn fetch(operator: &Operator) -> Result<Stakers, Error> {
+    // collected up front so the future doesn't borrow the boost accounts across awaits
+    let mints: Vec<Pubkey> = operator.boost_accounts.iter().map(|ba| ba.mint).collect();
+    fetch_with(
+        mints.into_iter(),
+        operator.stake_fetch_concurrency,
+        |mint| async move { operator.get_stakers_onchain(&mint).await },
+    )
+    .await
+}
+
+/// Fetches the staker balances of each mint with at most `concurrency` fetches in flight.
+/// Fails if any fetch fails, so that a partial snapshot never replaces the cached one.
+async fn fetch_with<F, Fut>(
+    mints: impl Iterator<Item = Pubkey>,
+    concurrency: usize,
+    fetch_stakers: F,
+) -> Result<Stakers, Error>
+where
+    F: Fn(Pubkey) -> Fut,
+    Fut: Future<Output = Result<HashMap<Pubkey, u64>, Error>>,
+{
+    futures::stream::iter(mints)
+        .map(|mint| {
+            let stakers = fetch_stakers(mint);
+            async move { stakers.await.map(|stakers| (mint, stakers)) }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .try_collect()
+        .await
+}
+
+/// Whether or not any boost's total deposits moved by more than the threshold,
+/// relative to the totals at the last refresh.
+fn has_moved(
+    previous: &HashMap<Pubkey, u64>,
+    latest: &HashMap<Pubkey, u64>,
+    threshold_pct: u64,
+) -> bool {
+    latest.iter().any(|(mint, latest)| {
+        let previous = previous.get(mint).copied().unwrap_or(0);
+        let delta = previous.abs_diff(*latest) as u128;
+        delta * 100 > (previous as u128) * (threshold_pct as u128)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn fetches_every_boost_within_concurrency() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let mints: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        let in_flight = AtomicUsize::new(0);
+        let max_in_flight = AtomicUsize::new(0);
+        let stake = fetch_with(mints.clone().into_iter(), 2, |mint| {
+            let (in_flight, max_in_flight) = (&in_flight, &max_in_flight);
+            async move {
+                let n = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(n, Ordering::SeqCst);
+                tokio::task::yield_now().await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok(HashMap::from([(mint, 1)]))
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(stake.len(), mints.len());
+        for mint in mints.iter() {
+            assert_eq!(stake[mint], HashMap::from([(*mint, 1)]));
+        }
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn failed_boost_fails_fetch() {
+        let mints = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let failing = mints[1];
+        let stake = fetch_with(mints.into_iter(), 2, |mint| async move {
+            if mint == failing {
+                return Err(Error::Internal("rpc".to_string()));
+            }
+            Ok(HashMap::new())
+        })
+        .await;
+        assert!(stake.is_err());
+    }
+
+    #[test]
+    fn small_change_has_not_moved() {
+        let mint = Pubkey::new_unique();
+        let previous = HashMap::from([(mint, 1_000)]);
+        let latest = HashMap::from([(mint, 1_040)]);
+        assert!(!has_moved(&previous, &latest, 5));
+    }
+
+    #[test]
+    fn material_change_has_moved() {
+        let mint = Pubkey::new_unique();
+        let previous = HashMap::from([(mint, 1_000)]);
+        assert!(has_moved(&previous, &HashMap::from([(mint, 1_060)]), 5));
+        assert!(has_moved(&previous, &HashMap::from([(mint, 900)]), 5));
+    }
+
+    #[test]
+    fn new_boost_has_moved() {
+        let mint = Pubkey::new_unique();
+        let latest = HashMap::from([(mint, 1)]);
+        assert!(has_moved(&HashMap::new(), &latest, 5));
+        assert!(!has_moved(&HashMap::new(), &HashMap::from([(mint, 0)]), 5));
+    }
+}
@@ -0,0 +1,50 @@
+use std::{fs::File, io::BufReader};
+
+use rustls::{Certificate, PrivateKey, ServerConfig};
+
+use crate::{config::TlsConfig, error::Error};
+
+/// Reads the TLS_CERT and TLS_KEY pem files into a server config,
+/// for terminating tls in the server rather than behind a reverse proxy.
+/// Errors if either file can't be read.
+pub fn server_config(tls: &TlsConfig) -> Result<ServerConfig, Error> {
+    let certs = read_certs(tls.cert_path.as_str())?;
+    let key = read_key(tls.key_path.as_str())?;
+    let config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|err| Error::Internal(format!("invalid tls cert or key: {}", err)))?;
+    Ok(config)
+}
+
+fn read_certs(path: &str) -> Result<Vec<Certificate>, Error> {
+    let mut reader = open(path)?;
+    let certs = rustls_pemfile::certs(&mut reader)
+        .map_err(|err| Error::Internal(format!("failed to parse tls cert {}: {}", path, err)))?;
+    if certs.is_empty() {
+        return Err(Error::Internal(format!("no certs found in {}", path)));
+    }
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+fn read_key(path: &str) -> Result<PrivateKey, Error> {
+    let mut reader = open(path)?;
+    let items = rustls_pemfile::read_all(&mut reader)
+        .map_err(|err| Error::Internal(format!("failed to parse tls key {}: {}", path, err)))?;
+    items
+        .into_iter()
+        .find_map(|item| match item {
+            rustls_pemfile::Item::PKCS8Key(key)
+            | rustls_pemfile::Item::RSAKey(key)
+            | rustls_pemfile::Item::ECKey(key) => Some(PrivateKey(key)),
+            _ => None,
+        })
+        .ok_or(Error::Internal(format!("no private key found in {}", path)))
+}
+
+fn open(path: &str) -> Result<BufReader<File>, Error> {
+    let file = File::open(path)
+        .map_err(|err| Error::Internal(format!("failed to read {}: {}", path, err)))?;
+    Ok(BufReader::new(file))
+}
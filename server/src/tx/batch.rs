@@ -0,0 +1,70 @@
+/// Packs a stream of items into fixed-size batches,
+/// e.g. instructions into transactions up to the account limit.
+pub struct Batch<T> {
+    size: usize,
+    buffer: Vec<T>,
+}
+
+impl<T> Batch<T> {
+    pub fn new(size: usize) -> Self {
+        let size = size.max(1);
+        Self {
+            size,
+            buffer: Vec::with_capacity(size),
+        }
+    }
+
+    /// Adds an item, returning the full batch once it reaches the batch size.
+    pub fn push(&mut self, item: T) -> Option<Vec<T>> {
+        self.buffer.push(item);
+        if self.buffer.len() >= self.size {
+            let full = std::mem::replace(&mut self.buffer, Vec::with_capacity(self.size));
+            return Some(full);
+        }
+        None
+    }
+
+    /// Returns the remainder, if any,
+    /// which must be flushed once the stream is exhausted.
+    pub fn finish(self) -> Option<Vec<T>> {
+        if self.buffer.is_empty() {
+            None
+        } else {
+            Some(self.buffer)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drain(items: impl Iterator<Item = u64>, size: usize) -> Vec<Vec<u64>> {
+        let mut batch = Batch::new(size);
+        let mut batches: Vec<Vec<u64>> = items.filter_map(|item| batch.push(item)).collect();
+        batches.extend(batch.finish());
+        batches
+    }
+
+    #[test]
+    fn remainder_is_flushed() {
+        let batches = drain(0..25, 10);
+        let sizes: Vec<usize> = batches.iter().map(|b| b.len()).collect();
+        assert_eq!(sizes, vec![10, 10, 5]);
+        let flat: Vec<u64> = batches.into_iter().flatten().collect();
+        assert_eq!(flat, (0..25).collect::<Vec<u64>>());
+    }
+
+    #[test]
+    fn exact_multiple_has_no_remainder() {
+        let batches = drain(0..20, 10);
+        assert_eq!(batches.len(), 2);
+        assert!(batches.iter().all(|b| b.len() == 10));
+    }
+
+    #[test]
+    fn fewer_than_batch_size_is_one_batch() {
+        assert_eq!(drain(0..3, 10), vec![vec![0, 1, 2]]);
+        assert!(drain(0..0, 10).is_empty());
+    }
+}
@@ -0,0 +1,119 @@
+use std::time::Duration;
+
+/// The weight given to each new sample in the smoothed latency.
+const ALPHA: f64 = 0.125;
+
+/// The weight given to each new sample in the smoothed deviation.
+const BETA: f64 = 0.25;
+
+/// Rolling estimate of the time it takes a submit to land,
+/// smoothed like a tcp round-trip time estimate.
+#[derive(Debug)]
+pub struct LatencyEstimate {
+    /// smoothed latency in seconds, none until the first sample
+    smoothed: Option<f64>,
+    /// smoothed mean deviation in seconds
+    deviation: f64,
+    /// the margin never drops below this many seconds
+    min_margin_secs: u64,
+}
+
+impl LatencyEstimate {
+    pub fn new(min_margin_secs: u64) -> Self {
+        Self {
+            smoothed: None,
+            deviation: 0.0,
+            min_margin_secs,
+        }
+    }
+
+    /// Records the observed latency of a landed submit.
+    pub fn record(&mut self, latency: Duration) {
+        let sample = latency.as_secs_f64();
+        match self.smoothed {
+            None => {
+                self.smoothed = Some(sample);
+                self.deviation = sample / 2.0;
+            }
+            Some(smoothed) => {
+                self.deviation = (1.0 - BETA) * self.deviation + BETA * (smoothed - sample).abs();
+                self.smoothed = Some((1.0 - ALPHA) * smoothed + ALPHA * sample);
+            }
+        }
+    }
+
+    /// The seconds to stop accepting contributions before the on-chain deadline,
+    /// the smoothed latency plus four deviations, rounded up.
+    pub fn margin_secs(&self) -> u64 {
+        self.estimate_secs().unwrap_or(0).max(self.min_margin_secs)
+    }
+
+    /// The seconds past the cutoff that late contributions may still be accepted for,
+    /// up to the requested grace.
+    /// The grace only comes out of the slack between the min margin and the latency estimate,
+    /// so the submit still has the estimated latency to land before the on-chain deadline,
+    /// and there is no grace until a submit has been observed.
+    pub fn grace_secs(&self, requested: u64) -> u64 {
+        match self.estimate_secs() {
+            Some(estimate) => requested.min(self.margin_secs().saturating_sub(estimate)),
+            None => 0,
+        }
+    }
+
+    fn estimate_secs(&self) -> Option<u64> {
+        self.smoothed
+            .map(|smoothed| (smoothed + 4.0 * self.deviation).ceil() as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn margin_defaults_to_min() {
+        let estimate = LatencyEstimate::new(5);
+        assert_eq!(estimate.margin_secs(), 5);
+    }
+
+    #[test]
+    fn grace_is_bounded_by_slack() {
+        let mut estimate = LatencyEstimate::new(5);
+        // no observed submits, no grace
+        assert_eq!(estimate.grace_secs(2), 0);
+        for _ in 0..20 {
+            estimate.record(Duration::from_millis(2_500));
+        }
+        // a steady 2.5s latency leaves 2s of slack in the 5s margin
+        assert_eq!(estimate.estimate_secs(), Some(3));
+        assert_eq!(estimate.grace_secs(0), 0);
+        assert_eq!(estimate.grace_secs(1), 1);
+        assert_eq!(estimate.grace_secs(2), 2);
+        assert_eq!(estimate.grace_secs(3), 2);
+        // the cutoff plus grace never passes the deadline less the latency estimate
+        let cutoff = 60 - estimate.margin_secs();
+        assert!(cutoff + estimate.grace_secs(u64::MAX) <= 60 - 3);
+    }
+
+    #[test]
+    fn grace_vanishes_when_latency_fills_margin() {
+        let mut estimate = LatencyEstimate::new(5);
+        for _ in 0..5 {
+            estimate.record(Duration::from_secs(8));
+        }
+        assert_eq!(estimate.grace_secs(2), 0);
+    }
+
+    #[test]
+    fn margin_adapts_to_latency_spike() {
+        let mut estimate = LatencyEstimate::new(5);
+        for _ in 0..20 {
+            estimate.record(Duration::from_secs(1));
+        }
+        assert_eq!(estimate.margin_secs(), 5);
+        for _ in 0..5 {
+            estimate.record(Duration::from_secs(8));
+        }
+        assert!(estimate.margin_secs() > 8);
+    }
+}
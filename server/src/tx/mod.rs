@@ -1,2 +1,4 @@
+pub mod batch;
+pub mod latency;
 pub mod submit;
 pub mod validate;
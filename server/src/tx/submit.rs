@@ -1,30 +1,76 @@
-use solana_client::nonblocking::rpc_client::RpcClient;
+use rand::Rng;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
     compute_budget::ComputeBudgetInstruction,
     instruction::Instruction,
-    signature::{Keypair, Signature},
-    signer::Signer,
-    transaction::Transaction,
+    signature::Signature,
+    transaction::{Transaction, TransactionError},
 };
 
-use crate::error::Error;
+use crate::{error::Error, rpc::Rpc, signer::OperatorSigner};
 
+/// Parameters for retrying a transaction with exponential backoff.
+#[derive(Clone, Copy, Debug)]
+pub struct Retry {
+    /// The max number of retries after the first attempt.
+    pub max_retries: u32,
+
+    /// The delay before the first retry, doubled on each subsequent retry.
+    pub base_delay_ms: u64,
+
+    /// The max random delay added to each retry.
+    pub max_jitter_ms: u64,
+}
+
+impl Retry {
+    fn delay(&self, retry: u32) -> tokio::time::Duration {
+        let backoff = self
+            .base_delay_ms
+            .saturating_mul(2u64.saturating_pow(retry));
+        let jitter = rand::thread_rng().gen_range(0..=self.max_jitter_ms);
+        tokio::time::Duration::from_millis(backoff.saturating_add(jitter))
+    }
+}
+
+/// Submits and confirms the instructions, retrying with exponential backoff.
+/// Each attempt fetches a new blockhash, since an expired blockhash
+/// is a common cause of failure.
 pub async fn submit_and_confirm_instructions(
-    signer: &Keypair,
-    rpc_client: &RpcClient,
+    signer: &dyn OperatorSigner,
+    rpc_client: &Rpc,
     ixs: &[Instruction],
     cu_limit: u32,
     cu_price: u64,
+    retry: Retry,
 ) -> Result<Signature, Error> {
-    let sig = submit_instructions(signer, rpc_client, ixs, cu_limit, cu_price).await?;
-    confirm_transaction(rpc_client, &sig).await?;
-    Ok(sig)
+    let mut retries = 0;
+    loop {
+        let res = match submit_instructions(signer, rpc_client, ixs, cu_limit, cu_price).await {
+            Ok(sig) => confirm_transaction(rpc_client, &sig).await.map(|_| sig),
+            Err(err) => Err(err),
+        };
+        match res {
+            Ok(sig) => return Ok(sig),
+            Err(err) if is_non_retryable(&err) => {
+                return Err(Error::NonRetryable(format!("{:?}", err)));
+            }
+            Err(err) => {
+                if retries >= retry.max_retries {
+                    return Err(Error::RetriesExhausted(format!("{:?}", err)));
+                }
+                let delay = retry.delay(retries);
+                log::info!("{:?}", err);
+                log::info!("retrying transaction in {:?}", delay);
+                tokio::time::sleep(delay).await;
+                retries += 1;
+            }
+        }
+    }
 }
 
 pub async fn submit_instructions(
-    signer: &Keypair,
-    rpc_client: &RpcClient,
+    signer: &dyn OperatorSigner,
+    rpc_client: &Rpc,
     ixs: &[Instruction],
     cu_limit: u32,
     cu_price: u64,
@@ -35,12 +81,12 @@ pub async fn submit_instructions(
     let final_ixs = [final_ixs, ixs].concat();
     let hash = rpc_client.get_latest_blockhash().await?;
     let mut tx = Transaction::new_with_payer(final_ixs.as_slice(), Some(&signer.pubkey()));
-    tx.sign(&[signer], hash);
+    crate::signer::sign_transaction(signer, &mut tx, hash).await?;
     rpc_client.send_transaction(&tx).await.map_err(From::from)
 }
 
 pub async fn submit_and_confirm_transaction(
-    rpc_client: &RpcClient,
+    rpc_client: &Rpc,
     tx: &Transaction,
 ) -> Result<Signature, Error> {
     let max_retries = 5;
@@ -68,7 +114,7 @@ pub async fn submit_and_confirm_transaction(
     ))
 }
 
-async fn confirm_transaction(rpc_client: &RpcClient, sig: &Signature) -> Result<(), Error> {
+async fn confirm_transaction(rpc_client: &Rpc, sig: &Signature) -> Result<(), Error> {
     // Confirm the transaction with retries
     let max_retries = 10;
     let mut retries = 0;
@@ -89,3 +135,16 @@ async fn confirm_transaction(rpc_client: &RpcClient, sig: &Signature) -> Result<
     }
     Ok(())
 }
+
+/// Errors that will fail the same way no matter how many times we retry.
+fn is_non_retryable(err: &Error) -> bool {
+    match err {
+        Error::SolanaClient(err) => matches!(
+            err.get_transaction_error(),
+            Some(TransactionError::InsufficientFundsForFee)
+                | Some(TransactionError::InsufficientFundsForRent { .. })
+                | Some(TransactionError::AccountNotFound)
+        ),
+        _ => false,
+    }
+}
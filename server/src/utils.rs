@@ -1,27 +1,96 @@
-use std::env;
-
 use actix_cors::Cors;
 use actix_web::http::header;
+use solana_sdk::hash::Hash;
 
-use crate::error::Error;
+/// The origins allowed by cors, read once at startup.
+/// ALLOWED_ORIGINS lists them, or DEV_CORS=1 allows any origin instead.
+/// If neither is set all cross-origin requests are denied.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AllowedOrigins {
+    /// Any origin, for local development only.
+    Any,
+    /// Only the listed origins, none if empty.
+    List(Vec<String>),
+}
 
-pub fn create_cors() -> Cors {
-    Cors::default()
-        .allowed_origin_fn(|_origin, _req_head| {
-            // origin.as_bytes().ends_with(b"ore.supply") || // Production origin
-            // origin == "http://localhost:8080" // Local development origin
-            true
-        })
-        .allowed_methods(vec!["GET", "POST"]) // Methods you want to allow
+pub fn create_cors(allowed_origins: &AllowedOrigins) -> Cors {
+    let cors = match allowed_origins {
+        AllowedOrigins::Any => Cors::default().allowed_origin_fn(|_origin, _req_head| true),
+        AllowedOrigins::List(origins) => origins
+            .iter()
+            .fold(Cors::default(), |cors, origin| cors.allowed_origin(origin)),
+    };
+    cors.allowed_methods(vec!["GET", "POST"]) // Methods you want to allow
         .allowed_headers(vec![header::AUTHORIZATION, header::ACCEPT])
         .allowed_header(header::CONTENT_TYPE)
         .max_age(3600)
 }
 
-pub fn try_env_var(name: &str) -> Result<String, Error> {
-    env::var(name).map_err(|e| Error::StdEnv(name.to_string(), e))
+/// Parses a comma-separated list of origins, e.g. "https://ore.supply,http://localhost:8080".
+/// Each origin must be a scheme and host (with optional port) without a path.
+pub fn parse_allowed_origins(str: &str) -> Result<Vec<String>, String> {
+    str.split(',')
+        .map(|origin| origin.trim())
+        .filter(|origin| !origin.is_empty())
+        .map(|origin| {
+            let host = origin
+                .strip_prefix("https://")
+                .or_else(|| origin.strip_prefix("http://"));
+            match host {
+                Some(host) if !host.is_empty() && !host.contains('/') => Ok(origin.to_string()),
+                _ => Err(format!("invalid: {}", origin)),
+            }
+        })
+        .collect()
+}
+
+/// The genesis hashes of the public clusters.
+const CLUSTERS: [(&str, &str); 3] = [
+    (
+        "mainnet-beta",
+        "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d",
+    ),
+    ("devnet", "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG"),
+    ("testnet", "4uhcVJyU9pJkvQyS88uRDiswHXSCkY3zQawwpjk2NsNY"),
+];
+
+/// The public cluster with the genesis hash, unknown for local or private clusters.
+pub fn cluster(genesis_hash: Option<&Hash>) -> &'static str {
+    let Some(genesis_hash) = genesis_hash else {
+        return "unknown";
+    };
+    let genesis_hash = genesis_hash.to_string();
+    CLUSTERS
+        .iter()
+        .find(|(_, hash)| *hash == genesis_hash)
+        .map(|(cluster, _)| *cluster)
+        .unwrap_or("unknown")
 }
 
-pub fn env_var_or_panic(name: &str) -> String {
-    try_env_var(name).expect(&format!("Required environment variable {} not set", name))
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn parses_allowed_origins() {
+        let origins = parse_allowed_origins("https://ore.supply, http://localhost:8080,").unwrap();
+        assert_eq!(origins, vec!["https://ore.supply", "http://localhost:8080"]);
+    }
+
+    #[test]
+    fn detects_cluster_from_genesis_hash() {
+        let devnet = Hash::from_str("EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG").unwrap();
+        assert_eq!(cluster(Some(&devnet)), "devnet");
+        assert_eq!(cluster(Some(&Hash::new_unique())), "unknown");
+        assert_eq!(cluster(None), "unknown");
+    }
+
+    #[test]
+    fn rejects_malformed_origins() {
+        assert!(parse_allowed_origins("ore.supply").is_err());
+        assert!(parse_allowed_origins("https://ore.supply/path").is_err());
+        assert!(parse_allowed_origins("https://").is_err());
+    }
+}
@@ -0,0 +1,180 @@
+use std::time::Instant;
+
+use ore_pool_types::{Challenge, ContributePayload};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{error::Error, metrics, scoring::Curve};
+
+/// The outcome of the cpu-bound checks of a contribution's solution,
+/// in the order they are checked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    BelowDifficulty(u32),
+    BelowMinScore(u64),
+    InvalidDigest,
+    Valid { score: u64 },
+}
+
+/// Runs signature verification and the drillx digest check on the blocking thread pool,
+/// so a burst of contributions doesn't stall the async runtime.
+/// At most `workers` contributions are validated at once,
+/// the rest wait their turn in the handler.
+pub struct Validator {
+    permits: tokio::sync::Semaphore,
+    /// the curve the score of a solution is checked against the min score with
+    score_curve: Curve,
+}
+
+impl Validator {
+    pub fn new(workers: usize, score_curve: Curve) -> Self {
+        log::info!("validation workers: {}", workers);
+        Self {
+            permits: tokio::sync::Semaphore::new(workers),
+            score_curve,
+        }
+    }
+
+    /// Verifies the signature, bound to this pool and challenge so it can't be replayed elsewhere.
+    /// Checked on its own, so that the authority is known to be genuine before it's rate limited.
+    pub async fn verify_signature(
+        &self,
+        payload: &ContributePayload,
+        pool: Pubkey,
+        challenge: Challenge,
+    ) -> Result<bool, Error> {
+        let payload = copy(payload);
+        self.run(move || payload.verify(&pool, challenge.lash_hash_at))
+            .await
+    }
+
+    /// Checks the solution of a contribution whose signature has been verified.
+    pub async fn validate(
+        &self,
+        payload: &ContributePayload,
+        challenge: Challenge,
+    ) -> Result<Verdict, Error> {
+        let payload = copy(payload);
+        let started_at = Instant::now();
+        let score_curve = self.score_curve;
+        let verdict = self
+            .run(move || verdict(&payload, &challenge, score_curve))
+            .await?;
+        let elapsed = started_at.elapsed();
+        log::debug!("validation latency: {:?}", elapsed);
+        metrics::observe_validation(elapsed);
+        Ok(verdict)
+    }
+
+    async fn run<T: Send + 'static>(
+        &self,
+        check: impl FnOnce() -> T + Send + 'static,
+    ) -> Result<T, Error> {
+        let _permit = self
+            .permits
+            .acquire()
+            .await
+            .map_err(|err| Error::Internal(format!("validation pool closed: {}", err)))?;
+        tokio::task::spawn_blocking(check)
+            .await
+            .map_err(|err| Error::Internal(format!("validation task failed: {}", err)))
+    }
+}
+
+fn copy(payload: &ContributePayload) -> ContributePayload {
+    ContributePayload {
+        authority: payload.authority,
+        solution: payload.solution,
+        signature: payload.signature,
+    }
+}
+
+/// Checks the difficulty and score, and finally the digest,
+/// the most expensive check, only once everything else has passed.
+fn verdict(payload: &ContributePayload, challenge: &Challenge, score_curve: Curve) -> Verdict {
+    let solution = &payload.solution;
+    let difficulty = solution.to_hash().difficulty();
+    if difficulty < (challenge.min_difficulty as u32) {
+        return Verdict::BelowDifficulty(difficulty);
+    }
+    let score = score_curve.score(difficulty);
+    if score < challenge.min_score {
+        return Verdict::BelowMinScore(score);
+    }
+    if !drillx::is_valid_digest(&challenge.challenge, &solution.n, &solution.d) {
+        return Verdict::InvalidDigest;
+    }
+    Verdict::Valid { score }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::signature::{Keypair, Signature, Signer};
+
+    use super::*;
+
+    fn challenge(min_difficulty: u64) -> Challenge {
+        Challenge {
+            challenge: [0; 32],
+            lash_hash_at: 100,
+            min_difficulty,
+            cutoff_time: 0,
+            min_score: 0,
+            epoch: 0,
+        }
+    }
+
+    fn signed_payload(pool: &Pubkey, challenge: &Challenge) -> ContributePayload {
+        let keypair = Keypair::new();
+        let solution = drillx::Solution::new([0; 16], [0; 8]);
+        let message = ContributePayload::message(pool, challenge.lash_hash_at, &solution);
+        ContributePayload {
+            authority: keypair.pubkey(),
+            solution,
+            signature: keypair.sign_message(message.as_slice()),
+        }
+    }
+
+    #[tokio::test]
+    async fn signature_is_bound_to_the_pool() {
+        let pool = Pubkey::new_unique();
+        let challenge = challenge(64);
+        let validator = Validator::new(1, Curve::Exponential);
+        let mut payload = signed_payload(&pool, &challenge);
+        assert!(validator
+            .verify_signature(&payload, pool, challenge)
+            .await
+            .unwrap());
+        assert!(!validator
+            .verify_signature(&payload, Pubkey::new_unique(), challenge)
+            .await
+            .unwrap());
+        payload.signature = Signature::default();
+        assert!(!validator
+            .verify_signature(&payload, pool, challenge)
+            .await
+            .unwrap());
+    }
+
+    #[test]
+    fn difficulty_is_checked_before_digest() {
+        let pool = Pubkey::new_unique();
+        let challenge = challenge(64);
+        let payload = signed_payload(&pool, &challenge);
+        let difficulty = payload.solution.to_hash().difficulty();
+        assert_eq!(
+            verdict(&payload, &challenge, Curve::Exponential),
+            Verdict::BelowDifficulty(difficulty)
+        );
+    }
+
+    #[test]
+    fn forged_digest_is_invalid() {
+        let pool = Pubkey::new_unique();
+        let challenge = challenge(0);
+        let payload = signed_payload(&pool, &challenge);
+        assert_eq!(
+            verdict(&payload, &challenge, Curve::Exponential),
+            Verdict::InvalidDigest
+        );
+    }
+}
@@ -4,7 +4,7 @@ use ore_pool_api::event::UnstakeEvent;
 use solana_sdk::pubkey::Pubkey;
 
 use crate::{
-    aggregator::Aggregator, database, error::Error, operator::Operator, utils::try_env_var,
+    aggregator::Aggregator, config::HeliusConfig, database, error::Error, operator::Operator,
 };
 
 const HELIUS_URL: &str = "https://api.helius.xyz";
@@ -72,7 +72,7 @@ pub struct EventMeta {
     pub log_messages: Vec<String>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Rewards {
     pub base: u64,
     pub boost_1: Option<ore_api::event::BoostEvent>,
@@ -82,10 +82,10 @@ pub struct Rewards {
 }
 
 impl Handle {
-    pub fn new() -> Result<Self, Error> {
-        let helius_auth_token = helius_auth_token()?;
-        let s = Self { helius_auth_token };
-        Ok(s)
+    pub fn new(helius: &HeliusConfig) -> Self {
+        Self {
+            helius_auth_token: helius.auth_token.clone(),
+        }
     }
 
     pub async fn share_account(
@@ -303,19 +303,14 @@ impl Handle {
 
 impl Client {
     /// create new client for listening to share account state changes
-    pub fn new_stake() -> Result<Self, Error> {
-        let helius_api_key = helius_api_key()?;
-        let helius_webhook_id = helius_webhook_id()?;
-        let helius_webhook_url = helius_webhook_url()?;
-        let helius_auth_token = helius_auth_token()?;
-        let s = Self {
+    pub fn new_stake(helius: &HeliusConfig) -> Self {
+        Self {
             http_client: reqwest::Client::new(),
-            helius_api_key,
-            helius_webhook_id,
-            helius_webhook_url,
-            helius_auth_token,
-        };
-        Ok(s)
+            helius_api_key: helius.api_key.clone(),
+            helius_webhook_id: helius.webhook_id.clone(),
+            helius_webhook_url: helius.webhook_url.clone(),
+            helius_auth_token: helius.auth_token.clone(),
+        }
     }
 
     /// puts entry into webhook
@@ -380,20 +375,3 @@ impl Client {
         Ok(resp)
     }
 }
-
-/// this the /webhook path that your server exposes to helius.
-fn helius_webhook_url() -> Result<String, Error> {
-    try_env_var("HELIUS_WEBHOOK_URL")
-}
-
-fn helius_api_key() -> Result<String, Error> {
-    try_env_var("HELIUS_API_KEY")
-}
-
-fn helius_auth_token() -> Result<String, Error> {
-    try_env_var("HELIUS_AUTH_TOKEN")
-}
-
-fn helius_webhook_id() -> Result<String, Error> {
-    try_env_var("HELIUS_WEBHOOK_ID")
-}
@@ -9,6 +9,25 @@ use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Signature, transaction::
 pub struct RegisterPayload {
     /// The authority of the member account sending the payload.
     pub authority: Pubkey,
+
+    /// Optional signature proving control of the authority.
+    /// If present, must be a valid signature of [`RegisterPayload::message`].
+    #[serde(default)]
+    pub signature: Option<Signature>,
+}
+
+impl RegisterPayload {
+    /// The domain tag prefixed to the register message.
+    pub const DOMAIN: &'static [u8] = b"ore-pool-register";
+
+    /// The message a member signs to register with a pool:
+    /// the domain tag and the pool address, concatenated.
+    pub fn message(pool: &Pubkey) -> Vec<u8> {
+        let mut message = Vec::with_capacity(Self::DOMAIN.len() + 32);
+        message.extend_from_slice(Self::DOMAIN);
+        message.extend_from_slice(pool.as_ref());
+        message
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -25,10 +44,130 @@ pub struct ContributePayload {
     /// The solution submitted.
     pub solution: Solution,
 
-    /// Must be a valid signature of the solution
+    /// Must be a valid signature of the domain-separated contribute message,
+    /// binding the solution to a specific pool and challenge.
+    /// See [`ContributePayload::message`].
     pub signature: Signature,
 }
 
+impl ContributePayload {
+    /// The domain tag prefixed to every contribute message.
+    pub const DOMAIN: &'static [u8] = b"ore-pool-contribute";
+
+    /// The message a member signs to contribute a solution:
+    /// the domain tag, the pool address, the challenge last-hash-at (le bytes),
+    /// and the solution bytes, concatenated.
+    pub fn message(pool: &Pubkey, last_hash_at: i64, solution: &Solution) -> Vec<u8> {
+        let mut message = Vec::with_capacity(Self::DOMAIN.len() + 32 + 8 + 24);
+        message.extend_from_slice(Self::DOMAIN);
+        message.extend_from_slice(pool.as_ref());
+        message.extend_from_slice(&last_hash_at.to_le_bytes());
+        message.extend_from_slice(&solution.to_bytes());
+        message
+    }
+
+    /// Whether or not the signature is valid for this pool and challenge.
+    pub fn verify(&self, pool: &Pubkey, last_hash_at: i64) -> bool {
+        let message = Self::message(pool, last_hash_at, &self.solution);
+        self.signature
+            .verify(&self.authority.to_bytes(), message.as_slice())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PoolAddressQuery {
+    /// Whether or not to verify that the pool account exists on-chain.
+    pub verify: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BalanceHistoryQuery {
+    /// The cursor returned with the previous page, omitted for the first page.
+    pub cursor: Option<i64>,
+
+    /// The max number of events to return.
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DailyEarningsQuery {
+    /// The first day, inclusive, as YYYY-MM-DD.
+    pub from: String,
+
+    /// The last day, inclusive, as YYYY-MM-DD.
+    pub to: String,
+
+    /// The IANA timezone the days are in, e.g. America/New_York (default UTC).
+    pub tz: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HealthQuery {
+    /// Whether or not to check the rpc and db connections.
+    pub deep: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LeaderboardQuery {
+    /// The max number of entries to return.
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EstimateQuery {
+    /// The difficulty of the hypothetical contribution.
+    pub difficulty: Option<u32>,
+
+    /// The hashrate (in hashes per second) of the prospective miner,
+    /// used to derive the expected difficulty if no difficulty is given.
+    pub hashrate: Option<u64>,
+}
+
+/// The payload of the /admin/min-difficulty request.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MinDifficultyPayload {
+    /// The min difficulty to raise the protocol min difficulty to,
+    /// or None to clear the override.
+    pub min_difficulty: Option<u64>,
+}
+
+/// Which access list an authority is on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessList {
+    /// Allowed to register and contribute when the pool is allowlist-only.
+    Allow,
+
+    /// Never allowed to register or contribute.
+    Deny,
+}
+
+/// The payload of the PUT /admin/access request,
+/// and an entry in the response from the GET /admin/access request.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccessEntry {
+    /// The member authority.
+    pub authority: Pubkey,
+
+    /// The list the authority is on.
+    pub list: AccessList,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MembersQuery {
+    /// Only members with this active status, any if omitted.
+    pub active: Option<bool>,
+
+    /// Only members with at least this total balance.
+    pub min_balance: Option<u64>,
+
+    /// The cursor returned with the previous page, omitted for the first page.
+    pub cursor: Option<i64>,
+
+    /// The max number of members to return.
+    pub limit: Option<usize>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct UpdateBalancePayload {
     /// The authority of the member account sending the payload.
@@ -42,6 +181,45 @@ pub struct UpdateBalancePayload {
     pub hash: Hash,
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+pub struct DeregisterPayload {
+    /// The authority of the member account sending the payload.
+    /// The member must have landed the deregister instruction on-chain first.
+    pub authority: Pubkey,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AutoCompoundPayload {
+    /// The authority of the member account sending the payload.
+    pub authority: Pubkey,
+
+    /// Whether or not the member's rewards should be restaked into their ORE share.
+    pub enabled: bool,
+
+    /// The unix timestamp (in seconds) the payload was signed at,
+    /// so that an old payload can't be replayed to flip the preference back.
+    pub timestamp: i64,
+
+    /// A signature of [`AutoCompoundPayload::message`] by the authority.
+    pub signature: Signature,
+}
+
+impl AutoCompoundPayload {
+    /// The domain tag prefixed to the auto-compound message.
+    pub const DOMAIN: &'static [u8] = b"ore-pool-auto-compound";
+
+    /// The message a member signs to set their auto-compound preference:
+    /// the domain tag, the pool address, the preference, and the timestamp, concatenated.
+    pub fn message(pool: &Pubkey, enabled: bool, timestamp: i64) -> Vec<u8> {
+        let mut message = Vec::with_capacity(Self::DOMAIN.len() + 32 + 1 + 8);
+        message.extend_from_slice(Self::DOMAIN);
+        message.extend_from_slice(pool.as_ref());
+        message.push(enabled as u8);
+        message.extend_from_slice(&timestamp.to_le_bytes());
+        message
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct RegisterStakerPayload {
     /// The authority of the member account sending the payload.
@@ -61,6 +239,14 @@ pub struct PoolAddress {
 
     /// The bump returned when deriving the pda.
     pub bump: u8,
+
+    /// Whether or not the pool account exists on-chain, if verified.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exists: Option<bool>,
+
+    /// The total number of members in the pool, if verified and it exists.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_members: Option<u64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Copy, Clone)]
@@ -76,6 +262,17 @@ pub struct Challenge {
 
     // The cutoff time to stop accepting contributions.
     pub cutoff_time: u64,
+
+    /// The minimum score (per the operator's score curve) accepted by the pool operator.
+    /// Solutions that satisfy the on-chain min difficulty may still fall below this floor.
+    pub min_score: u64,
+
+    /// Incremented each time the pool moves on to a new challenge,
+    /// so that a client can discard a challenge older than one it has already seen,
+    /// e.g. a stale response cached by a proxy. Seeded from the round's `last_hash_at`
+    /// when the server starts, so that it keeps increasing across restarts.
+    #[serde(default)]
+    pub epoch: u64,
 }
 
 /// The member record that sits in the operator database
@@ -105,6 +302,18 @@ pub struct Member {
 
     /// Whether or not this member's on-chain balance is in sync with the operator db balance.
     pub is_synced: bool,
+
+    /// Whether or not this member is active, false once deregistered.
+    pub is_active: bool,
+
+    /// Whether or not this member's rewards are restaked into their ORE share each epoch.
+    #[serde(default)]
+    pub auto_compound: bool,
+
+    /// The unix timestamp this member registered with the operator,
+    /// None if registered before registrations were recorded.
+    #[serde(default)]
+    pub registered_at: Option<i64>,
 }
 
 /// The staker record that sits in the operator database
@@ -135,6 +344,360 @@ pub struct MemberChallenge {
 
     /// The number of total members to divide the nonce space by.
     pub num_total_members: u64,
+
+    /// The operator commission in % percentage at the current total score.
+    /// May change within a challenge if the operator uses commission tiers.
+    pub operator_commission: u64,
+
+    /// The staker commission in % percentage.
+    pub staker_commission: u64,
+
+    /// Whether or not contributions are paused after repeated submit failures.
+    /// Contributions are rejected with `pool_paused` until a submit lands again.
+    #[serde(default)]
+    pub paused: bool,
+}
+
+/// The response from the /estimate request.
+/// An estimate only, from the average of recently distributed rounds,
+/// actual rewards vary with the hash-power of the pool and the score caps.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RewardEstimate {
+    /// The difficulty the estimate is for.
+    pub difficulty: u32,
+
+    /// The score of a contribution of the difficulty.
+    pub score: u64,
+
+    /// The average total score of the sampled rounds.
+    pub average_round_score: u64,
+
+    /// The average rewards paid out to miners per sampled round.
+    pub average_miner_rewards: u64,
+
+    /// The estimated reward per round for a contribution of the difficulty.
+    pub estimated_reward: u64,
+
+    /// The number of recent rounds the estimate is averaged over.
+    pub rounds_sampled: usize,
+}
+
+/// The stake backing a boost, per boost mint in the response from the /stake request.
+/// From the operator's cached snapshot of the stake, refreshed each stake epoch.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BoostStake {
+    /// The total staked in the boost.
+    pub total_staked: u64,
+
+    /// The number of distinct stakers with a nonzero stake.
+    pub num_stakers: usize,
+
+    /// The staker commission of the boost in % percentage.
+    pub staker_commission: u64,
+}
+
+/// An entry in the response from the /leaderboard request.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    /// The authority of the member.
+    pub member: Pubkey,
+
+    /// The score of the member's contribution for the current challenge.
+    pub score: u64,
+
+    /// The difficulty of the member's contribution for the current challenge.
+    pub difficulty: u32,
+
+    /// The member's score as a percentage of the total score.
+    pub percentage: f64,
+}
+
+/// The response from the /member/{authority}/pending request.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PendingContribution {
+    /// Whether or not the member has contributed to the current challenge.
+    pub contributed: bool,
+
+    /// The score of the member's contribution.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<u64>,
+
+    /// Whether or not the member's contribution is the current best solution.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub winner: Option<bool>,
+}
+
+/// The response from the contribute request,
+/// proof that the pool accepted the member's contribution to a round.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ContributionReceipt {
+    /// The authority of the member that contributed.
+    pub authority: Pubkey,
+
+    /// Foreign key to the ORE proof account, identifies the round.
+    pub last_hash_at: i64,
+
+    /// The challenge epoch the contribution was accepted in, see [`Challenge::epoch`].
+    #[serde(default)]
+    pub epoch: u64,
+
+    /// The score the contribution was accepted with.
+    pub score: u64,
+
+    /// A signature of [`ContributionReceipt::message`] by the operator keypair.
+    pub signature: Signature,
+}
+
+impl ContributionReceipt {
+    /// The domain tag prefixed to the receipt message.
+    pub const DOMAIN: &'static [u8] = b"ore-pool-receipt";
+
+    /// The message the operator signs to accept a contribution:
+    /// the domain tag, the member authority, the challenge last-hash-at (le bytes),
+    /// the challenge epoch (le bytes), and the score (le bytes), concatenated.
+    pub fn message(authority: &Pubkey, last_hash_at: i64, epoch: u64, score: u64) -> Vec<u8> {
+        let mut message = Vec::with_capacity(Self::DOMAIN.len() + 32 + 8 + 8 + 8);
+        message.extend_from_slice(Self::DOMAIN);
+        message.extend_from_slice(authority.as_ref());
+        message.extend_from_slice(&last_hash_at.to_le_bytes());
+        message.extend_from_slice(&epoch.to_le_bytes());
+        message.extend_from_slice(&score.to_le_bytes());
+        message
+    }
+
+    /// Whether or not the receipt was signed by the operator.
+    pub fn verify(&self, operator: &Pubkey) -> bool {
+        let message = Self::message(&self.authority, self.last_hash_at, self.epoch, self.score);
+        self.signature
+            .verify(&operator.to_bytes(), message.as_slice())
+    }
+}
+
+/// The response from the /member/{authority}/nonce-range request.
+#[derive(Debug, Serialize, Deserialize, Copy, Clone)]
+pub struct NonceRange {
+    /// The challenge the range is assigned for.
+    pub last_hash_at: i64,
+
+    /// The number of members the nonce space is divided between,
+    /// as of the last reset.
+    pub num_members: u64,
+
+    /// The first nonce of the range, inclusive.
+    pub start: u64,
+
+    /// The end of the range, exclusive.
+    pub end: u64,
+}
+
+/// The response from the /challenge/{last_hash_at} request,
+/// a closed round retained in the challenge history.
+#[derive(Debug, Serialize, Deserialize, Copy, Clone)]
+pub struct RoundSummary {
+    /// The challenge parameters of the round.
+    pub challenge: Challenge,
+
+    /// The total difficulty score of all the contributions to the round.
+    pub total_score: u64,
+
+    /// The difficulty of the best solution submitted, if any.
+    pub winner_difficulty: Option<u32>,
+
+    /// The contributions hash of the round's attestation block, if any contributions were made.
+    pub attestation: Option<[u8; 32]>,
+}
+
+/// An entry in the response from the /admin/jail request.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JailedMember {
+    /// The authority of the jailed member.
+    pub authority: Pubkey,
+
+    /// The seconds remaining until the member is released.
+    pub remaining_secs: u64,
+}
+
+/// An entry in the response from the /admin/forensics request,
+/// the metadata of an accepted contribution.
+#[derive(Debug, Serialize, Deserialize, Copy, Clone)]
+pub struct ForensicsEntry {
+    /// The authority of the contributing member.
+    pub member: Pubkey,
+
+    /// The challenge the contribution was made against.
+    pub last_hash_at: i64,
+
+    /// The nonce of the solution.
+    pub nonce: u64,
+
+    /// The difficulty of the solution.
+    pub difficulty: u32,
+
+    /// When the contribution was accepted (unix timestamp in milliseconds).
+    pub received_at: u64,
+
+    /// The milliseconds since the challenge started at which the contribution was accepted.
+    pub offset_ms: u64,
+}
+
+/// An entry in the response from the /admin/timeline/{last_hash_at} request,
+/// when an accepted contribution arrived within its round.
+#[derive(Debug, Serialize, Deserialize, Copy, Clone)]
+pub struct TimelineEntry {
+    /// The authority of the contributing member.
+    pub member: Pubkey,
+
+    /// The milliseconds since the round started at which the contribution arrived,
+    /// measured on a monotonic clock.
+    pub received_at: u64,
+
+    /// The nonce of the solution.
+    pub nonce: u64,
+
+    /// The difficulty score of the solution.
+    pub score: u64,
+}
+
+/// A message pushed over the /member/{authority}/balance/ws websocket
+/// when the member's balance is attributed.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct BalanceNotification {
+    /// Foreign key to the ORE proof account, identifies the round.
+    pub last_hash_at: i64,
+
+    /// The balance earned in the round.
+    pub amount: u64,
+
+    /// The total balance attributed to the member, including not yet synced on-chain.
+    pub total_balance: u64,
+}
+
+/// A member's earnings for a single round.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BalanceEvent {
+    /// Foreign key to the ORE proof account, identifies the round.
+    pub last_hash_at: i64,
+
+    /// The balance earned in the round.
+    pub amount: u64,
+}
+
+/// A member's earnings for a single calendar day,
+/// from the response to the /member/{authority}/daily request.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DailyEarnings {
+    /// The day, as YYYY-MM-DD in the requested timezone.
+    pub date: String,
+
+    /// The balance earned over the day.
+    pub amount: u64,
+}
+
+/// The response from the /member/{authority}/history request.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BalanceHistory {
+    /// The balance events, newest first.
+    pub events: Vec<BalanceEvent>,
+
+    /// The cursor for the next page, none past the end.
+    pub next_cursor: Option<i64>,
+}
+
+/// The response from the /admin/members request,
+/// a page of members in ascending id order.
+/// The total number of matching members is returned in the x-total-count header.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MemberPage {
+    /// The members, by ascending id.
+    pub members: Vec<Member>,
+
+    /// The cursor for the next page, none past the end.
+    pub next_cursor: Option<i64>,
+}
+
+/// The response from the /health?deep=true request.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Health {
+    /// Whether or not the rpc responded in time.
+    pub rpc: bool,
+
+    /// Whether or not the db responded in time.
+    pub db: bool,
+}
+
+/// The response from the /version request,
+/// which build and program an operator is running.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Version {
+    /// The server crate version.
+    pub version: String,
+
+    /// The git commit the server was built from, if provided at build time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_commit: Option<String>,
+
+    /// The pool program id.
+    pub program_id: Pubkey,
+
+    /// The genesis hash of the connected rpc, if it responded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub genesis_hash: Option<Hash>,
+
+    /// The cluster detected from the genesis hash:
+    /// mainnet-beta, devnet, testnet, or unknown.
+    pub cluster: String,
+}
+
+/// A line of the attestation block.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AttestationEntry {
+    /// The authority of the contributing member.
+    pub member: Pubkey,
+
+    /// The hex encoded digest of the member's solution.
+    pub digest: String,
+
+    /// The nonce of the member's solution.
+    pub nonce: u64,
+
+    /// The milliseconds since the round started at which the contribution arrived,
+    /// only attested if the operator opts in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub received_at: Option<u64>,
+}
+
+/// The response from the /attestation/{last_hash_at} request,
+/// while the round's contributions are still held in memory.
+/// The block is the [`Attestation::header`] line followed by one line per entry,
+/// "{member} {digest} {nonce}\n", sorted by member.
+/// The contributions hash is the sha3 hash of the block.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Attestation {
+    /// Foreign key to the ORE proof account, identifies the round.
+    pub last_hash_at: i64,
+
+    /// The version of the attestation block format, see [`Attestation::VERSION`].
+    /// Zero for an unversioned block.
+    #[serde(default)]
+    pub version: u32,
+
+    /// The entries of the attestation block, sorted by member.
+    pub entries: Vec<AttestationEntry>,
+
+    /// The contributions hash of the attestation block.
+    pub hash: [u8; 32],
+}
+
+impl Attestation {
+    /// The version of the attestation block format.
+    /// Blocks without a header are unversioned, and their contributions hash is
+    /// the sha3 hash of the block, or briefly the sha3 hash of the wrapping sum of each line's sha3 hash.
+    pub const VERSION: u32 = 2;
+
+    /// The first line of an attestation block, naming its version.
+    pub fn header() -> String {
+        format!("ore-pool-attestation v{}", Self::VERSION)
+    }
 }
 
 /// The response from the update-balance request.
@@ -145,4 +708,8 @@ pub struct BalanceUpdate {
 
     /// The transaction signature.
     pub signature: Signature,
+
+    /// The seconds left until the member may claim the balance (0 if it may claim now).
+    #[serde(default)]
+    pub claim_cooldown_remaining: u64,
 }